@@ -16,7 +16,8 @@
 //! removing them when blocking code is needed.
 //! - Add `maybe` attributes and specify feature conditions in the macro parameters to determine
 //! which variant of code should be generated.
-//! - Use `only_if` (or `remove_if`) to keep code in a specific variant when necessary.
+//! - Use `only_if` (or `remove_if`) to keep code in a specific variant when necessary, or just
+//!   `only_sync`/`only_async` for the variants with the default keys.
 //!
 //! The `maybe` procedural macro can be applied to the following code:
 //! - use declarations
@@ -157,11 +158,104 @@
 //!     }
 //!     ```
 //!
+//!     Inside the `content` macro, entries of grouped imports can be marked with `only_if` (or
+//!   other conditional attributes), which is not allowed by Rust elsewhere. Such entries are
+//!   moved to separate `use` items, and groups left empty are removed.
+//!
+//!     ```rust
+//!     maybe_async_cfg2::content!{
+//!     #[maybe_async_cfg2::maybe(
+//!         sync(feature="use_sync"),
+//!         async(feature="use_async")
+//!     )]
+//!     use crate::io::{Reader, #[maybe_async_cfg2::only_if(async)] AsyncTimeout};
+//!     } // content!
+//!     ```
+//!     After conversion:
+//!     ```rust
+//!     #[cfg(feature="use_sync")]
+//!     use crate::io::{Reader};
+//!     #[cfg(feature="use_async")]
+//!     use crate::io::{Reader};
+//!     #[cfg(feature="use_async")]
+//!     use crate::io::AsyncTimeout;
+//!     ```
+//!
+//!     The variants specified in the `default` attribute are used by the `maybe` macros which do
+//!   not define their own ones.
+//!
+//! - procedural macro **`define_preset`**
+//!
+//!     The `define_preset` macro defines a set of parameters once and generates a function-like
+//!   macro that wraps the items like `content` does, so the same variants can be shared across
+//!   many modules. It is a wrapper and not an attribute, since only procedural macro crates can
+//!   define attribute macros.
+//!
+//!     ```rust
+//!     maybe_async_cfg2::define_preset!(
+//!         pub(crate) variants(sync(feature="use_sync"), async(feature="use_async"))
+//!     );
+//!
+//!     variants! {
+//!     #[maybe_async_cfg2::maybe]
+//!     struct Struct;
+//!     } // variants!
+//!     ```
+//!     After conversion:
+//!     ```rust
+//!     #[cfg(feature="use_sync")]
+//!     struct StructSync;
+//!     #[cfg(feature="use_async")]
+//!     struct StructAsync;
+//!     ```
+//!
+//! - procedural macro **`guard`**
+//!
+//!     The `guard` macro emits `compile_error!` when several variants are enabled at once or none
+//!   of them is, e.g.
+//!   `maybe_async_cfg2::guard!(sync(feature="use_sync"), async(feature="use_async"));` in the
+//!   crate root.
+//!
+//! - procedural macro **`facade`**
+//!
+//!     The `facade` macro re-exports the enabled variants of the listed items under their original
+//!   names, e.g. `maybe_async_cfg2::facade!{ crate::client::Client, crate::client::run }` with
+//!   the variants from the crate-wide defaults.
+//!
+//! - crate-wide defaults in **`Cargo.toml`**
+//!
+//!     The `defaults` key of the `[package.metadata.maybe-async-cfg2]` section of `Cargo.toml`
+//!   specifies the parameters applied to every `maybe` macro in the crate, as `default` does
+//!   inside `content`. The variants of the defaults are used by the `maybe` macros which do not
+//!   define their own ones. The key has to be a single-line basic (`"..."`) or literal (`'...'`)
+//!   string in the section with this exact table header. Multi-line strings, dotted keys
+//!   (`maybe-async-cfg2.defaults = ".."`) and inline tables (`maybe-async-cfg2 = { .. }`) are
+//!   rejected with an error.
+//!
+//!     ```toml
+//!     [package.metadata.maybe-async-cfg2]
+//!     defaults = 'sync(feature="use_sync"), async(feature="use_async"), idents(Conn)'
+//!     ```
+//!
+//!     The expanded items include `Cargo.toml` with `include_str!`, so cargo expands them again
+//!   when it is edited.
+//!
+//! - environment variable **`MAYBE_ASYNC_FORCE_VARIANT`**
+//!
+//!     If set to the key of a variant (e.g. `MAYBE_ASYNC_FORCE_VARIANT=async`), all `maybe` macros
+//!   emit only this variant and without its `#[cfg(...)]` condition, regardless of the enabled
+//!   features. This is useful for local development and for tools that do not understand feature
+//!   flags. A key that an item has no variant for is an error, which lists the keys of its
+//!   variants. The expanded items read the variable with `option_env!`, so cargo rebuilds the
+//!   crate when it changes.
+//!
 //! ## Doctests
 //!     
 //! When writing doctests, they can be marked as applicable only in the corresponding code variant.
 //! To do this, specify `only_if(`_VARIANT_KEY_`)` in the doctest attributes. Then in all other
-//! variants, this doctest will be replaced with an empty string.
+//! variants, this doctest will be replaced with an empty string. The doc comments of nested items
+//! are filtered the same way: fields, enum variants and the methods of traits (whose default
+//! bodies are converted as any other code) and impl blocks.
 //!
 //! ```rust
 //! #[maybe_async_cfg2::maybe(
@@ -252,9 +346,14 @@ const DEFAULT_CRATE_NAME: &'static str = "maybe_async_cfg2";
 const MACRO_MAYBE_NAME: &'static str = "maybe";
 const MACRO_ONLY_IF_NAME: &'static str = "only_if";
 const MACRO_REMOVE_IF_NAME: &'static str = "remove_if";
+const MACRO_ONLY_SYNC_NAME: &str = "only_sync";
+const MACRO_ONLY_ASYNC_NAME: &str = "only_async";
 const MACRO_NOOP_NAME: &'static str = "noop";
 const MACRO_REMOVE_NAME: &'static str = "remove";
 const MACRO_DEFAULT_NAME: &'static str = "default";
+const MACRO_ASSOC_TYPE_NAME: &str = "assoc_type";
+const MACRO_RECEIVER_NAME: &str = "receiver";
+const MACRO_FIELD_TYPE_NAME: &str = "field_type";
 
 const STANDARD_MACROS: &'static [&'static str] = &[
     "dbg",
@@ -267,6 +366,10 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 
 /// Marks code that can be presented in several variants.
 ///
+/// The attribute can be applied to impl blocks, structs, enums, traits, functions, `use`
+/// declarations and modules. Other items are reported as errors; to keep them unchanged in all
+/// variants, leave them unmarked inside `content!`.
+///
 /// ### The `maybe` macro has the following parameters:
 ///
 /// - `disable`
@@ -278,15 +381,524 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///
 ///     The name of `maybe-async-cfg2` crate. If not set, `"maybe_async_cfg2"` will be used.
 ///
+/// - `default_variant`
+///
+///     The key of the variant that is also enabled when the conditions of all other variants are
+/// false, so that the crate does not compile to nothing when no feature is selected. The condition
+/// of the variant is extended with `not(any(...))` of the conditions of other variants; a variant
+/// without condition is left as is.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         default_variant="async",
+///     )]
+///     struct Client;
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     struct ClientSync;
+///     #[cfg(any(feature="use_async", not(any(feature="use_sync"))))]
+///     struct ClientAsync;
+///     ```
+///
+/// - `only`
+///
+///     Generates only the listed variants (by key) of the inherited ones, e.g. of the variants
+/// specified in the `default` attribute of `content` or in the crate-wide defaults.
+///
+///     ```rust
+///     maybe_async_cfg2::content!{
+///     #![maybe_async_cfg2::default(sync(feature="use_sync"), async(feature="use_async"))]
+///
+///     #[maybe_async_cfg2::maybe(only(async))]
+///     struct Struct;
+///     } // content!
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_async")]
+///     struct StructAsync;
+///     ```
+///
+/// - `conditions`
+///
+///     Names `cfg` predicates, so that the variants can refer to them with `cond` instead of
+/// repeating them. Usually specified once in the `default` attribute of `content` or in the
+/// crate-wide defaults, the conditions are inherited by all `maybe` macros.
+///
+///     ```rust
+///     maybe_async_cfg2::content!{
+///     #![maybe_async_cfg2::default(
+///         conditions(tokio = "all(feature=\"async\", feature=\"rt-tokio\")"),
+///     )]
+///
+///     #[maybe_async_cfg2::maybe(sync(feature="sync"), async(cond="tokio"))]
+///     struct Struct;
+///     } // content!
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="sync")]
+///     struct StructSync;
+///     #[cfg(all(feature="async", feature="rt-tokio"))]
+///     struct StructAsync;
+///     ```
+///
+/// - `merge_cfgs`
+///
+///     Merges the `#[cfg(...)]` attributes of the item (placed after `maybe`) into the conditions
+/// of the variants, instead of keeping them as separate attributes.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(sync(feature="use_sync"), async(feature="use_async"), merge_cfgs)]
+///     #[cfg(windows)]
+///     struct Struct;
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(all(windows, feature="use_sync"))]
+///     struct StructSync;
+///     #[cfg(all(windows, feature="use_async"))]
+///     struct StructAsync;
+///     ```
+///
+/// - `dedup`
+///
+///     Converts all variants at once and emits the code that is identical in several variants
+/// (apart from their conditions) only once, under `cfg(any(...))` of their conditions. Useful for
+/// plain data items whose names are kept, to reduce the amount of generated code.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         dedup,
+///         keep_self,
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     struct Struct {
+///         f: usize,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(any(feature="use_sync", feature="use_async"))]
+///     struct Struct {
+///         f: usize,
+///     }
+///     ```
+///
+/// - `dispatch`
+///
+///     Additionally generate the enum `<Name>Either` (or with the name given by
+/// `dispatch = "Name"`) over the (first) sync and async variants of an inherent impl block or a
+/// trait, enabled when both variants are, so the implementation can be chosen at runtime. Its
+/// methods forward the calls to the variant it holds and are async if the method of the async
+/// variant is. The methods without a receiver, the ones whose signatures differ between the
+/// variants (apart from asyncness) and the ones mentioning `Self` are not forwarded. For traits,
+/// the enum is generic over the implementors of both variants.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         dispatch,
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     impl Client {
+///         pub async fn get(&self, key: &str) -> Option<String> {
+///             self.request(key).await
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     impl ClientSync {
+///         pub fn get(&self, key: &str) -> Option<String> {
+///             self.request(key)
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     impl ClientAsync {
+///         pub async fn get(&self, key: &str) -> Option<String> {
+///             self.request(key).await
+///         }
+///     }
+///     #[cfg(all(feature="use_sync", feature="use_async"))]
+///     /// Either the sync or the async variant of `Client`, chosen at runtime.
+///     pub enum ClientEither {
+///         Sync(ClientSync),
+///         Async(ClientAsync),
+///     }
+///     #[cfg(all(feature="use_sync", feature="use_async"))]
+///     impl ClientEither {
+///         pub async fn get(&self, __arg1: &str) -> Option<String> {
+///             match self {
+///                 Self::Sync(inner) => inner.get(__arg1),
+///                 Self::Async(inner) => inner.get(__arg1).await,
+///             }
+///         }
+///     }
+///     ```
+///
+/// - `register`, `use_registered`
+///
+///     `register = "Name"` saves the parameters (including the variants and the `idents` list)
+/// under the specified name, and `use_registered("Name")` takes exactly the same parameters for
+/// another item, e.g. an impl block of a registered trait. The name of a registered trait is added
+/// to the `idents` list. Parameters specified along with `use_registered` are added to the
+/// registered ones (the variants can not be specified there). The parameters are passed through a
+/// hidden `macro_rules!` macro, which is visible in the whole crate, so the order of the items does
+/// not matter; in other modules, use the path to the module of the registering item, e.g.
+/// `use_registered("crate::client::Client")`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         idents(Conn),
+///         register="Client",
+///     )]
+///     trait Client {
+///         async fn get(&self, conn: &Conn) -> Response;
+///     }
+///
+///     #[maybe_async_cfg2::maybe(use_registered("Client"))]
+///     impl Client for Http {
+///         async fn get(&self, conn: &Conn) -> Response {
+///             conn.request().await
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait ClientSync {
+///         fn get(&self, conn: &ConnSync) -> Response;
+///     }
+///     #[cfg(feature="use_async")]
+///     trait ClientAsync {
+///         async fn get(&self, conn: &ConnAsync) -> Response;
+///     }
+///
+///     #[cfg(feature="use_sync")]
+///     impl ClientSync for HttpSync {
+///         fn get(&self, conn: &ConnSync) -> Response {
+///             conn.request()
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     impl ClientAsync for HttpAsync {
+///         async fn get(&self, conn: &ConnAsync) -> Response {
+///             conn.request().await
+///         }
+///     }
+///     ```
+///
 /// - `sync`, `async`
 ///
-///     Defines variants of code: the item to which the attribute `maybe` refers will be
-/// replaced with multiple copies (one for each variant), which will be modified according to
-/// the variant kind and its parameters.
+///     Defines variants of code: the item to which the attribute `maybe` refers will be replaced
+/// with multiple copies (one for each variant), which will be modified according to the variant
+/// kind and its parameters.
+///
+///     For the `sync` variant, the item will be converted from async to sync code by deleting the
+/// `async` and `await` keywords, including the ones of async closures (`async move |x| { .. }`
+/// becomes `move |x| { .. }`) and of the async blocks returned by closures (`|x| async move { .. }`
+/// becomes `move |x| { .. }`). Async blocks containing `return` or `?` will be replaced with
+/// immediately called closures, so that `async move { f()?; Ok(()) }` becomes
+/// `(move || { f()?; Ok(()) })()`; the nested `try` blocks are kept as is apart from the `.await`s.
+/// The `let .. else` statements, `let` chains and inline `const { .. }` blocks are converted (in
+/// both variants) as any other code, while the `gen` and `async gen` blocks cannot be parsed yet
+/// and result in an error.
+///
+///     Types `Future<Output=XXX>` will be replaced with just `XXX`, and so will
+/// `impl Future<Output=XXX> + Send + 'a`, the boxed futures
+/// `Pin<Box<dyn Future<Output=XXX> + Send + 'a>>`, `BoxFuture<'a, XXX>`, `LocalBoxFuture<'a, XXX>`
+/// and the ready futures `Ready<XXX>`. This applies to return types and associated types alike,
+/// dropping the other bounds, and the lifetime parameters of functions used only by them.
+///
+///     The generic future parameters of the converted functions are dropped as well, their uses
+/// being replaced with the output type, so
+/// `<F: Fn(Request) -> Fut, Fut: Future<Output = Response>>` (or the same bounds in the `where`
+/// clause) becomes `<F: Fn(Request) -> Response>`. The other bounds of such a parameter are
+/// dropped, unless its output is a type parameter itself:
+/// `Fut: Future<Output = T> + Send + 'static` becomes `where T: Send + 'static` (unless listed in
+/// `relax_bounds`, `Unpin` is removed).
+///
+///     `Box::pin(async move { .. })` and `async move { .. }.boxed()` (or `.boxed_local()`) will be
+/// replaced with the block. The ready futures `std::future::ready(x)` (`futures::future::ready(x)`)
+/// will be replaced with `x`, and the `.into_future()` calls will be removed.
+///
+///     Bounds `Stream<Item=XXX>` (including `impl Stream<Item=XXX>`) will be replaced with
+/// `Iterator<Item=XXX>`, and types `BoxStream<'a, XXX>` (`LocalBoxStream<'a, XXX>`) with
+/// `Box<dyn Iterator<Item=XXX> + Send + 'a>` (without `Send`). Bounds `AsyncRead`, `AsyncWrite`,
+/// `AsyncBufRead` and `AsyncSeek` (including `impl AsyncRead` and `dyn AsyncRead`) will be replaced
+/// with `::std::io::Read`, `::std::io::Write`, `::std::io::BufRead` and `::std::io::Seek`, and the
+/// `Unpin` bounds next to them will be removed (`R: AsyncRead + Unpin` becomes
+/// `R: ::std::io::Read`). Bounds `AsyncFn(A) -> R`, `AsyncFnMut(A) -> R` and `AsyncFnOnce(A) -> R`
+/// will be replaced with `Fn(A) -> R`, `FnMut(A) -> R` and `FnOnce(A) -> R`.
+///
+///     The stream consumption loops `while let Some(x) = stream.next().await { .. }` will be
+/// replaced with `for x in stream.by_ref() { .. }`.
+///
+///     The `futures::join!(a, b)` and `tokio::join!(a, b)` macros (called by these paths) will be
+/// replaced with the tuples `(a, b)` evaluated sequentially, and `try_join!(a, b)` with the matches
+/// returning the tuple of the results or the first error. The `futures::select!` and
+/// `tokio::select!` macros cannot be converted and result in an error, so they have to be marked
+/// with `only_if(async)`. The same goes for the `async_stream::stream!` macros, unless the macro
+/// generating an iterator is specified with `stream_macro` (see below). Other `join!`, `select!`
+/// and `stream!` macros are left as is.
+///
+///     Calls to executors, such as `futures::executor::block_on(future)`,
+/// `pollster::block_on(future)` or `runtime.block_on(future)`, will be replaced with just the
+/// (converted) `future` expression, so `block_on(async { fetch().await })` becomes `fetch()`. The
+/// async sleeps `tokio::time::sleep(duration).await` and `async_std::task::sleep(duration).await`
+/// will be replaced with `::std::thread::sleep(duration)`, and the `tokio::task::yield_now().await`
+/// calls (or those of `async_std`, `futures_lite` and `smol`) will be removed (see also `yield_now`
+/// below). The blocking sections `tokio::task::spawn_blocking(|| expr).await.unwrap()` (or with
+/// `.expect(..)` or `?`), `async_std::task::spawn_blocking(|| expr).await` and
+/// `tokio::task::block_in_place(|| expr)` will be replaced with just `expr`.
+///
+///     An `.await` left in the `sync` variant outside of async blocks and closures (usually inside
+/// a macro which is not converted) results in an error pointing at it.
+///
+///     For the `async` variant, the item will be left async.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     async fn total(ids: Vec<u32>) -> u32 {
+///         let fetch = async move |id: u32| { load(id).await + 1 };
+///         let mut sum = 0;
+///         for id in ids {
+///             sum += fetch(id).await;
+///         }
+///         sum
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn total_sync(ids: Vec<u32>) -> u32 {
+///         let fetch = move |id: u32| { load(id) + 1 };
+///         let mut sum = 0;
+///         for id in ids {
+///             sum += fetch(id);
+///         }
+///         sum
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn total_async(ids: Vec<u32>) -> u32 {
+///         let fetch = async move |id: u32| { load(id).await + 1 };
+///         let mut sum = 0;
+///         for id in ids {
+///             sum += fetch(id).await;
+///         }
+///         sum
+///     }
+///     ```
+///
+///     The functions in the manual-future style are converted as well:
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     fn get(&self) -> impl Future<Output = Result<Item>> + Send + '_ {
+///         async move { self.fetch().await }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn get_sync(&self) -> Result<Item> {
+///         self.fetch()
+///     }
+///     #[cfg(feature="use_async")]
+///     fn get_async(&self) -> impl Future<Output = Result<Item>> + Send + '_ {
+///         async move { self.fetch().await }
+///     }
+///     ```
+///
+///     In traits and impl blocks, the associated future types (`type Fut: Future<Output=XXX>` of
+/// a trait, `type Fut = BoxFuture<'a, XXX>` or `type Fut = Ready<XXX>` of an impl block) are
+/// removed, and `Self::Fut` is replaced with `XXX`. This includes the lending futures, generic over
+/// a lifetime (`Self::Fut<'_>`):
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(Source),
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     trait Source {
+///         type Fut<'a>: Future<Output = Item> + Send + 'a where Self: 'a;
+///         fn get<'a>(&'a self) -> Self::Fut<'a>;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait SourceSync {
+///         fn get<'a>(&'a self) -> Item;
+///     }
+///     #[cfg(feature="use_async")]
+///     trait SourceAsync {
+///         type Fut<'a>: Future<Output = Item> + Send + 'a where Self: 'a;
+///         fn get<'a>(&'a self) -> Self::Fut<'a>;
+///     }
+///     ```
+///
+/// - `exclusive`
+///
+///     A shortcut for crates toggled by a single feature: `exclusive(feature="is_sync")` (or just
+/// `exclusive("is_sync")`) defines the `sync` variant under `cfg(feature="is_sync")` and the
+/// `async` variant under `cfg(not(feature="is_sync"))`. Other parameters in the list are passed to
+/// both variants.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(exclusive("is_sync"))]
+///     struct Client;
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="is_sync")]
+///     struct ClientSync;
+///     #[cfg(not(feature="is_sync"))]
+///     struct ClientAsync;
+///     ```
+///
+/// - `dyn`
+///
+///     Defines an object-safe variant of a trait: its async methods will return
+/// `Pin<Box<dyn Future<Output=XXX> + Send + '_>>` instead (the default method bodies are boxed
+/// accordingly), and the name gets the `Dyn` suffix (other identifiers are named as in the `async`
+/// variant). If there is an `async` variant as well, the
+/// `dyn` trait will be implemented for all implementors of the (first) `async` trait. With the
+/// default `Send` bound this requires the futures of the `async` trait to be `Send` (`send =
+/// "Send"`, with `async_trait` or `impl_future`), otherwise an error is reported; specify `send =
+/// "?Send"` to omit the bound. Items other than traits are not present in the `dyn` variant.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         dyn(feature="use_async", send="?Send"),
+///     )]
+///     trait Plugin {
+///         async fn run(&self, input: &str) -> usize;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait PluginSync {
+///         fn run(&self, input: &str) -> usize;
+///     }
+///     #[cfg(feature="use_async")]
+///     trait PluginAsync {
+///         async fn run(&self, input: &str) -> usize;
+///     }
+///     #[cfg(feature="use_async")]
+///     trait PluginDyn {
+///         fn run<'maybe_async>(&'maybe_async self, input: &'maybe_async str)
+///             -> ::std::pin::Pin<::std::boxed::Box<
+///                 dyn ::std::future::Future<Output = usize> + 'maybe_async
+///             >>
+///         where
+///             Self: 'maybe_async;
+///     }
+///     #[cfg(feature="use_async")]
+///     impl<__MaybeAsyncSelf: PluginAsync + ?::std::marker::Sized> PluginDyn for __MaybeAsyncSelf {
+///         fn run<'maybe_async>(&'maybe_async self, __arg1: &'maybe_async str)
+///             -> ::std::pin::Pin<::std::boxed::Box<
+///                 dyn ::std::future::Future<Output = usize> + 'maybe_async
+///             >>
+///         where
+///             Self: 'maybe_async,
+///         {
+///             ::std::boxed::Box::pin(<__MaybeAsyncSelf as PluginAsync>::run(self, __arg1))
+///         }
+///     }
+///     ```
+///
+/// - `variant`
+///
+///     Defines a variant of any kind, which is specified by the `mode` parameter (`"sync"`,
+/// `"async"` or `"dyn"`). The `key` parameter is required, and the names get the suffix made from
+/// the key (e.g. `Tokio` for the `"tokio"` key) unless the `suffix` parameter is specified. This
+/// way, an item can be expanded into any number of variants of the same kind.
+///
+///     The `mode` parameter can also be specified in `sync(...)`, `async(...)` and `dyn(...)`, and
+/// overrides the kind given by the name, e.g. `sync(mode="async", key="tokio")` is an async
+/// variant.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(Conn),
+///         variant(key="tokio", mode="async", feature="rt-tokio"),
+///         variant(key="smol", mode="async", feature="rt-smol"),
+///         variant(key="blocking", mode="sync", feature="blocking"),
+///     )]
+///     struct Client {
+///         conn: Conn,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="rt-tokio")]
+///     struct ClientTokio {
+///         conn: ConnTokio,
+///     }
+///     #[cfg(feature="rt-smol")]
+///     struct ClientSmol {
+///         conn: ConnSmol,
+///     }
+///     #[cfg(feature="blocking")]
+///     struct ClientBlocking {
+///         conn: ConnBlocking,
+///     }
+///     ```
+///
+/// - `matrix`
+///
+///     Defines the variants as the cartesian product of several axes. Every `axis(...)` lists its
+/// entries, which take the version parameters. An entry is named `sync`, `async`, `dyn` (these set
+/// the mode), `variant` (with `key` and optional `mode`), or any other name, which becomes its key.
+/// Exactly one entry of each combination has to specify the mode.
+///
+///     For each combination, the conditions of the entries are combined with `all(...)`, the keys
+/// are joined with `_` (e.g. `sync_native`) and the suffixes are concatenated (e.g. `SyncNative`).
+/// All other parameters of the entries are merged.
 ///
-///     For the `sync` variant, the item will be converted from async to sync code by deleting
-/// the `async` and `await` keywords. Types `Future<Output=XXX>` will also be replaced with just
-/// `XXX`. For the `async` variant, the item will be left async.
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         matrix(
+///             axis(sync(feature="use_sync"), async(feature="use_async")),
+///             axis(
+///                 native(cfg(not(target_arch="wasm32"))),
+///                 wasm(cfg(target_arch="wasm32"), send="?Send"),
+///             ),
+///         )
+///     )]
+///     struct Client;
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(all(feature="use_sync", not(target_arch="wasm32")))]
+///     struct ClientSyncNative;
+///     #[cfg(all(feature="use_sync", target_arch="wasm32"))]
+///     struct ClientSyncWasm;
+///     #[cfg(all(feature="use_async", not(target_arch="wasm32")))]
+///     struct ClientAsyncNative;
+///     #[cfg(all(feature="use_async", target_arch="wasm32"))]
+///     struct ClientAsyncWasm;
+///     ```
 ///
 ///     In any case, the item will be converted according to all parameters described below. For
 /// functions, structs/enums and traits, the name will be changed as if it is mentioned in the
@@ -306,7 +918,8 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// - `key`
 ///
 ///     Defines unique name of the variant to use it in `only_if`/`remove_if` conditions. If
-/// omitted, `sync`/`async` will be used.
+/// omitted, `sync`/`async`/`dyn` will be used. A condition with a key that none of the variants
+/// has is an error.
 ///
 ///     ```rust
 ///     #[maybe_async_cfg2::maybe(
@@ -334,7 +947,109 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///     }
 ///     ```
 ///
-/// - `cfg`
+/// - `preset`
+///
+///     Use the parameters commonly needed for a runtime. The available presets are:
+///     - `"tokio"` (async): `#[test]` functions get `#[tokio::test]` instead, the `main` function
+/// gets `#[tokio::main]`, and `send = "Send"` is applied to traits and impl blocks;
+///     - `"async-std"` (async): the same with `async_std::test` and `async_std::main`;
+///     - `"wasm"` (async): the variant appears only under `cfg(target_arch = "wasm32")`, `#[test]`
+/// functions get `#[wasm_bindgen_test::wasm_bindgen_test]` instead, and `send = "?Send"` is
+/// applied;
+///     - `"std"` (sync): `#[test]` stays as is;
+///     - `"reqwest"` (sync): the async client of `reqwest` is replaced with the blocking one:
+/// `reqwest::Client`, `ClientBuilder`, `Request`, `RequestBuilder`, `Response`, `Body`,
+/// `multipart` and `get` become the ones of `reqwest::blocking` (as the paths of the `types`
+/// parameter do, including `use reqwest::{Client, Response}`), and the `.await`s are removed as
+/// usual;
+///     - `"fs"` (sync): `tokio::fs` and `async_std::fs` are replaced with `std::fs`, and the I/O
+/// extension traits and buffers of `tokio::io` and `async_std::io` (`AsyncReadExt`,
+/// `AsyncWriteExt`, `BufReader`, the prelude of `async_std` and so on) with the ones of `std::io`,
+/// so that `File::open(path).await?.read_to_string(&mut s).await?` becomes
+/// `File::open(path)?.read_to_string(&mut s)?`.
+///
+///     Explicitly specified parameters (`test_attr`, `main_attr`, `send`) take precedence over the
+/// preset. The condition of the preset is combined with the `feature`/`cfg` of the variant. In
+/// `variant(...)`, the `mode` and the `key` can be omitted to use the ones of the preset.
+///
+///     Presets are combined by repeating the parameter, e.g. `preset="std", preset="reqwest",
+/// preset="fs"`: the paths of all of them are replaced and all their conditions apply, while
+/// `test_attr`, `main_attr` and `send` come from the first preset defining them, and the `mode`
+/// and the `key` from the first preset. All the presets have to be of the mode of the variant.
+///
+///     Presets leave the naming alone: the items of a `sync(...)` or `async(...)` variant get the
+/// suffix of its mode, and the ones of a `variant(...)` the suffix made from its key, as without a
+/// preset. Two variants of the same mode, e.g. with `preset="tokio"` and `preset="async-std"`,
+/// need their own `key`, `suffix` or `idents` to not clash.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", preset="std"),
+///         async(feature="use_async", preset="tokio"),
+///     )]
+///     mod tests {
+///         #[test]
+///         async fn connect() {
+///             assert!(Client::connect().await.is_ok());
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     mod tests_sync {
+///         #[test]
+///         fn connect() {
+///             assert!(Client::connect().is_ok());
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     mod tests_async {
+///         #[tokio::test]
+///         async fn connect() {
+///             assert!(Client::connect().await.is_ok());
+///         }
+///     }
+///     ```
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", preset="reqwest", preset="fs"),
+///         async(feature="use_async"),
+///     )]
+///     async fn fetch(client: &reqwest::Client, url: &str) -> reqwest::Result<String> {
+///         let text = client.get(url).send().await?.text().await?;
+///         tokio::fs::write("page.html", &text).await.unwrap();
+///         Ok(text)
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn fetch_sync(client: &reqwest::blocking::Client, url: &str) -> reqwest::Result<String> {
+///         let text = client.get(url).send()?.text()?;
+///         std::fs::write("page.html", &text).unwrap();
+///         Ok(text)
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn fetch_async(client: &reqwest::Client, url: &str) -> reqwest::Result<String> {
+///         let text = client.get(url).send().await?.text().await?;
+///         tokio::fs::write("page.html", &text).await.unwrap();
+///         Ok(text)
+///     }
+///     ```
+///
+/// - `test_attr`, `main_attr`
+///
+///     In this variant, replace the `#[test]` attribute of functions with the specified attribute,
+/// and add the specified attribute to the `main` function, respectively.
+///
+/// - `suffix`
+///
+///     The suffix added to the generated names in this variant instead of the default one (`Sync`,
+/// `Async` or `Dyn`). For snake case names, it is converted to snake case (`_suffix`).
+///
+/// - `cfg`
 ///
 ///     Defines the condition (based on features), under which the current variant should appear.
 ///
@@ -353,147 +1068,1526 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///     After conversion:
 ///     ```rust
 ///     #[cfg(feature="use_sync")]
-///     struct StructSync {
-///         f: Foo,
+///     struct StructSync {
+///         f: Foo,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct StructAsync {
+///         f: Foo,
+///     }
+///     ```
+///
+/// - `cond`
+///
+///     Adds the condition with the given name, defined by the `conditions` parameter, to the
+/// condition of the variant. Both `cond="name"` and `cond(name)` forms are accepted. A name that is
+/// not defined is an error.
+///
+/// - `no_cfg`
+///
+///     The variant is always compiled: no condition is attached to it, including the inherited
+/// ones and the ones of presets. Specified for all variants, it lets a crate provide both sync and
+/// async APIs in one build.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(sync(no_cfg), async(no_cfg))]
+///     pub struct Client;
+///     ```
+///     After conversion:
+///     ```rust
+///     pub struct ClientSync;
+///     pub struct ClientAsync;
+///     ```
+///  
+/// - `idents`
+///
+///     Defines a list of identifiers that should be renamed depending on the variant of code.
+///
+///     Each identifier can have the following clarifying parameters:
+///
+///     - `snake`, `fn`, `mod`
+///
+///         means that this is the snake-case name of the function or module and it should be
+/// converted by adding the suffixes `"_sync"`/`"_async"` (otherwise, the suffixes
+/// `"Sync"`/`"Async"` will be used).
+///
+///     - `use`
+///
+///         in `use` lists, using this identifier will result in renaming via the `as` expression,
+/// rather than a simple replacement as is. In other cases, a simple replacement will be used.
+///
+///     - `keep`
+///
+///         this identifier will not be converted anywhere
+///
+///     - `sync`, `async`
+///
+///         specifies the name that will be used in the corresponding variant of code. Overrides
+/// the standard scheme of suffixes used by default. If the parameter value is omitted,
+/// the identifier will not be renamed in this case.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(
+///             Foo,
+///             Bar,
+///             baz(fn),
+///             Qux(use),
+///             waldo(sync, async="async_waldo"),
+///             xyzzy(fn, use, sync="xizzy_the_sync_func"),
+///         ),
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///     )]
+///     async fn func() {
+///         struct Foo {}
+///         use extcrate::{
+///             Bar,
+///             baz,
+///             Qux,
+///             waldo::{
+///                 plugh,
+///                 xyzzy
+///             }
+///         };
+///         let _ = baz( Foo {}, Bar::new() ).await;
+///         let _ = xizzy( Qux::flob(b).await );
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn func_sync() {
+///         struct FooSync {}
+///         use extcrate::{
+///             BarSync,
+///             baz_sync,
+///             Qux as QuxSync,
+///             waldo::{
+///                 plugh,
+///                 xyzzy as xizzy_the_sync_func
+///             }
+///         };         
+///         let _ = baz_sync( FooSync {}, BarSync::new() );
+///         let _ = xizzy_the_sync_func( QuxSync::flob() );
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn func_async() {
+///         struct FooAsync {}
+///         use extcrate::{
+///             BarAsync,
+///             baz_async,
+///             Qux as QuxAsync,
+///             async_waldo::{
+///                 plugh,
+///                 xyzzy as xyzzy_async
+///             }
+///         };
+///         let _ = baz_async( FooAsync {}, BarAsync::new() ).await;
+///         let _ = xyzzy_async( QuxAsync::flob().await );     
+///     }
+///     ```
+///
+///     Note: if different identifiers (including the name of the item itself) get the same name
+/// in some variant, an error is reported, unless both names are specified explicitly.
+///
+/// - `keep_self`
+///
+///     Do not change name of item to which attribute `maybe` refers.
+///
+/// - `self`
+///
+///     Defines the name that will be assigned to the item in this variant.
+///
+/// - `self_path`
+///
+///     Places the item of this variant in a module instead of renaming it: the item keeps its name
+/// and is wrapped into a module named by the last segment of the path (with the visibility of the
+/// item and `use super::*;` inside). A module item itself gets this name instead, and the items
+/// in it keep their names. Since every wrapped item gets its own module, apply `self_path` to a
+/// module item (or to a single item per module name).
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", self_path="crate::blocking"),
+///         async(feature="use_async", self_path="crate::nonblocking"),
+///     )]
+///     pub mod client {
+///         pub struct Client;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub mod blocking {
+///         pub struct Client;
+///     }
+///     #[cfg(feature="use_async")]
+///     pub mod nonblocking {
+///         pub struct Client;
+///     }
+///     ```
+///
+/// - `primary`
+///
+///     The item keeps its original name in this variant, while it is renamed in all other variants.
+/// Identifiers from the `idents` list are not suffixed in this variant either (explicitly specified
+/// names are still used). Only one variant can be marked as `primary`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(Foo),
+///         sync(feature="use_sync"),
+///         async(feature="use_async", primary),
+///     )]
+///     struct Struct {
+///         f: Foo,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     struct StructSync {
+///         f: FooSync,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct Struct {
+///         f: Foo,
+///     }
+///     ```
+///
+/// - `alias`
+///
+///     In addition to the renamed item, emits an alias with the original name under the condition
+/// of this variant: a type alias for structs and enums, and a `use` declaration for traits,
+/// functions and modules. This way, the unsuffixed name can always be used. If several variants are
+/// enabled at once, the name refers to the first of them. Can be specified for all variants at
+/// once; it has no effect if some variant is `primary`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         alias,
+///     )]
+///     pub struct Struct<T: Clone> {
+///         f: T,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub struct StructSync<T: Clone> {
+///         f: T,
+///     }
+///     #[cfg(feature="use_sync")]
+///     pub type Struct<T> = StructSync<T>;
+///     #[cfg(feature="use_async")]
+///     pub struct StructAsync<T: Clone> {
+///         f: T,
+///     }
+///     #[cfg(all(feature="use_async", not(any(feature="use_sync"))))]
+///     pub type Struct<T> = StructAsync<T>;
+///     ```
+///
+/// - `doc_hidden`
+///
+///     Adds `#[doc(hidden)]` to the code of this variant, so it is excluded from the documentation
+/// but remains available.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", doc_hidden),
+///         async(feature="use_async"),
+///     )]
+///     struct Struct {
+///         f: usize,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     #[doc(hidden)]
+///     struct StructSync {
+///         f: usize,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct StructAsync {
+///         f: usize,
+///     }
+///     ```
+///
+/// - `doc_cfg`
+///
+///     Adds `#[cfg_attr(docsrs, doc(cfg(...)))]` with the condition of the variant, so that docs.rs
+/// shows which features enable it. The crate has to enable the `doc_cfg` feature for `docsrs`
+/// (`#![cfg_attr(docsrs, feature(doc_cfg))]`). Can be specified for all variants at once.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(sync(feature="use_sync"), async(feature="use_async"), doc_cfg)]
+///     pub struct Client;
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     #[cfg_attr(docsrs, doc(cfg(feature="use_sync")))]
+///     pub struct ClientSync;
+///     #[cfg(feature="use_async")]
+///     #[cfg_attr(docsrs, doc(cfg(feature="use_async")))]
+///     pub struct ClientAsync;
+///     ```
+///
+/// - `deprecate`
+///
+///     Marks the item of this variant as `#[deprecated]`, with the optional `note` and `since`
+/// parameters. The note may contain placeholders: `{key}` is replaced with the key of the variant,
+/// `{name}` with the name of the item in the variant, `{original}` with the original name of the
+/// item, and `{name:KEY}` with the name of the item in the variant with the `KEY` key.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", deprecate(note="use {name:async} instead", since="0.4.0")),
+///         async(feature="use_async"),
+///     )]
+///     pub struct Client;
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     #[deprecated(since="0.4.0", note="use ClientAsync instead")]
+///     pub struct ClientSync;
+///     #[cfg(feature="use_async")]
+///     pub struct ClientAsync;
+///     ```
+///
+/// - `test_only`
+///
+///     Combines the condition of this variant with `cfg(test)`. If `test_only = "..."` is
+/// specified, the condition is combined with `cfg(any(test, feature = "..."))` instead. Useful for
+/// an additional variant of selected items intended for tests only.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         async(key="hooked", self="StructHooked", feature="use_async", test_only="test-util"),
+///     )]
+///     struct Struct {
+///         f: usize,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     struct StructSync {
+///         f: usize,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct StructAsync {
+///         f: usize,
+///     }
+///     #[cfg(all(any(test, feature="test-util"), feature="use_async"))]
+///     struct StructHooked {
+///         f: usize,
+///     }
+///     ```
+///
+/// - `constness`
+///
+///     Marks the function of the `sync` variant `const fn`. Specified as `constness` or
+/// `constness = true`; at the top level, it applies to all `sync` variants. It is an error if
+/// asynchronous code is left in the function after the conversion, or if the item is not a
+/// function.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", constness = true),
+///         async(feature="use_async")
+///     )]
+///     async fn add(a: u32, b: u32) -> u32 {
+///         a + b
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     const fn add_sync(a: u32, b: u32) -> u32 {
+///         a + b
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn add_async(a: u32, b: u32) -> u32 {
+///         a + b
+///     }
+///     ```
+///
+/// - `send`
+///
+///     If `send = "Send"` or `send = "true"` is present, the attribute
+/// `#[async_trait::async_trait]` will be added before the async code. If `send = "?Send"` or
+/// `send = "false"` then `#[async_trait::async_trait(?Send)]` will be added.  
+///
+///     An `async_trait` attribute already present on the item is replaced with the one given by
+/// `send` (and kept as is without `send`), while the sync variant drops it, so an existing
+/// async-trait codebase can be annotated as is.
+///
+///     Only traits and impl blocks get the attribute. An async function with `send = "Send"`
+/// returns `impl Future<Output = T> + Send` in the async variant instead, so that its future is
+/// checked to be `Send`. `send` on other items (or on a trait without async methods) results in a
+/// warning. The impl blocks of a trait have to use the same `send` as the trait, an impl block
+/// whose `send` differs is reported as well if the trait is in the same `content!`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", send="Send"),
+///     )]
+///     pub async fn fetch(url: &str) -> Response {
+///         get(url).await
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub fn fetch_sync(url: &str) -> Response {
+///         get(url)
+///     }
+///     #[cfg(feature="use_async")]
+///     pub fn fetch_async<'maybe_async>(
+///         url: &'maybe_async str,
+///     ) -> impl ::std::future::Future<Output = Response> + ::std::marker::Send + 'maybe_async {
+///         async move { get(url).await }
+///     }
+///     ```
+///
+///     With `send = "trait_variant"`, the async variant of a trait gets
+/// `#[trait_variant::make(Send)]` instead of the `async_trait` attribute, and its impl blocks get
+/// no attribute at all (their methods are kept async). `trait_variant = "path::to::make"` sets
+/// another path of the attribute (such as a re-export), implying `send = "trait_variant"`. Async
+/// functions are treated as with `send = "Send"`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", send="trait_variant"),
+///     )]
+///     trait Client {
+///         async fn get(&self, url: &str) -> Response;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait ClientSync {
+///         fn get(&self, url: &str) -> Response;
+///     }
+///     #[cfg(feature="use_async")]
+///     #[trait_variant::make(Send)]
+///     trait ClientAsync {
+///         async fn get(&self, url: &str) -> Response;
+///     }
+///     ```
+///
+/// - `async_trait_path`
+///
+///     The path of the attribute added by `send` instead of `async_trait::async_trait`, for crates
+/// which re-export or rename the `async-trait` dependency (or use a fork of it).
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", send="Send"),
+///         async_trait_path="crate::reexports::async_trait",
+///     )]
+///     trait Client {
+///         async fn get(&self, url: &str) -> Response;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait ClientSync {
+///         fn get(&self, url: &str) -> Response;
+///     }
+///     #[cfg(feature="use_async")]
+///     #[crate::reexports::async_trait]
+///     trait ClientAsync {
+///         async fn get(&self, url: &str) -> Response;
+///     }
+///     ```
+///
+/// - `impl_future`
+///
+///     In the async variant, desugar the async methods of traits and trait impl blocks into
+/// `fn f(&self) -> impl Future<Output = T> + Send` (their bodies into `async move { .. }`) instead
+/// of adding the `async_trait` attribute, so that the futures of the trait are checked to be `Send`
+/// without boxing. `send = "?Send"` drops the `Send` bound. The default methods of traits get
+/// `where Self: Sync` (`where Self: Send` for `&mut self` and `self`), as their futures hold
+/// `self`. The methods of inherent impl blocks are kept async. The parameter can be given at the
+/// top level and then applies to the async variants only.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", impl_future),
+///     )]
+///     trait Service {
+///         async fn call(&self, req: Request) -> Response;
+///         async fn ready(&self) -> bool {
+///             true
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait ServiceSync {
+///         fn call(&self, req: Request) -> Response;
+///         fn ready(&self) -> bool {
+///             true
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     trait ServiceAsync {
+///         fn call(
+///             &self,
+///             req: Request,
+///         ) -> impl ::std::future::Future<Output = Response> + ::std::marker::Send;
+///         fn ready(&self) -> impl ::std::future::Future<Output = bool> + ::std::marker::Send
+///         where
+///             Self: ::std::marker::Sync,
+///         {
+///             async move { true }
+///         }
+///     }
+///     ```
+///
+/// - `send_bounds`
+///
+///     A thread-safe async variant: add `+ Send` to the `impl Future<..>` and `impl Stream<..>`
+/// return types, and `Send + Sync` to the type parameters of the functions returning futures (and
+/// of the impl blocks and traits with such methods). Async functions are made `Send` as with
+/// `send = "Send"`, while traits and impl blocks get the `async_trait` attribute only with an
+/// explicit `send`. `send = "?Send"` disables the bounds.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", send_bounds),
+///     )]
+///     impl<C: Codec> Client<C> {
+///         fn events<F>(&self, filter: F) -> impl Stream<Item = Event> {
+///             self.subscribe(filter)
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     impl<C: Codec> ClientSync<C> {
+///         fn events<F>(&self, filter: F) -> impl Iterator<Item = Event> {
+///             self.subscribe(filter)
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     impl<C: Codec + ::std::marker::Send + ::std::marker::Sync> ClientAsync<C> {
+///         fn events<F: ::std::marker::Send + ::std::marker::Sync>(
+///             &self,
+///             filter: F,
+///         ) -> impl Stream<Item = Event> + ::std::marker::Send {
+///             self.subscribe(filter)
+///         }
+///     }
+///     ```
+///
+/// - `dyn_compat`
+///
+///     In the async variant, make a trait dyn-compatible by the given attribute instead of the
+/// `async_trait` one: `dyn_compat = "dynosaur"` adds `#[dynosaur::dynosaur(DynName)]` (`Name`
+/// being the name of the trait in this variant), any other value is the path of an attribute
+/// taking the name of the dyn type the same way. The trait impl blocks with this parameter do not
+/// get the `async_trait` attribute either, and the sync variant remains a plain trait.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", dyn_compat="dynosaur"),
+///     )]
+///     trait Client {
+///         async fn get(&self, url: &str) -> Response;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     trait ClientSync {
+///         fn get(&self, url: &str) -> Response;
+///     }
+///     #[cfg(feature="use_async")]
+///     #[dynosaur::dynosaur(DynClientAsync)]
+///     trait ClientAsync {
+///         async fn get(&self, url: &str) -> Response;
+///     }
+///     ```
+///
+/// - `stream_macro`
+///
+///     In the sync variant, replace `async_stream::stream!` macros with the specified macro, which
+/// generates an iterator lazily. The body of the macro is converted as usual. Without this
+/// parameter, such macros result in an error in the sync variant. Only the macros called by their
+/// full path `async_stream::stream!` are recognized, a bare `stream!` is left as is.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", stream_macro="genawaiter::sync_gen"),
+///         async(feature="use_async"),
+///     )]
+///     async fn numbers() -> impl Stream<Item = u32> {
+///         async_stream::stream! {
+///             for i in 0..10 {
+///                 yield fetch(i).await;
+///             }
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn numbers_sync() -> impl Iterator<Item = u32> {
+///         genawaiter::sync_gen! {
+///             for i in 0..10 {
+///                 yield fetch(i);
+///             }
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn numbers_async() -> impl Stream<Item = u32> {
+///         async_stream::stream! {
+///             for i in 0..10 {
+///                 yield fetch(i).await;
+///             }
+///         }
+///     }
+///     ```
+///
+/// - `process_macros`
+///
+///     Convert the bodies of the specified macros (paths or string literals) in addition to the
+/// standard ones (`dbg!`, `print!`, `println!`, `assert!`, `assert_eq!` and `assert_ne!`). A macro
+/// is matched by its full path or by its last segment. Its body is converted as described for
+/// `process_all_macros` below.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", process_macros(tracing::debug, vec)),
+///         async(feature="use_async"),
+///     )]
+///     async fn load(store: &Store) -> Vec<Item> {
+///         tracing::debug!("loading {} items", store.len().await);
+///         vec![store.first().await; 2]
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn load_sync(store: &Store) -> Vec<Item> {
+///         tracing::debug!("loading {} items", store.len());
+///         vec![store.first(); 2]
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn load_async(store: &Store) -> Vec<Item> {
+///         tracing::debug!("loading {} items", store.len().await);
+///         vec![store.first().await; 2]
+///     }
+///     ```
+///
+/// - `no_macro_processing`
+///
+///     Leave the bodies of the specified macros as is, or of all macros if no list is given. This
+/// is useful for the standard macros whose arguments cannot be parsed as expressions, which results
+/// in an error otherwise.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", no_macro_processing(assert_eq)),
+///         async(feature="use_async", no_macro_processing),
+///     )]
+///     async fn check(value: u32) {
+///         assert_eq!(value, 1);
+///     }
+///     ```
+///
+/// - `process_all_macros`
+///
+///     Convert the bodies of all macros, not only of the standard ones (`dbg!`, `print!`,
+/// `println!`, `assert!`, `assert_eq!` and `assert_ne!`). The arguments of a macro are converted as
+/// expressions if they can be parsed as a comma separated list of expressions. Otherwise its tokens
+/// are rewritten: the listed identifiers are renamed and, in the sync variant, `.await` and `async`
+/// are removed.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", process_all_macros),
+///         async(feature="use_async"),
+///     )]
+///     async fn check(client: &Client) -> anyhow::Result<()> {
+///         tracing::info!(target: "check", "status: {}", client.status().await);
+///         anyhow::ensure!(client.ping().await, "no answer");
+///         Ok(())
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn check_sync(client: &Client) -> anyhow::Result<()> {
+///         tracing::info!(target: "check", "status: {}", client.status());
+///         anyhow::ensure!(client.ping(), "no answer");
+///         Ok(())
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn check_async(client: &Client) -> anyhow::Result<()> {
+///         tracing::info!(target: "check", "status: {}", client.status().await);
+///         anyhow::ensure!(client.ping().await, "no answer");
+///         Ok(())
+///     }
+///     ```
+///
+/// - `spawn`
+///
+///     In the sync variant, replace the spawning of async tasks (`tokio::spawn`,
+/// `tokio::task::spawn`, `async_std::task::spawn` or `task::spawn`) with the spawning of threads by
+/// the specified function, `::std::thread::spawn` if only `spawn` is specified. The future (or the
+/// body of the async block) is moved into a closure, while the arguments of a call creating the
+/// future are evaluated before spawning, as they are for the future (`spawn(f(x.clone()))` does not
+/// move `x`). An awaited join handle is joined: `tokio::spawn(..).await` becomes
+/// `::std::thread::spawn(..).join()` (both give a `Result`), and `async_std::task::spawn(..).await`
+/// becomes `::std::thread::spawn(..).join().unwrap()`. The same goes for the variables initialized
+/// with a join handle (`let handle = tokio::spawn(..);` and later `handle.await`), while the
+/// handles stored elsewhere (e.g. in a `Vec`) are not recognized.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", spawn),
+///         async(feature="use_async"),
+///     )]
+///     async fn start(n: u32) {
+///         tokio::spawn(async move {
+///             work(n).await;
+///         });
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn start_sync(n: u32) {
+///         ::std::thread::spawn(move || {
+///             work(n);
+///         });
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn start_async(n: u32) {
+///         tokio::spawn(async move {
+///             work(n).await;
+///         });
+///     }
+///     ```
+///
+/// - `locks`
+///
+///     In the sync variant, replace the async locks (`Mutex`, `RwLock` and their guards of
+/// `tokio::sync`, `async_std::sync` and `futures::lock`) with the locks of `std::sync`, and the
+/// awaited `.lock()`, `.read()` and `.write()` calls (without arguments) with the blocking ones,
+/// whatever their receiver is, e.g. `self.field.lock().await`. With `lock_methods` this is
+/// restricted, see below. The poisoned lock is handled by the specified policy: `"unwrap"` (the
+/// default if only `locks` is specified) panics, `"ignore_poison"` takes the guard anyway. Only the
+/// paths starting with the crate name and the `use` declarations are converted, a group in `use` is
+/// converted only if all its items are locks.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", locks="ignore_poison"),
+///         async(feature="use_async"),
+///     )]
+///     async fn bump(counter: &tokio::sync::Mutex<u32>) {
+///         *counter.lock().await += 1;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn bump_sync(counter: &std::sync::Mutex<u32>) {
+///         *counter.lock().unwrap_or_else(::std::sync::PoisonError::into_inner) += 1;
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn bump_async(counter: &tokio::sync::Mutex<u32>) {
+///         *counter.lock().await += 1;
+///     }
+///     ```
+///
+/// - `lock_methods`
+///
+///     With `locks`, convert the awaited calls of the listed methods (`lock`, `read` or `write`)
+/// whatever their receiver is, e.g. the fields of `self` or the items of a slice. The other ones are
+/// only converted on the arguments and variables declared with a type (or initialized with an
+/// expression) mentioning `Mutex` or `RwLock`, e.g. to keep the `read()` of an async reader.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", locks, lock_methods(lock)),
+///         async(feature="use_async"),
+///     )]
+///     async fn bump(counters: &[tokio::sync::Mutex<u32>]) {
+///         *counters[0].lock().await += 1;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn bump_sync(counters: &[std::sync::Mutex<u32>]) {
+///         *counters[0].lock().unwrap() += 1;
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn bump_async(counters: &[tokio::sync::Mutex<u32>]) {
+///         *counters[0].lock().await += 1;
+///     }
+///     ```
+///
+/// - `dyn_markers`
+///
+///     Add the `Send + Sync` markers to the trait objects (`dyn Client`) in fields, signatures and
+/// type aliases with `dyn_markers = "add"`, or strip them with `dyn_markers = "strip"`. Just
+/// `dyn_markers` adds them in the async variants and strips them in the sync ones. The markers of
+/// the future and stream trait objects are left as is.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         dyn_markers,
+///     )]
+///     struct Service<'a> {
+///         client: Arc<dyn Client>,
+///         handler: Box<dyn Handler + Send + 'a>,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     struct ServiceSync<'a> {
+///         client: Arc<dyn Client>,
+///         handler: Box<dyn Handler + 'a>,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct ServiceAsync<'a> {
+///         client: Arc<dyn Client + ::std::marker::Send + ::std::marker::Sync>,
+///         handler: Box<dyn Handler + Send + 'a + ::std::marker::Sync>,
+///     }
+///     ```
+///
+/// - `yield_now`
+///
+///     In the sync variant, the calls yielding to the async scheduler (`yield_now().await` of
+/// `tokio::task`, `async_std::task`, `futures_lite::future` or `smol::future`) are removed by
+/// default. With `yield_now="thread"` they are replaced with `::std::thread::yield_now()` instead,
+/// `yield_now="remove"` restores the default.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", yield_now="thread"),
+///         async(feature="use_async"),
+///     )]
+///     async fn spin(flag: &std::sync::atomic::AtomicBool) {
+///         while !flag.load(std::sync::atomic::Ordering::Acquire) {
+///             tokio::task::yield_now().await;
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn spin_sync(flag: &std::sync::atomic::AtomicBool) {
+///         while !flag.load(std::sync::atomic::Ordering::Acquire) {
+///             ::std::thread::yield_now();
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn spin_async(flag: &std::sync::atomic::AtomicBool) {
+///         while !flag.load(std::sync::atomic::Ordering::Acquire) {
+///             tokio::task::yield_now().await;
+///         }
+///     }
+///     ```
+///
+/// - `timeout`
+///
+///     In the sync variant, convert the awaited timeouts
+/// `tokio::time::timeout(duration, call).await` and
+/// `async_std::future::timeout(duration, call).await`. With `timeout` or `timeout="ok"` the timeout
+/// is dropped and the call is wrapped in `Ok`, whose error type is inferred from its use. Where
+/// that is ambiguous (`.unwrap()`, `match`, or `?` into an error type with several `From` impls)
+/// the error type is given as `timeout="ok(path::to::Error)"`.
+/// With `timeout="path::to::helper"` the timeout is mapped to the blocking helper, which is called
+/// as `helper(duration, || call)` and has to return a `Result` itself. Without this parameter the
+/// timeouts are left as is.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", timeout="crate::util::with_timeout"),
+///         async(feature="use_async"),
+///     )]
+///     async fn fetch(conn: &mut Conn) -> Result<Vec<u8>, Error> {
+///         Ok(tokio::time::timeout(TIMEOUT, conn.fetch()).await??)
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn fetch_sync(conn: &mut Conn) -> Result<Vec<u8>, Error> {
+///         Ok(crate::util::with_timeout(TIMEOUT, || conn.fetch())??)
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn fetch_async(conn: &mut Conn) -> Result<Vec<u8>, Error> {
+///         Ok(tokio::time::timeout(TIMEOUT, conn.fetch()).await??)
+///     }
+///     ```
+///
+/// - `channels`
+///
+///     In the sync variant, replace the channels of `tokio::sync::mpsc` with the channels of
+/// `std::sync::mpsc`: the bounded `channel` becomes `sync_channel` (and `Sender` becomes
+/// `SyncSender`), the unbounded one becomes `channel`. The awaited `.send(x)` calls lose the
+/// `.await`, the awaited `.recv()` calls become `.recv().ok()`. The mapping table of the paths
+/// can be extended or overridden by the `path = "target"` entries, e.g.
+/// `channels(flume::Sender = "std::sync::mpsc::Sender")`. A path is converted if it starts with
+/// one of the listed paths. The imported items keep their names (`use tokio::sync::mpsc::Sender`
+/// becomes `use std::sync::mpsc::SyncSender as Sender`), and a `use` with a converted item of a
+/// group becomes a group of full paths. The paths through a module imported in the item are
+/// converted as well: `use tokio::sync::mpsc` becomes `use std::sync::mpsc`, and `mpsc::channel`
+/// becomes `mpsc::sync_channel` (if the targets are not in one module, the paths are replaced with
+/// their full targets).
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", channels),
+///         async(feature="use_async"),
+///     )]
+///     async fn relay(
+///         tx: tokio::sync::mpsc::Sender<u32>,
+///         mut rx: tokio::sync::mpsc::Receiver<u32>,
+///     ) {
+///         while let Some(value) = rx.recv().await {
+///             tx.send(value).await.unwrap();
+///         }
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn relay_sync(
+///         tx: std::sync::mpsc::SyncSender<u32>,
+///         mut rx: std::sync::mpsc::Receiver<u32>,
+///     ) {
+///         while let Some(value) = rx.recv().ok() {
+///             tx.send(value).unwrap();
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn relay_async(
+///         tx: tokio::sync::mpsc::Sender<u32>,
+///         mut rx: tokio::sync::mpsc::Receiver<u32>,
+///     ) {
+///         while let Some(value) = rx.recv().await {
+///             tx.send(value).await.unwrap();
+///         }
+///     }
+///     ```
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", channels),
+///         async(feature="use_async"),
+///     )]
+///     fn pipe() -> (tokio::sync::mpsc::Sender<u32>, tokio::sync::mpsc::Receiver<u32>) {
+///         use tokio::sync::mpsc;
+///         mpsc::channel(8)
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn pipe_sync() -> (std::sync::mpsc::SyncSender<u32>, std::sync::mpsc::Receiver<u32>) {
+///         use std::sync::mpsc;
+///         mpsc::sync_channel(8)
+///     }
+///     #[cfg(feature="use_async")]
+///     fn pipe_async() -> (tokio::sync::mpsc::Sender<u32>, tokio::sync::mpsc::Receiver<u32>) {
+///         use tokio::sync::mpsc;
+///         mpsc::channel(8)
+///     }
+///     ```
+///
+/// - `types`
+///
+///     Replace the paths in this variant by the `path = "target"` entries, e.g.
+/// `types(tokio::net::TcpStream = "std::net::TcpStream")`. The replacement applies to all paths
+/// (in signatures, fields, bounds, bodies and `use` declarations) and works like the one of
+/// `channels`: a path is converted if it starts with one of the listed paths, keeping its generic
+/// arguments. The entries of `types` take precedence over the ones of `channels` and presets.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", types(reqwest::Client = "ureq::Agent")),
+///         async(feature="use_async"),
+///     )]
+///     struct Api {
+///         client: reqwest::Client,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     struct ApiSync {
+///         client: ureq::Agent,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct ApiAsync {
+///         client: reqwest::Client,
+///     }
+///     ```
+///
+/// - `crates`
+///
+///     Replace the first segment of the paths in this variant by the `name = "name"` entries, so
+/// that a whole dependency is switched, e.g. `crates(tokio_postgres = "postgres")`. Only the paths
+/// of several segments (or starting with `::`) and the `use` declarations are converted,
+/// `use tokio_postgres;` becomes `use postgres as tokio_postgres;`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", crates(tokio_postgres = "postgres")),
+///         async(feature="use_async"),
+///     )]
+///     async fn query(client: &tokio_postgres::Client) -> Result<u64, tokio_postgres::Error> {
+///         client.execute("DELETE FROM jobs", &[]).await
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn query_sync(client: &postgres::Client) -> Result<u64, postgres::Error> {
+///         client.execute("DELETE FROM jobs", &[])
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn query_async(
+///         client: &tokio_postgres::Client,
+///     ) -> Result<u64, tokio_postgres::Error> {
+///         client.execute("DELETE FROM jobs", &[]).await
+///     }
+///     ```
+///
+/// - `calls`
+///
+///     Replace the called functions in this variant by the `path = "expression"` entries, e.g.
+/// `calls(crate::util::fetch_async = "crate::util::fetch")`. A call is converted if its function
+/// is exactly the listed path, the arguments are kept. The replacement can be any expression (it is
+/// put in parentheses unless it is a path), the await of the call is removed in the sync variant as
+/// usual. The entries take precedence over the built-in conversions, such as the one of
+/// `tokio::time::sleep`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", calls(crate::net::fetch_async = "crate::net::fetch")),
+///         async(feature="use_async"),
+///     )]
+///     async fn load(id: u32) -> Vec<u8> {
+///         crate::net::fetch_async(id).await
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn load_sync(id: u32) -> Vec<u8> {
+///         crate::net::fetch(id)
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn load_async(id: u32) -> Vec<u8> {
+///         crate::net::fetch_async(id).await
+///     }
+///     ```
+///
+/// - `asyncness_removal`
+///
+///     In the sync variant, remove asyncness only in the listed places (by default in all of
+/// them): `signature` is the marked function itself, `members` are the methods of the marked
+/// trait or impl block, `nested` are functions and methods nested in bodies and modules, and
+/// `closures` are async blocks and async closures. The `.await`s inside kept async code are kept
+/// as well, and so are executor calls and future types (`impl Future<Output=XXX>`, `BoxFuture`)
+/// when `closures` is not listed.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", asyncness_removal(signature, members, nested)),
+///         async(feature="use_async"),
+///     )]
+///     async fn load(url: &str) -> usize {
+///         let raw = download(url).await;
+///         runtime().block_on(async { parse(raw).await })
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn load_sync(url: &str) -> usize {
+///         let raw = download(url);
+///         runtime().block_on(async { parse(raw).await })
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn load_async(url: &str) -> usize {
+///         let raw = download(url).await;
+///         runtime().block_on(async { parse(raw).await })
+///     }
+///     ```
+///
+/// - `receiver`
+///
+///     Replace the receiver of all methods taking `&self` or `&mut self` of the trait or impl
+/// block, e.g. `receiver = "&mut self"`; the methods taking `self` by value are left as is. To
+/// change the receiver of a single method, use the `receiver` attribute.
+///
+/// - `blocking_ext`
+///
+///     In the `async` variant of a trait or an inherent impl block, additionally generate the
+/// `<Name>BlockingExt` trait, which has a blocking counterpart with the `_blocking` suffix for
+/// every async method. Such methods call the async ones through the specified `block_on` function,
+/// and the trait is implemented for all implementors of the trait (or for the type of the impl
+/// block).
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", blocking_ext="futures::executor::block_on"),
+///     )]
+///     pub trait Store {
+///         async fn get(&self, key: &str) -> Option<String>;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub trait StoreSync {
+///         fn get(&self, key: &str) -> Option<String>;
+///     }
+///     #[cfg(feature="use_async")]
+///     pub trait StoreAsync {
+///         async fn get(&self, key: &str) -> Option<String>;
+///     }
+///     #[cfg(feature="use_async")]
+///     pub trait StoreBlockingExt: StoreAsync {
+///         fn get_blocking(&self, __arg1: &str) -> Option<String>;
+///     }
+///     #[cfg(feature="use_async")]
+///     impl<__MaybeAsyncSelf: StoreAsync + ?Sized> StoreBlockingExt for __MaybeAsyncSelf {
+///         fn get_blocking(&self, __arg1: &str) -> Option<String> {
+///             futures::executor::block_on(<__MaybeAsyncSelf as StoreAsync>::get(self, __arg1))
+///         }
+///     }
+///     ```
+///
+/// - `delegate`
+///
+///     In the `sync` variant of functions, do not convert the body, but call the function of the
+/// (first) `async` variant with the same arguments and block on the returned future with the
+/// specified `block_on` function. This gives a sync API for the functions whose bodies cannot be
+/// converted.
+///
+///     In the `async` variant, call the function of the (first) `sync` variant in a closure passed
+/// to the specified `spawn_blocking` function (e.g. `tokio::task::spawn_blocking` or
+/// `blocking::unblock`) and await the result. This is useful for CPU-bound or FFI-backed functions
+/// having no real async implementation; note that the arguments have to be `'static` and `Send`.
+/// For `tokio` functions, whose join handles resolve to a `Result`, the panic of the task is
+/// propagated.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(fetch(sync="fetch_blocking", async="fetch")),
+///         sync(feature="use_sync", delegate="futures::executor::block_on"),
+///         async(feature="use_async"),
+///     )]
+///     pub async fn fetch(url: &str) -> usize {
+///         connect(url).await.len()
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub fn fetch_blocking(__arg0: &str) -> usize {
+///         futures::executor::block_on(fetch(__arg0))
+///     }
+///     #[cfg(feature="use_async")]
+///     pub async fn fetch(url: &str) -> usize {
+///         connect(url).await.len()
+///     }
+///     ```
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(checksum(async="checksum_async")),
+///         sync(feature="use_sync"),
+///         async(feature="use_async", delegate="blocking::unblock"),
+///     )]
+///     pub fn checksum(data: Vec<u8>) -> u64 {
+///         data.iter().map(|byte| *byte as u64).sum()
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub fn checksum(data: Vec<u8>) -> u64 {
+///         data.iter().map(|byte| *byte as u64).sum()
+///     }
+///     #[cfg(feature="use_async")]
+///     pub async fn checksum_async(__arg0: Vec<u8>) -> u64 {
+///         blocking::unblock(move || checksum(__arg0)).await
+///     }
+///     ```
+///
+/// - `bridge`
+///
+///     For a trait, additionally implement it for the specified `wrapper` type (a tuple struct with
+/// one generic parameter) over the implementors of the other variant of the trait, so that one
+/// implementation serves both APIs. In the `sync` variant, the async methods of the wrapped value
+/// are called through the `block_on` function (required there); in the `async` variant, the sync
+/// methods are called in the async ones. Associated types and constants are taken from the wrapped
+/// value. If the futures of the `async` variant are `Send`, the wrapped value is required to be
+/// `Send + Sync`. The parameter given at the top level applies to all variants. Since the impl
+/// block names the trait of the other variant, it is emitted only if both variants are enabled.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         idents(Client),
+///         sync(
+///             feature="use_sync",
+///             bridge(wrapper="Blocking", block_on="futures::executor::block_on"),
+///         ),
+///         async(feature="use_async", bridge(wrapper="Nonblocking")),
+///     )]
+///     pub trait Client {
+///         async fn request(&self, uri: &str) -> Result<String, Error>;
+///         fn name(&self) -> String;
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     pub trait ClientSync {
+///         fn request(&self, uri: &str) -> Result<String, Error>;
+///         fn name(&self) -> String;
+///     }
+///     #[cfg(feature="use_sync")]
+///     #[cfg(feature="use_async")]
+///     impl<__MaybeInner: ClientAsync> ClientSync for Blocking<__MaybeInner> {
+///         fn request(&self, __arg1: &str) -> Result<String, Error> {
+///             futures::executor::block_on(<__MaybeInner as ClientAsync>::request(&self.0, __arg1))
+///         }
+///         fn name(&self) -> String {
+///             <__MaybeInner as ClientAsync>::name(&self.0)
+///         }
+///     }
+///     #[cfg(feature="use_async")]
+///     pub trait ClientAsync {
+///         async fn request(&self, uri: &str) -> Result<String, Error>;
+///         fn name(&self) -> String;
+///     }
+///     #[cfg(feature="use_async")]
+///     #[cfg(feature="use_sync")]
+///     impl<__MaybeInner: ClientSync> ClientAsync for Nonblocking<__MaybeInner> {
+///         async fn request(&self, __arg1: &str) -> Result<String, Error> {
+///             <__MaybeInner as ClientSync>::request(&self.0, __arg1)
+///         }
+///         fn name(&self) -> String {
+///             <__MaybeInner as ClientSync>::name(&self.0)
+///         }
+///     }
+///     ```
+///
+/// - `drop_attrs`
+///
+///     Remove any attributes with specified names.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", drop_attrs(attr)),
+///         async(feature="use_async"),
+///     )]
+///     struct Struct {
+///         f: usize,
+///
+///         // This attribute will be removed in sync variant
+///         #[attr(param)]
+///         field1: bool,
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     struct StructSync {
+///         f: usize,
+///         field1: bool,
+///     }
+///     #[cfg(feature="use_async")]
+///     struct StructAsync {
+///         f: usize,
+///         #[attr(param)]
+///         field1: bool,
+///     }
+///     ```
+///
+/// - `attr_policy`
+///
+///     Keep the specified attributes only in the variants of the given kind: `"async"` or
+/// `"sync"`, or everywhere with `"keep"`. An attribute is matched by its full path or by its last
+/// segment. The attributes known to make sense only for async functions and traits
+/// (`async_recursion`, `async_backtrace::framed`, `async_trait` and `must_use` with a message about
+/// futures) are kept only in the `async` variants without this parameter,
+/// `attr_policy(async_recursion = "keep")` overrides that.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         attr_policy(tracing::instrument = "async"),
+///     )]
+///     #[async_recursion::async_recursion]
+///     #[tracing::instrument]
+///     async fn depth(node: &Node) -> usize {
+///         todo!()
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn depth_sync(node: &Node) -> usize {
+///         todo!()
+///     }
+///     #[cfg(feature="use_async")]
+///     #[async_recursion::async_recursion]
+///     #[tracing::instrument]
+///     async fn depth_async(node: &Node) -> usize {
+///         todo!()
+///     }
+///     ```
+///
+/// - `strip_methods`
+///
+///     Remove the calls of the methods with specified names (identifiers or string literals) from
+/// expressions: `fut.boxed()` becomes just `fut`. This is useful for the future adapters having no
+/// sync counterpart, such as `boxed`, `fuse`, `instrument` or `in_current_span`.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", strip_methods(boxed, "instrument")),
+///         async(feature="use_async"),
+///     )]
+///     async fn run(span: Span) -> usize {
+///         fetch().instrument(span).boxed().await
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn run_sync(span: Span) -> usize {
+///         fetch()
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn run_async(span: Span) -> usize {
+///         fetch().instrument(span).boxed().await
+///     }
+///     ```
+///
+/// - `methods`
+///
+///     Rename the called methods: `methods(read_async = "read")` replaces `x.read_async()` with
+/// `x.read()`. Only the method calls are renamed, not the paths or the definitions of the methods.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", methods(connect_async = "connect")),
+///         async(feature="use_async"),
+///     )]
+///     async fn open(client: &Client) -> Connection {
+///         client.connect_async(ADDR).await.unwrap()
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn open_sync(client: &Client) -> Connection {
+///         client.connect(ADDR).unwrap()
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn open_async(client: &Client) -> Connection {
+///         client.connect_async(ADDR).await.unwrap()
+///     }
+///     ```
+///
+/// - `into_future_map`
+///
+///     In the sync variant, replace the awaits of the results of the listed methods with the
+/// calls of the given methods instead of just dropping them. This is useful for the builders
+/// implementing `IntoFuture`, whose blocking counterparts are executed by a method call.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", into_future_map(send = "execute")),
+///         async(feature="use_async"),
+///     )]
+///     async fn fetch(client: &Client) -> Result<Response, Error> {
+///         client.get(URL).send().await
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn fetch_sync(client: &Client) -> Result<Response, Error> {
+///         client.get(URL).send().execute()
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn fetch_async(client: &Client) -> Result<Response, Error> {
+///         client.get(URL).send().await
+///     }
+///     ```
+///
+/// - `add_await`
+///
+///     For the sync-first code: in the `async` variant, append `.await` to the calls of the listed
+/// functions (`fns`, matched by the last segment of the path) and methods (`methods`). The listed
+/// functions and methods defined in the item become async, and so do the functions, methods and
+/// closures in which `.await` was added.
+///
+///     ```rust
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync"),
+///         async(feature="use_async", add_await(fns(fetch), methods(read))),
+///     )]
+///     fn load(conn: &Conn) -> usize {
+///         conn.read() + fetch(conn)
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     #[cfg(feature="use_sync")]
+///     fn load_sync(conn: &Conn) -> usize {
+///         conn.read() + fetch(conn)
+///     }
+///     #[cfg(feature="use_async")]
+///     async fn load_async(conn: &Conn) -> usize {
+///         conn.read().await + fetch(conn).await
+///     }
+///     ```
+///
+/// - `add_generics`, `drop_generics`
+///
+///     Add the generic parameters (given as string literals) to the item in this variant, or
+/// remove the ones with the specified names together with their `where` predicates. Lifetimes are
+/// given as string literals in `drop_generics`. The paths of the removed parameters can be replaced
+/// with `types`.
+///
+///     ```rust
+///     # trait Runtime {}
+///     # struct Blocking;
+///     #[maybe_async_cfg2::maybe(
+///         sync(feature="use_sync", drop_generics(R), types(R = "Blocking")),
+///         async(feature="use_async"),
+///     )]
+///     async fn run<R: Runtime>(rt: &R, n: usize) {
+///         todo!()
+///     }
+///     ```
+///     After conversion:
+///     ```rust
+///     # trait Runtime {}
+///     # struct Blocking;
+///     #[cfg(feature="use_sync")]
+///     fn run_sync(rt: &Blocking, n: usize) {
+///         todo!()
 ///     }
 ///     #[cfg(feature="use_async")]
-///     struct StructAsync {
-///         f: Foo,
+///     async fn run_async<R: Runtime>(rt: &R, n: usize) {
+///         todo!()
 ///     }
 ///     ```
-///  
-/// - `idents`
-///
-///     Defines a list of identifiers that should be renamed depending on the variant of code.
-///
-///     Each identifier can have the following clarifying parameters:
-///
-///     - `snake`, `fn`, `mod`
-///
-///         means that this is the snake-case name of the function or module and it should be
-/// converted by adding the suffixes `"_sync"`/`"_async"` (otherwise, the suffixes
-/// `"Sync"`/`"Async"` will be used).
-///
-///     - `use`
-///
-///         in `use` lists, using this identifier will result in renaming via the `as` expression,
-/// rather than a simple replacement as is. In other cases, a simple replacement will be used.
 ///
-///     - `keep`
-///
-///         this identifier will not be converted anywhere
-///
-///     - `sync`, `async`
+/// - `add_bounds`, `drop_bounds`
 ///
-///         specifies the name that will be used in the corresponding variant of code. Overrides
-/// the standard scheme of suffixes used by default. If the parameter value is omitted,
-/// the identifier will not be renamed in this case.
+///     Add the `where` predicates (given as string literals) to the item in this variant, or
+/// remove the specified bounds from the generic parameters, the `where` clause and the supertraits
+/// of a trait. A trait bound is matched by its full path or by its last segment, lifetimes are
+/// given as string literals.
 ///
 ///     ```rust
 ///     #[maybe_async_cfg2::maybe(
-///         idents(
-///             Foo,
-///             Bar,
-///             baz(fn),
-///             Qux(use),
-///             waldo(sync, async="async_waldo"),
-///             xyzzy(fn, use, sync="xizzy_the_sync_func"),
-///         ),
-///         sync(feature="use_sync"),
-///         async(feature="use_async"),
+///         sync(feature="use_sync", drop_bounds(Unpin)),
+///         async(feature="use_async", add_bounds("T: Send + 'static")),
 ///     )]
-///     async fn func() {
-///         struct Foo {}
-///         use extcrate::{
-///             Bar,
-///             baz,
-///             Qux,
-///             waldo::{
-///                 plugh,
-///                 xyzzy
-///             }
-///         };
-///         let _ = baz( Foo {}, Bar::new() ).await;
-///         let _ = xizzy( Qux::flob(b).await );
+///     async fn run<T: Clone + Unpin>(t: T) {
+///         todo!()
 ///     }
 ///     ```
 ///     After conversion:
 ///     ```rust
 ///     #[cfg(feature="use_sync")]
-///     fn func_sync() {
-///         struct FooSync {}
-///         use extcrate::{
-///             BarSync,
-///             baz_sync,
-///             Qux as QuxSync,
-///             waldo::{
-///                 plugh,
-///                 xyzzy as xizzy_the_sync_func
-///             }
-///         };         
-///         let _ = baz_sync( FooSync {}, BarSync::new() );
-///         let _ = xizzy_the_sync_func( QuxSync::flob() );
+///     fn run_sync<T: Clone>(t: T) {
+///         todo!()
 ///     }
 ///     #[cfg(feature="use_async")]
-///     async fn func_async() {
-///         struct FooAsync {}
-///         use extcrate::{
-///             BarAsync,
-///             baz_async,
-///             Qux as QuxAsync,
-///             async_waldo::{
-///                 plugh,
-///                 xyzzy as xyzzy_async
-///             }
-///         };
-///         let _ = baz_async( FooAsync {}, BarAsync::new() ).await;
-///         let _ = xyzzy_async( QuxAsync::flob().await );     
+///     async fn run_async<T: Clone + Unpin>(t: T)
+///     where
+///         T: Send + 'static,
+///     {
+///         todo!()
 ///     }
 ///     ```
 ///
-/// - `keep_self`
-///
-///     Do not change name of item to which attribute `maybe` refers.
-///
-/// - `self`
-///
-///     Defines the name that will be assigned to the item in this variant.
-///
-/// - `send`
-///
-///     If `send = "Send"` or `send = "true"` is present, the attribute
-/// `#[async_trait::async_trait]` will be added before the async code. If `send = "?Send"` or
-/// `send = "false"` then `#[async_trait::async_trait(?Send)]` will be added.  
-///
-/// - `drop_attrs`
+/// - `relax_bounds`
 ///
-///     Remove any attributes with specified names.
+///     Remove the specified bounds from the generic parameters and the `where` clauses of the item
+/// and of all items nested in it (methods of an `impl` block or a trait) in the `sync` variant.
+/// This is useful for bounds like `Send` or `'static` that only matter for futures. The `where`
+/// predicates left without bounds are removed. Bounds are matched as in `drop_bounds`. When given
+/// at the top level, it applies to all `sync` variants.
 ///
 ///     ```rust
 ///     #[maybe_async_cfg2::maybe(
-///         sync(feature="use_sync", drop_attrs(attr)),
+///         sync(feature="use_sync", relax_bounds(Send, "'static")),
 ///         async(feature="use_async"),
 ///     )]
-///     struct Struct {
-///         f: usize,
-///
-///         // This attribute will be removed in sync variant
-///         #[attr(param)]
-///         field1: bool,
+///     impl<T: Send + 'static> Pool<T> {
+///         async fn spawn<F>(&self, f: F)
+///         where
+///             F: Future + Send + 'static,
+///         {
+///             f.await;
+///         }
 ///     }
 ///     ```
 ///     After conversion:
 ///     ```rust
 ///     #[cfg(feature="use_sync")]
-///     struct StructSync {
-///         f: usize,
-///         field1: bool,
+///     impl<T> PoolSync<T> {
+///         fn spawn<F>(&self, f: F)
+///         where
+///             F: Future,
+///         {
+///             f;
+///         }
 ///     }
 ///     #[cfg(feature="use_async")]
-///     struct StructAsync {
-///         f: usize,
-///         #[attr(param)]
-///         field1: bool,
+///     impl<T: Send + 'static> PoolAsync<T> {
+///         async fn spawn<F>(&self, f: F)
+///         where
+///             F: Future + Send + 'static,
+///         {
+///             f.await;
+///         }
 ///     }
 ///     ```
 ///
@@ -566,8 +2660,8 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 ///     ```
 ///
 /// - In other cases, the following rules apply:
-///     - name-value pairs (`xxx = "yyy"`) with a name other than `key`, `prefix`, `send` and
-/// `feature` will produce an error.
+///     - name-value pairs (`xxx = "yyy"`) with a name other than `key`, `prefix`, `send`,
+/// `test_only`, `stream_macro` and `feature` will produce an error.
 ///     
 ///     - `feature = "..."`, `not(...)`, `all(...)`, `any(...)` will be interpreted as condition for
 /// current variant (as wrapped in `cfg(...)`).
@@ -583,25 +2677,90 @@ const STANDARD_MACROS: &'static [&'static str] = &[
 /// > _Parameter_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;`disable`\
 /// > &nbsp;&nbsp;|&nbsp;`keep_self`\
+/// > &nbsp;&nbsp;|&nbsp;`dedup`\
+/// > &nbsp;&nbsp;|&nbsp;`dispatch` (`=` _STRING_LITERAL_)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`merge_cfgs`\
+/// > &nbsp;&nbsp;|&nbsp;`register` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`use_registered` `(` _STRING_LITERAL_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`prefix` `=` _STRING_LITERAL_\
-/// > &nbsp;&nbsp;|&nbsp;(`sync` | `async`) `(` _VersionParametersList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`default_variant` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`only` `(` _IdentifiersList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`conditions` `(` _IDENTIFIER_ `=` _STRING_LITERAL_ (`,` _IDENTIFIER_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;(`sync` | `async` | `dyn`) `(` _VersionParametersList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`variant` `(` (`mode` `=` _STRING_LITERAL_ `,`)<sup>\?</sup> _VersionParametersList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`exclusive` `(` (_STRING_LITERAL_ | `feature` `=` _STRING_LITERAL_) (`,` _VersionParameter_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`matrix` `(` _Axis_ (`,` _Axis_)<sup>\*</sup> `)`\
 /// > &nbsp;&nbsp;|&nbsp;`idents` `(` _IdentsList_ `)`\
 /// >
+/// > _Axis_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;`axis` `(` _AxisEntry_ (`,` _AxisEntry_)<sup>\*</sup> `)`
+/// >
+/// > _AxisEntry_ :\
+/// > &nbsp;&nbsp;&nbsp;&nbsp;_IDENTIFIER_ `(` (`mode` `=` _STRING_LITERAL_ `,`)<sup>\?</sup> _VersionParametersList_ `)`
+/// >
 /// > _VersionParametersList_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;_VersionParameter_ (`,` _VersionParameter_)<sup>\*</sup>
 /// >
 /// > _VersionParameter_ :\
 /// > &nbsp;&nbsp;&nbsp;&nbsp;`disable`\
 /// > &nbsp;&nbsp;|&nbsp;`keep_self`\
+/// > &nbsp;&nbsp;|&nbsp;`primary`\
+/// > &nbsp;&nbsp;|&nbsp;`alias`\
+/// > &nbsp;&nbsp;|&nbsp;`no_cfg`\
+/// > &nbsp;&nbsp;|&nbsp;`doc_cfg`\
+/// > &nbsp;&nbsp;|&nbsp;`deprecate` `(` (`note` `=` _STRING_LITERAL_)<sup>\?</sup> `,`<sup>\?</sup> (`since` `=` _STRING_LITERAL_)<sup>\?</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`doc_hidden`\
+/// > &nbsp;&nbsp;|&nbsp;`constness` (`=` (`true` | `false`))<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`test_only` (`=` _STRING_LITERAL_)<sup>\?</sup>\
 /// > &nbsp;&nbsp;|&nbsp;`key` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`mode` `=` (`"sync"` | `"async"` | `"dyn"`)\
+/// > &nbsp;&nbsp;|&nbsp;`suffix` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`preset` `=` (`"tokio"` | `"async-std"` | `"wasm"` | `"std"` | `"reqwest"` | `"fs"`)\
+/// > &nbsp;&nbsp;|&nbsp;(`test_attr` | `main_attr`) `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;`feature` `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;`self` `=` _STRING_LITERAL_\
-/// > &nbsp;&nbsp;|&nbsp;`send` `=` (`""` | `"Send"` | `"true"` | `"?Send"` | `"false"`)\
+/// > &nbsp;&nbsp;|&nbsp;`self_path` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`send` `=` (`""` | `"Send"` | `"true"` | `"?Send"` | `"false"` | `"trait_variant"`)\
+/// > &nbsp;&nbsp;|&nbsp;`trait_variant` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`async_trait_path` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`impl_future`\
+/// > &nbsp;&nbsp;|&nbsp;`send_bounds`\
+/// > &nbsp;&nbsp;|&nbsp;`dyn_compat` `=` _STRING_LITERAL_\
 /// > &nbsp;&nbsp;|&nbsp;(`cfg` | `any` | `all` | `not`) `(` _ANY_CFG_CONDITION_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`cond` (`=` _STRING_LITERAL_ | `(` _IDENTIFIER_ `)`)\
 /// > &nbsp;&nbsp;|&nbsp;`idents` `(` _IdentsList_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;(`outer` | `inner`) `(` _AttributesList_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`replace_feature` `(` _STRING_LITERAL_ `,` _STRING_LITERAL_ `)`\
 /// > &nbsp;&nbsp;|&nbsp;`drop_attrs` `(` _IdentifiersList_ `)`\
+/// > &nbsp;&nbsp;|&nbsp;`attr_policy` `(` _Path_ `=` _STRING_LITERAL_ (`,` _Path_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`strip_methods` `(` (_IDENTIFIER_ | _STRING_LITERAL_) (`,` (_IDENTIFIER_ | _STRING_LITERAL_))<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`methods` `(` _IDENTIFIER_ `=` _STRING_LITERAL_ (`,` _IDENTIFIER_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`into_future_map` `(` _IDENTIFIER_ `=` _STRING_LITERAL_ (`,` _IDENTIFIER_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`add_await` `(` ((`fns` | `methods`) `(` _IDENTIFIER_ (`,` _IDENTIFIER_)<sup>\*</sup> `)`)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`add_generics` `(` _STRING_LITERAL_ (`,` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`drop_generics` `(` (_IDENTIFIER_ | _STRING_LITERAL_) (`,` (_IDENTIFIER_ | _STRING_LITERAL_))<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`add_bounds` `(` _STRING_LITERAL_ (`,` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`drop_bounds` `(` (_Path_ | _IDENTIFIER_ | _STRING_LITERAL_) (`,` (_Path_ | _IDENTIFIER_ | _STRING_LITERAL_))<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`relax_bounds` `(` (_Path_ | _IDENTIFIER_ | _STRING_LITERAL_) (`,` (_Path_ | _IDENTIFIER_ | _STRING_LITERAL_))<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`stream_macro` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`process_macros` `(` (_Path_ | _STRING_LITERAL_) (`,` (_Path_ | _STRING_LITERAL_))<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`process_all_macros`\
+/// > &nbsp;&nbsp;|&nbsp;`no_macro_processing` (`(` (_Path_ | _STRING_LITERAL_) (`,` (_Path_ | _STRING_LITERAL_))<sup>\*</sup> `)`)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`spawn` (`=` _STRING_LITERAL_)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`locks` (`=` _STRING_LITERAL_)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`lock_methods` `(` (_IDENTIFIER_ | _STRING_LITERAL_) (`,` (_IDENTIFIER_ | _STRING_LITERAL_))<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`yield_now` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`dyn_markers` (`=` _STRING_LITERAL_)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`timeout` (`=` _STRING_LITERAL_)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`channels` (`(` (_Path_ `=` _STRING_LITERAL_ (`,` _Path_ `=` _STRING_LITERAL_)<sup>\*</sup>)<sup>\?</sup> `)`)<sup>\?</sup>\
+/// > &nbsp;&nbsp;|&nbsp;`types` `(` _Path_ `=` _STRING_LITERAL_ (`,` _Path_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`calls` `(` _Path_ `=` _STRING_LITERAL_ (`,` _Path_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`crates` `(` _IDENTIFIER_ `=` _STRING_LITERAL_ (`,` _IDENTIFIER_ `=` _STRING_LITERAL_)<sup>\*</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`receiver` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`blocking_ext` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`delegate` `=` _STRING_LITERAL_\
+/// > &nbsp;&nbsp;|&nbsp;`bridge` `(` `wrapper` `=` _STRING_LITERAL_ (`,` `block_on` `=` _STRING_LITERAL_)<sup>\?</sup> `)`\
+/// > &nbsp;&nbsp;|&nbsp;`asyncness_removal` `(` (`signature` | `members` | `nested` | `closures`)<sup>\*</sup> `)`\
 /// > &nbsp;&nbsp;|&nbsp;_Attribute_
 /// >
 /// > _Path_ :\
@@ -639,6 +2798,21 @@ pub fn maybe(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream>
 }
 
 /// Marks conditional content that should only be used in the specified variant of code.
+///
+/// Besides items, fields and enum variants, it can mark statements inside
+/// function bodies, e.g. to keep a `select!` only in the async variant.
+///
+/// ```rust
+/// # async fn wait() {}
+/// #[maybe_async_cfg2::maybe(sync(feature="use_sync"), async(feature="use_async"))]
+/// async fn run() -> u32 {
+///     #[maybe_async_cfg2::only_if(async)]
+///     let n = { wait().await; 1 };
+///     #[maybe_async_cfg2::only_if(sync)]
+///     let n = 2;
+///     n
+/// }
+/// ```
 #[manyhow]
 #[proc_macro_attribute]
 pub fn only_if(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
@@ -653,6 +2827,158 @@ pub fn remove_if(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream>
     Ok(body)
 }
 
+/// Marks conditional content that should only be used in the variant with the `sync` key. The same
+/// as `only_if(sync)`.
+///
+/// ```rust
+/// #[maybe_async_cfg2::maybe(
+///     sync(feature="use_sync"),
+///     async(feature="use_async"),
+/// )]
+/// struct Client {
+///     #[maybe_async_cfg2::only_sync]
+///     agent: ureq::Agent,
+///     #[maybe_async_cfg2::only_async]
+///     client: reqwest::Client,
+/// }
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(feature="use_sync")]
+/// struct ClientSync {
+///     agent: ureq::Agent,
+/// }
+/// #[cfg(feature="use_async")]
+/// struct ClientAsync {
+///     client: reqwest::Client,
+/// }
+/// ```
+#[manyhow]
+#[proc_macro_attribute]
+pub fn only_sync(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
+    Ok(body)
+}
+
+/// Marks conditional content that should only be used in the variant with the `async` key. The same
+/// as `only_if(async)`.
+#[manyhow]
+#[proc_macro_attribute]
+pub fn only_async(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
+    Ok(body)
+}
+
+/// Defines an associated type of a trait (or of a trait implementation) per variant of code.
+///
+/// In the variants listed in the attribute parameters (by their keys), the associated type will be
+/// removed, and all its usages as `Self::Type` will be replaced with the specified type. In all
+/// other variants the associated type stays as is.
+///
+/// ```rust
+/// #[maybe_async_cfg2::maybe(
+///     sync(feature="use_sync"),
+///     async(feature="use_async"),
+/// )]
+/// trait Connector {
+///     #[maybe_async_cfg2::assoc_type(sync = "Conn")]
+///     type ConnectFuture<'a>: Future<Output = Conn> + 'a where Self: 'a;
+///
+///     fn connect(&self) -> Self::ConnectFuture<'_>;
+/// }
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(feature="use_sync")]
+/// trait ConnectorSync {
+///     fn connect(&self) -> Conn;
+/// }
+/// #[cfg(feature="use_async")]
+/// trait ConnectorAsync {
+///     type ConnectFuture<'a>: Future<Output = Conn> + 'a where Self: 'a;
+///
+///     fn connect(&self) -> Self::ConnectFuture<'_>;
+/// }
+/// ```
+#[manyhow]
+#[proc_macro_attribute]
+pub fn assoc_type(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
+    Ok(body)
+}
+
+/// Defines the type of a struct (or enum variant) field per variant of code.
+///
+/// In the variants listed in the attribute parameters (by their keys), the type of the field will
+/// be replaced with the specified one. In all other variants the field type stays as is.
+///
+/// ```rust
+/// #[maybe_async_cfg2::maybe(
+///     sync(feature="use_sync"),
+///     async(feature="use_async"),
+/// )]
+/// struct Storage {
+///     #[maybe_async_cfg2::field_type(sync = "std::fs::File", async = "tokio::fs::File")]
+///     file: File,
+///     #[maybe_async_cfg2::field_type(async = "std::sync::Arc<tokio::sync::Mutex<Vec<u8>>>")]
+///     buf: Vec<u8>,
+/// }
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(feature="use_sync")]
+/// struct StorageSync {
+///     file: std::fs::File,
+///     buf: Vec<u8>,
+/// }
+/// #[cfg(feature="use_async")]
+/// struct StorageAsync {
+///     file: tokio::fs::File,
+///     buf: std::sync::Arc<tokio::sync::Mutex<Vec<u8>>>,
+/// }
+/// ```
+#[manyhow]
+#[proc_macro_attribute]
+pub fn field_type(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
+    Ok(body)
+}
+
+/// Defines the receiver of a method of a trait (or of an impl block) per variant of code.
+///
+/// In the variants listed in the attribute parameters (by their keys), the receiver of the method
+/// will be replaced with the specified one. This takes precedence over the `receiver` parameter of
+/// the variant.
+///
+/// ```rust
+/// #[maybe_async_cfg2::maybe(
+///     sync(feature="use_sync"),
+///     async(feature="use_async"),
+/// )]
+/// impl Device {
+///     #[maybe_async_cfg2::receiver(sync = "&mut self")]
+///     async fn read(&self, buf: &mut [u8]) -> usize {
+///         self.port.read(buf).await
+///     }
+/// }
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(feature="use_sync")]
+/// impl DeviceSync {
+///     fn read(&mut self, buf: &mut [u8]) -> usize {
+///         self.port.read(buf)
+///     }
+/// }
+/// #[cfg(feature="use_async")]
+/// impl DeviceAsync {
+///     async fn read(&self, buf: &mut [u8]) -> usize {
+///         self.port.read(buf).await
+///     }
+/// }
+/// ```
+#[manyhow]
+#[proc_macro_attribute]
+pub fn receiver(_: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
+    Ok(body)
+}
+
 /// Does nothing (leaves content intact).
 #[manyhow]
 #[proc_macro_attribute]
@@ -714,3 +3040,156 @@ pub fn remove(_: TokenStream, _: TokenStream) -> syn::Result<TokenStream> {
 pub fn content(body: TokenStream) -> syn::Result<TokenStream> {
     macros::content(body)
 }
+
+/// Reports conflicting or missing variant selections
+///
+/// The `guard` macro takes the variants (as `maybe` does, the crate-wide defaults from `Cargo.toml`
+/// are applied too) and emits `compile_error!` under the conditions where several variants are
+/// enabled at once (`exclusive`) or none of them is enabled (`required`). Both checks are emitted
+/// unless one of them is specified. This way, a wrong feature selection produces one clear error
+/// instead of many unresolved names. Variants without conditions are not checked.
+///
+/// ```rust
+/// maybe_async_cfg2::guard!(exclusive, sync(feature="use_sync"), async(feature="use_async"));
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(all(feature = "use_sync", feature = "use_async"))]
+/// compile_error!(
+///     "The `sync` and `async` variants are enabled at the same time \
+///     (`feature = \"use_sync\"` and `feature = \"use_async\"`), only one of them can be enabled"
+/// );
+/// ```
+#[manyhow]
+#[proc_macro]
+pub fn guard(body: TokenStream) -> syn::Result<TokenStream> {
+    macros::guard(body)
+}
+
+/// Re-exports the enabled variants of items under their original names
+///
+/// The `facade` macro takes the paths of items, optionally followed by `;` and the variants (as
+/// `maybe` does, the crate-wide defaults from `Cargo.toml` are applied too), and emits a re-export
+/// of every item for each variant, under the condition of the variant. If several variants are
+/// enabled at once, the items of the first of them are re-exported. The names of the variants are
+/// made the same way as in `maybe` (names starting with a lowercase letter are snake-cased).
+/// The re-exports are `pub` unless another visibility is specified at the beginning.
+///
+/// ```rust
+/// pub mod client {
+///     #[maybe_async_cfg2::maybe(sync(feature="use_sync"), async(feature="use_async"))]
+///     pub struct Client;
+///
+///     #[maybe_async_cfg2::maybe(sync(feature="use_sync"), async(feature="use_async"))]
+///     pub async fn run() {}
+/// }
+///
+/// pub mod api {
+///     maybe_async_cfg2::facade! {
+///         crate::client::Client, crate::client::run;
+///         sync(feature="use_sync"), async(feature="use_async")
+///     }
+/// }
+/// ```
+/// After conversion:
+/// ```rust
+/// pub mod api {
+///     #[cfg(feature = "use_sync")]
+///     pub use crate::client::ClientSync as Client;
+///     #[cfg(feature = "use_sync")]
+///     pub use crate::client::run_sync as run;
+///     #[cfg(all(feature = "use_async", not(any(feature = "use_sync"))))]
+///     pub use crate::client::ClientAsync as Client;
+///     #[cfg(all(feature = "use_async", not(any(feature = "use_sync"))))]
+///     pub use crate::client::run_async as run;
+/// }
+/// ```
+#[manyhow]
+#[proc_macro]
+pub fn facade(body: TokenStream) -> syn::Result<TokenStream> {
+    macros::facade(body)
+}
+
+/// Defines the `MaybeSend` and `MaybeSync` marker traits
+///
+/// The `maybe_send_alias` macro takes the variants (as `maybe` does, the crate-wide defaults from
+/// `Cargo.toml` are applied too) and emits the `MaybeSend` and `MaybeSync` traits, implemented for
+/// all types. Under the conditions of the `async` variants (unless they are `?Send`, see the `send`
+/// parameter), the traits require `Send` and `Sync`; otherwise, they require nothing. Use them in
+/// bounds that are needed by thread-safe futures only. The traits are defined once: if both kinds
+/// of variants are enabled, the markers are required. The traits are `pub` unless another
+/// visibility is specified at the beginning.
+///
+/// ```rust
+/// maybe_async_cfg2::maybe_send_alias!(sync(feature="use_sync"), async(feature="use_async"));
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(any(feature = "use_async"))]
+/// pub trait MaybeSend: Send {}
+/// #[cfg(any(feature = "use_async"))]
+/// impl<T: Send + ?Sized> MaybeSend for T {}
+/// #[cfg(all(any(feature = "use_sync"), not(any(feature = "use_async"))))]
+/// pub trait MaybeSend {}
+/// #[cfg(all(any(feature = "use_sync"), not(any(feature = "use_async"))))]
+/// impl<T: ?Sized> MaybeSend for T {}
+///
+/// #[cfg(any(feature = "use_async"))]
+/// pub trait MaybeSync: Sync {}
+/// #[cfg(any(feature = "use_async"))]
+/// impl<T: Sync + ?Sized> MaybeSync for T {}
+/// #[cfg(all(any(feature = "use_sync"), not(any(feature = "use_async"))))]
+/// pub trait MaybeSync {}
+/// #[cfg(all(any(feature = "use_sync"), not(any(feature = "use_async"))))]
+/// impl<T: ?Sized> MaybeSync for T {}
+/// ```
+#[manyhow]
+#[proc_macro]
+pub fn maybe_send_alias(body: TokenStream) -> syn::Result<TokenStream> {
+    macros::maybe_send_alias(body)
+}
+
+/// Defines a reusable set of `maybe` parameters
+///
+/// The `define_preset` macro generates a macro with the specified name, which wraps its body into
+/// [`content!`] with the specified parameters as the defaults. Inside it, the `maybe` attributes
+/// get the variants of the preset unless they define their own ones. With `pub(crate)` (or another
+/// restricted visibility), the generated macro is re-exported, so it can be used in other modules
+/// by its path.
+///
+/// The generated macro is applied around the items (`client_variants! { .. }`), not as an
+/// attribute (`#[client_variants::maybe]`): attribute macros can only be defined by procedural
+/// macro crates, so a macro generated by another macro cannot be one.
+///
+/// ```rust
+/// maybe_async_cfg2::define_preset!(
+///     pub(crate) client_variants(
+///         sync(feature="use_sync"),
+///         async(feature="use_async"),
+///         idents(Conn),
+///     )
+/// );
+///
+/// client_variants! {
+///     #[maybe_async_cfg2::maybe]
+///     struct Client {
+///         conn: Conn,
+///     }
+/// }
+/// ```
+/// After conversion:
+/// ```rust
+/// #[cfg(feature = "use_sync")]
+/// struct ClientSync {
+///     conn: ConnSync,
+/// }
+/// #[cfg(feature = "use_async")]
+/// struct ClientAsync {
+///     conn: ConnAsync,
+/// }
+/// ```
+#[manyhow]
+#[proc_macro]
+pub fn define_preset(body: TokenStream) -> syn::Result<TokenStream> {
+    macros::define_preset(body)
+}
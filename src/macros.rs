@@ -1,23 +1,25 @@
+use std::collections::HashMap;
 #[allow(unused_imports)]
 use std::iter::FromIterator;
 
 use proc_macro::TokenStream;
 
-use proc_macro2::TokenStream as TokenStream2;
-use syn::{spanned::Spanned, visit_mut::VisitMut};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{spanned::Spanned, visit_mut::VisitMut, Ident};
 
 #[allow(unused_imports)]
 use quote::{quote, ToTokens};
 
 use crate::{
     debug::*,
-    params::{ConvertMode, MacroParameters},
+    params::{ConvertMode, Deprecation, MacroParameterVersion, MacroParameters},
+    utils::{make_warning_block, DefinePreset, Facade, MaybeSendAlias},
     visit_ext::Visitor,
     visitor_async::{
-        remove_asyncness_on_fn, remove_asyncness_on_impl, remove_asyncness_on_trait,
-        AsyncAwaitVisitor,
+        check_left_await, make_blocking_ext_impl, make_blocking_ext_trait, make_bridge_impl,
+        make_delegate_fn, make_dyn_blanket_impl, make_send_fn, AsyncAwaitVisitor,
     },
-    visitor_content::ContentVisitor,
+    visitor_content::{split_use_trees, ContentVisitor},
     MACRO_MAYBE_NAME,
 };
 
@@ -26,7 +28,7 @@ use crate::{
 pub fn maybe(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     dump_maybe!(&args, &input);
 
-    let params = MacroParameters::from_tokens(args)?;
+    let mut params = MacroParameters::from_tokens(args)?;
     dump_params!("maybe params", &params);
 
     if params.disable_get() {
@@ -37,36 +39,1311 @@ pub fn maybe(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream>
         return convert(params, input, convert_mode);
     }
 
+    if let Some(tokens) = registry_apply(&mut params, &input)? {
+        return Ok(tokens);
+    }
+    params = crate_defaults_apply(params)?;
+    params.conditions_apply(true)?;
+    params.variant_keys_apply();
+    params.only_apply()?;
+    force_variant(&mut params)?;
+    check_supported_items(&input)?;
+    check_name_collisions(&params, &input)?;
+    dyn_from_set(&mut params, &input)?;
+    delegate_set(&mut params, &input)?;
+    let modules = self_path_apply(&mut params, &input);
+    let input = if params.merge_cfgs_get() {
+        merge_cfgs(&mut params, input)?
+    } else {
+        input
+    };
+
+    if params.dedup_get() {
+        return maybe_dedup(&params, &modules, input);
+    }
+
     let mut tokens = TokenStream::new();
 
-    for version in &params.versions {
+    for (version, module) in params.versions.iter().zip(&modules) {
         let mut ts = TokenStream2::new();
+        let mut item = TokenStream2::new();
 
         match version.kind {
-            ConvertMode::IntoAsync | ConvertMode::IntoSync => {
+            ConvertMode::IntoAsync | ConvertMode::IntoSync | ConvertMode::IntoDyn => {
                 let _ = version
                     .params
                     .extend_tokenstream2_with_cfg_outer_attrs(&mut ts)?;
                 let name = params.make_self_path(MACRO_MAYBE_NAME);
                 let args = version.params.to_tokens(Some(version.kind));
-                ts.extend(quote!(#[#name(#args)]));
+                item.extend(quote!(#[#name(#args)]));
 
                 let _ = version
                     .params
-                    .extend_tokenstream2_with_inner_attrs(&mut ts)?;
+                    .extend_tokenstream2_with_inner_attrs(&mut item)?;
             }
         }
 
-        let ts: TokenStream = ts.into();
-        tokens.extend(ts);
-        tokens.extend(input.clone());
+        ts.extend(make_deprecated_attr(&params, version, &input)?);
+        item.extend(TokenStream2::from(input.clone()));
+        ts.extend(wrap_in_module(item, module));
+        ts.extend(make_aliases(&params, version, &input));
+        tokens.extend(TokenStream::from(ts));
     }
+    tokens.extend(TokenStream::from(make_dispatch(&params, &modules, &input)?));
+    tokens.extend(TokenStream::from(tracked_inputs(&input)));
 
     dump_tokens!("maybe after", &tokens);
 
     Ok(tokens)
 }
 
+/// Returns the name of the implementing type of an impl block, which may be behind a reference.
+fn impl_self_ident(ty: &syn::Type) -> Option<&syn::Ident> {
+    match ty {
+        syn::Type::Path(syn::TypePath { path, .. }) => path.segments.last().map(|last| &last.ident),
+        syn::Type::Reference(ty) => impl_self_ident(&ty.elem),
+        syn::Type::Paren(ty) => impl_self_ident(&ty.elem),
+        syn::Type::Group(ty) => impl_self_ident(&ty.elem),
+        _ => None,
+    }
+}
+
+/// Returns the name of the item (and whether it is snake-cased) as it will be registered in
+/// `idents` during conversion.
+fn item_self_name(item: &syn::Item) -> Option<(&syn::Ident, bool)> {
+    match item {
+        syn::Item::Impl(item) => impl_self_ident(&item.self_ty).map(|ident| (ident, false)),
+        syn::Item::Struct(item) => Some((&item.ident, false)),
+        syn::Item::Enum(item) => Some((&item.ident, false)),
+        syn::Item::Trait(item) => Some((&item.ident, false)),
+        syn::Item::Fn(item) => Some((&item.sig.ident, true)),
+        syn::Item::Mod(item) => Some((&item.ident, true)),
+        _ => None,
+    }
+}
+
+/// Describes the kind of an item that cannot be converted.
+fn unsupported_item_kind(item: &syn::Item) -> Option<&'static str> {
+    match item {
+        syn::Item::Impl(_)
+        | syn::Item::Struct(_)
+        | syn::Item::Enum(_)
+        | syn::Item::Trait(_)
+        | syn::Item::Fn(_)
+        | syn::Item::Use(_)
+        | syn::Item::Mod(_) => None,
+        syn::Item::Const(_) => Some("const items"),
+        syn::Item::Static(_) => Some("static items"),
+        syn::Item::Type(_) => Some("type aliases"),
+        syn::Item::Union(_) => Some("unions"),
+        syn::Item::TraitAlias(_) => Some("trait aliases"),
+        syn::Item::ExternCrate(_) => Some("`extern crate` items"),
+        syn::Item::ForeignMod(_) => Some("extern blocks"),
+        syn::Item::Macro(item) if item.ident.is_some() => Some("`macro_rules!` definitions"),
+        syn::Item::Macro(_) => Some("macro invocations"),
+        syn::Item::Macro2(_) => Some("macro definitions"),
+        _ => Some("this kind of items"),
+    }
+}
+
+fn unsupported_item_error(item: &syn::Item, kind: &str) -> syn::Error {
+    syn::Error::new(
+        item.span(),
+        format!(
+            "`maybe` cannot be applied to {}: only impl, struct, enum, trait, fn, use and mod \
+            items are supported. To keep this item unchanged in all variants, place it inside \
+            `content!` without `maybe` (or mark it with `noop`)",
+            kind
+        ),
+    )
+}
+
+/// Reports unsupported items once, before they are copied into every variant.
+fn check_supported_items(input: &TokenStream) -> syn::Result<()> {
+    let file = match syn::parse::<syn::File>(input.clone()) {
+        Ok(file) => file,
+        Err(_) => {
+            if let Some(span) = find_gen_block(TokenStream2::from(input.clone())) {
+                return Err(syn::Error::new(
+                    span,
+                    "`gen` and `async gen` blocks are not supported yet, move them out of the \
+                    converted item",
+                ));
+            }
+            // the error will be reported during conversion
+            return Ok(());
+        }
+    };
+
+    for item in &file.items {
+        if let Some(kind) = unsupported_item_kind(item) {
+            return Err(unsupported_item_error(item, kind));
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds a `gen { .. }`, `gen move { .. }` or `async gen { .. }` block, which syn cannot parse yet.
+fn find_gen_block(ts: TokenStream2) -> Option<Span> {
+    let tokens = ts.into_iter().collect::<Vec<_>>();
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            proc_macro2::TokenTree::Ident(ident) if ident == "gen" => {
+                let mut rest = tokens[index + 1..].iter();
+                let next = match rest.next() {
+                    Some(proc_macro2::TokenTree::Ident(ident)) if ident == "move" => rest.next(),
+                    next => next,
+                };
+                if let Some(proc_macro2::TokenTree::Group(group)) = next {
+                    if group.delimiter() == proc_macro2::Delimiter::Brace {
+                        let start = match index.checked_sub(1).map(|index| &tokens[index]) {
+                            Some(proc_macro2::TokenTree::Ident(ident)) if ident == "async" => {
+                                ident.span()
+                            }
+                            _ => ident.span(),
+                        };
+                        return Some(start.join(group.span()).unwrap_or(start));
+                    }
+                }
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                if let Some(span) = find_gen_block(group.stream()) {
+                    return Some(span);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Checks that renaming does not produce the same name for different identifiers in any variant.
+fn check_name_collisions(params: &MacroParameters, input: &TokenStream) -> syn::Result<()> {
+    let file = match syn::parse::<syn::File>(input.clone()) {
+        Ok(file) => file,
+        // the error will be reported during conversion
+        Err(_) => return Ok(()),
+    };
+
+    for version in &params.versions {
+        let mut version_params = version.params.clone();
+        let mut spans = HashMap::new();
+        for (ident, snake_case) in file.items.iter().filter_map(item_self_name) {
+            version_params.original_self_name_set(ident.to_string(), snake_case);
+            spans.insert(ident.to_string(), ident.span());
+        }
+
+        let mut targets: HashMap<String, (String, bool)> = HashMap::new();
+        for (name, target, explicit) in version_params.idents_renamed(version.kind) {
+            if let Some((other, other_explicit)) = targets.get(&target) {
+                // several names may be mapped to the same one on purpose
+                if explicit && *other_explicit {
+                    continue;
+                }
+
+                let span = spans
+                    .get(&name)
+                    .or_else(|| spans.get(other))
+                    .copied()
+                    .unwrap_or_else(Span::call_site);
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "Identifiers `{}` and `{}` are both renamed to `{}` in variant `{}`",
+                        other,
+                        name,
+                        target,
+                        version_params.key_get().unwrap_or(version.kind.to_str()),
+                    ),
+                ));
+            }
+            targets.insert(target, (name, explicit));
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes the name of the macro generated for `register = "Name"`. The name may be a path to the
+/// module of the registering item, the last segment is the registered name.
+fn registered_macro_path(name: &str) -> syn::Result<syn::Path> {
+    let mut path = syn::parse_str::<syn::Path>(name).map_err(|_| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("Expected the name of registered parameters, got `{}`", name),
+        )
+    })?;
+    if let Some(last) = path.segments.last_mut() {
+        last.ident = quote::format_ident!("__maybe_registered_{}", last.ident);
+    }
+    Ok(path)
+}
+
+/// For `register = "Name"`, generates a `macro_rules!` macro which applies the parameters to
+/// another item (with its own parameters added), and the item itself with the rest of the
+/// parameters. For `use_registered(...)`, wraps the item into the invocation of such a macro. This
+/// way, the parameters do not depend on the order in which the items are expanded.
+fn registry_apply(
+    params: &mut MacroParameters,
+    input: &TokenStream,
+) -> syn::Result<Option<TokenStream>> {
+    if let Some(name) = params.use_registered_take() {
+        if !params.versions.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "Variants cannot be specified along with `use_registered`, they are taken from \
+                the registered parameters",
+            ));
+        }
+
+        let path = registered_macro_path(&name)?;
+        let own = params.to_tokens(None);
+        let input = TokenStream2::from(input.clone());
+        let ts = quote!(#path! { (#own) #input });
+        dump_tokens2!("use_registered after", &ts);
+        return Ok(Some(ts.into()));
+    }
+
+    let name = match params.register_take() {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let path = registered_macro_path(&name)?;
+    let ident = match path.get_ident() {
+        Some(ident) => ident,
+        None => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("Expected a name for `register`, got `{}`", name),
+            ))
+        }
+    };
+
+    // the names of registered traits are converted in the impl blocks as well
+    let mut registered = params.clone();
+    if let Ok(file) = syn::parse::<syn::File>(input.clone()) {
+        for item in &file.items {
+            if let syn::Item::Trait(item) = item {
+                for version in &mut registered.versions {
+                    version
+                        .params
+                        .original_self_name_set(item.ident.to_string(), false);
+                }
+            }
+        }
+    }
+
+    let maybe = params.make_self_path(MACRO_MAYBE_NAME);
+    let registered = registered.to_tokens(None);
+    let args = params.to_tokens(None);
+    let input = TokenStream2::from(input.clone());
+    let ts = quote! {
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #ident {
+            (($($own:tt)*) $($item:tt)*) => {
+                #[#maybe(#registered, $($own)*)]
+                $($item)*
+            };
+        }
+        #[allow(unused_imports)]
+        pub(crate) use #ident;
+
+        #[#maybe(#args)]
+        #input
+    };
+    dump_tokens2!("register after", &ts);
+    Ok(Some(ts.into()))
+}
+
+/// The section of `Cargo.toml` with the crate-wide default parameters.
+const METADATA_SECTION: &str = "maybe-async-cfg2";
+
+/// Reads the `defaults` key of the `[package.metadata.maybe-async-cfg2]` section of `Cargo.toml` of
+/// the crate being compiled. It is read for every item (not cached), since it can change during the
+/// life of the process running the macros, such as a language server.
+fn crate_defaults() -> syn::Result<Option<String>> {
+    let dir = match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => dir,
+        Err(_) => return Ok(None),
+    };
+    let path = std::path::Path::new(&dir).join("Cargo.toml");
+    let manifest = match std::fs::read_to_string(&path) {
+        Ok(manifest) => manifest,
+        Err(_) => return Ok(None),
+    };
+    let error = |msg: String| {
+        syn::Error::new(
+            Span::call_site(),
+            format!(
+                "Cannot read the defaults from `{}`: {}",
+                path.display(),
+                msg
+            ),
+        )
+    };
+    manifest_defaults(&manifest).map_err(error)
+}
+
+/// Finds the `defaults` key in the metadata section of the manifest. Only the
+/// `[package.metadata.maybe-async-cfg2]` table header and the single-line string values are
+/// recognized, the section given in other forms (dotted keys, inline tables) is an error.
+fn manifest_defaults(manifest: &str) -> Result<Option<String>, String> {
+    let section = format!("package.metadata.{}", METADATA_SECTION);
+    let mut table = String::new();
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            // `[package.metadata."maybe-async-cfg2"] # comment`
+            table = line.split(']').next().unwrap_or_default()[1..]
+                .replace(|c: char| c.is_whitespace() || c == '"', "");
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (
+                key.replace(|c: char| c.is_whitespace() || c == '"', ""),
+                value,
+            ),
+            None => continue,
+        };
+        if table == section {
+            if key == "defaults" {
+                return manifest_string(value.trim()).map(Some);
+            }
+            continue;
+        }
+        // `maybe-async-cfg2 = { defaults = ".." }` or `maybe-async-cfg2.defaults = ".."` in
+        // `[package.metadata]`, and the like
+        let path = if table.is_empty() {
+            key
+        } else {
+            format!("{}.{}", table, key)
+        };
+        if path.starts_with(&section) {
+            return Err(format!(
+                "the defaults have to be specified as `defaults = \"...\"` under the `[{}]` \
+                table header",
+                section
+            ));
+        }
+    }
+    Ok(None)
+}
+
+/// Parses a basic (`"..."`) or literal (`'...'`) single-line string of TOML.
+fn manifest_string(value: &str) -> Result<String, String> {
+    let error = || "`defaults` has to be a single-line string".to_string();
+    let mut chars = value.chars();
+    let quote = chars
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')
+        .ok_or_else(error)?;
+    let mut string = String::new();
+    loop {
+        let c = match chars.next().ok_or_else(error)? {
+            c if c == quote => break,
+            '\\' if quote == '"' => match chars.next().ok_or_else(error)? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '"' => '"',
+                '\\' => '\\',
+                'u' => {
+                    let code = chars.by_ref().take(4).collect::<String>();
+                    u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(error)?
+                }
+                _ => return Err(error()),
+            },
+            c => c,
+        };
+        string.push(c);
+    }
+    // only a comment can follow the string (this also rejects the multi-line strings)
+    match chars.as_str().trim_start() {
+        rest if rest.is_empty() || rest.starts_with('#') => Ok(string),
+        _ => Err(error()),
+    }
+}
+
+/// Applies the crate-wide default parameters from `Cargo.toml` to the parameters of an item. The
+/// variants of the defaults are used if the item does not define its own ones.
+fn crate_defaults_apply(mut params: MacroParameters) -> syn::Result<MacroParameters> {
+    let defaults = match crate_defaults()? {
+        Some(defaults) => defaults,
+        None => return Ok(params),
+    };
+    let error = |msg: String| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("Invalid defaults in `Cargo.toml`: {}", msg),
+        )
+    };
+    let tokens: TokenStream = defaults
+        .parse()
+        .map_err(|e: proc_macro::LexError| error(e.to_string()))?;
+    let defaults = MacroParameters::from_tokens(tokens).map_err(|e| error(e.to_string()))?;
+
+    MacroParameters::apply_parent(&mut params, &defaults)?;
+    if params.versions.is_empty() {
+        params.versions = defaults.versions.clone();
+    }
+
+    // the variants are built again to get the inherited parameters
+    MacroParameters::from_tokens(params.to_tokens(None).into())
+}
+
+/// The environment variable that makes `maybe` emit only the variant with the specified key,
+/// without its condition.
+const FORCE_VARIANT_VAR: &str = "MAYBE_ASYNC_FORCE_VARIANT";
+
+fn force_variant(params: &mut MacroParameters) -> syn::Result<()> {
+    let key = match std::env::var(FORCE_VARIANT_VAR) {
+        Ok(key) if !key.is_empty() => key,
+        _ => return Ok(()),
+    };
+
+    let keys = params
+        .versions
+        .iter()
+        .filter_map(|version| version.params.key_get())
+        .collect::<Vec<_>>();
+    if !keys.is_empty() && !keys.contains(&key.as_str()) {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!(
+                "`{}` is set to unknown variant `{}`, the variants of this item are `{}`",
+                FORCE_VARIANT_VAR,
+                key,
+                keys.join("`, `")
+            ),
+        ));
+    }
+
+    params
+        .versions
+        .retain(|version| version.params.key_get() == Some(key.as_str()));
+    for version in &mut params.versions {
+        version.params.unconditional_set();
+    }
+    Ok(())
+}
+
+/// Makes cargo track the inputs of the macro read outside of the source code, the environment
+/// variable of `force_variant` and `Cargo.toml` with the crate-wide defaults, so that the items are
+/// expanded again when they change. Methods do not get it, since a `const _` cannot be placed in
+/// impl blocks.
+fn tracked_inputs(input: &TokenStream) -> TokenStream2 {
+    if let Ok(item) = syn::parse::<syn::ItemFn>(input.clone()) {
+        if item.sig.receiver().is_some() {
+            return TokenStream2::new();
+        }
+    }
+
+    let mut ts = quote!(
+        const _: Option<&str> = ::core::option_env!(#FORCE_VARIANT_VAR);
+    );
+    let has_manifest = std::env::var("CARGO_MANIFEST_DIR")
+        .is_ok_and(|dir| std::path::Path::new(&dir).join("Cargo.toml").is_file());
+    if has_manifest {
+        ts.extend(quote! {
+            const _: &str = ::core::include_str!(::core::concat!(
+                ::core::env!("CARGO_MANIFEST_DIR"),
+                "/Cargo.toml"
+            ));
+        });
+    }
+    ts
+}
+
+/// Makes the condition under which only the first of the variants is enabled: `None` if the variant
+/// is never the first (an earlier one has no condition), `Some(None)` if it is always.
+fn first_enabled_cfg(
+    cfg: Option<syn::Meta>,
+    earlier: &[Option<syn::Meta>],
+) -> Option<Option<TokenStream2>> {
+    if earlier.iter().any(Option::is_none) {
+        return None;
+    }
+    Some(match (cfg, earlier.is_empty()) {
+        (cfg, true) => cfg.map(|cfg| quote!(#cfg)),
+        (None, false) => Some(quote!(not(any(#(#earlier),*)))),
+        (Some(cfg), false) => Some(quote!(all(#cfg, not(any(#(#earlier),*))))),
+    })
+}
+
+/// Makes the original names of the renamed items refer to the variant under its condition (the
+/// `alias` parameter): a type alias for structs and enums, a `use` declaration for traits,
+/// functions and modules. The names kept by a `primary` variant are not aliased. If several
+/// variants are enabled at once, the first of them gets the aliases.
+fn make_aliases(
+    params: &MacroParameters,
+    version: &MacroParameterVersion,
+    input: &TokenStream,
+) -> TokenStream2 {
+    let mut ts = TokenStream2::new();
+    if !version.params.alias_get() || params.versions.iter().any(|v| v.params.primary_get()) {
+        return ts;
+    }
+    let file = match syn::parse::<syn::File>(input.clone()) {
+        Ok(file) => file,
+        Err(_) => return ts,
+    };
+
+    let earlier = params
+        .versions
+        .iter()
+        .take_while(|v| !std::ptr::eq(*v, version))
+        .filter(|v| v.params.alias_get())
+        .map(|v| v.params.cfg_get())
+        .collect::<Vec<_>>();
+    let cfg = match first_enabled_cfg(version.params.cfg_get(), &earlier) {
+        Some(cfg) => cfg.map(|cfg| quote!(#[cfg(#cfg)])),
+        None => return ts,
+    };
+    for item in &file.items {
+        let (ident, snake_case) = match item_self_name(item) {
+            Some(name) if !matches!(item, syn::Item::Impl(_)) => name,
+            _ => continue,
+        };
+        let renamed =
+            version
+                .params
+                .self_name_renamed(&ident.to_string(), snake_case, version.kind);
+        if *ident == renamed {
+            continue;
+        }
+        let renamed = Ident::new(&renamed, ident.span());
+
+        let alias = match item {
+            syn::Item::Struct(syn::ItemStruct { vis, generics, .. })
+            | syn::Item::Enum(syn::ItemEnum { vis, generics, .. }) => {
+                // bounds are not enforced in type aliases, the defaults are kept
+                let mut alias_generics = generics.clone();
+                alias_generics.where_clause = None;
+                for param in alias_generics.params.iter_mut() {
+                    match param {
+                        syn::GenericParam::Type(param) => {
+                            param.colon_token = None;
+                            param.bounds.clear();
+                        }
+                        syn::GenericParam::Lifetime(param) => {
+                            param.colon_token = None;
+                            param.bounds.clear();
+                        }
+                        syn::GenericParam::Const(_) => {}
+                    }
+                }
+                let (_, ty_generics, _) = generics.split_for_impl();
+                quote!(#vis type #ident #alias_generics = #renamed #ty_generics;)
+            }
+            syn::Item::Trait(syn::ItemTrait { vis, .. })
+            | syn::Item::Fn(syn::ItemFn { vis, .. })
+            | syn::Item::Mod(syn::ItemMod { vis, .. }) => {
+                quote!(#vis use self::#renamed as #ident;)
+            }
+            _ => continue,
+        };
+        ts.extend(quote!(#cfg #alias));
+    }
+    ts
+}
+
+/// Makes the `#[deprecated]` attribute for the `deprecate(...)` parameter of the variant.
+fn make_deprecated_attr(
+    params: &MacroParameters,
+    version: &MacroParameterVersion,
+    input: &TokenStream,
+) -> syn::Result<TokenStream2> {
+    let deprecate = match version.params.deprecate_get() {
+        Some(deprecate) => deprecate,
+        None => return Ok(TokenStream2::new()),
+    };
+
+    let mut args = vec![];
+    if let Some(since) = &deprecate.since {
+        args.push(quote!(since = #since));
+    }
+    if let Some(note) = &deprecate.note {
+        let item = syn::parse::<syn::File>(input.clone())
+            .ok()
+            .and_then(|file| {
+                file.items
+                    .iter()
+                    .find_map(item_self_name)
+                    .map(|(i, s)| (i.to_string(), s))
+            });
+        let (original, snake_case) = item.unwrap_or_default();
+        let key = version.params.key_get().unwrap_or(version.kind.to_str());
+        let name_in = |key: &str| {
+            params
+                .versions
+                .iter()
+                .find(|v| v.params.key_get() == Some(key))
+                .map(|v| v.params.self_name_renamed(&original, snake_case, v.kind))
+        };
+        let note = Deprecation::interpolate(note, key, &original, name_in)
+            .map_err(|msg| syn::Error::new(Span::call_site(), msg))?;
+        args.push(quote!(note = #note));
+    }
+
+    Ok(quote!(#[deprecated(#(#args),*)]))
+}
+
+/// Moves the `#[cfg]` attributes of the item into the conditions of the variants.
+fn merge_cfgs(params: &mut MacroParameters, input: TokenStream) -> syn::Result<TokenStream> {
+    let parser = |input: syn::parse::ParseStream| {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let rest: TokenStream2 = input.parse()?;
+        Ok((attrs, rest))
+    };
+    let (attrs, rest) = syn::parse::Parser::parse(parser, input)?;
+
+    let mut cfgs = vec![];
+    let mut other_attrs = vec![];
+    for attr in attrs {
+        if !attr.path.is_ident("cfg") {
+            other_attrs.push(attr);
+            continue;
+        }
+        match attr.parse_meta()? {
+            syn::Meta::List(list) if list.nested.len() == 1 => match list.nested.first() {
+                Some(syn::NestedMeta::Meta(cfg)) => cfgs.push(cfg.clone()),
+                _ => return Err(syn::Error::new_spanned(list, "Expected condition")),
+            },
+            meta => return Err(syn::Error::new_spanned(meta, "Expected condition")),
+        }
+    }
+
+    for version in &mut params.versions {
+        version.params.item_cfgs_set(cfgs.clone());
+    }
+
+    Ok(quote!(#(#other_attrs)* #rest).into())
+}
+
+/// A module that the variant of an item is placed in: its visibility and name.
+type SelfModule = Option<(syn::Visibility, Ident)>;
+
+/// Applies the `self_path` parameters of the variants. A module gets the name of the last segment
+/// of the path, other items keep their names and are placed in a module with this name (returned
+/// for every variant).
+fn self_path_apply(params: &mut MacroParameters, input: &TokenStream) -> Vec<SelfModule> {
+    let item = syn::parse::<syn::File>(input.clone())
+        .ok()
+        .and_then(|file| file.items.into_iter().next());
+
+    params
+        .versions
+        .iter_mut()
+        .map(|version| {
+            let module = version.params.self_module_get()?;
+            match &item {
+                Some(syn::Item::Mod(_)) => {
+                    version.params.self_name_set(module.to_string());
+                    None
+                }
+                item => {
+                    version.params.keep_self_set();
+                    let vis = item.as_ref().and_then(item_vis).cloned();
+                    Some((vis.unwrap_or(syn::Visibility::Inherited), module))
+                }
+            }
+        })
+        .collect()
+}
+
+fn item_vis(item: &syn::Item) -> Option<&syn::Visibility> {
+    match item {
+        syn::Item::Const(item) => Some(&item.vis),
+        syn::Item::Enum(item) => Some(&item.vis),
+        syn::Item::Fn(item) => Some(&item.vis),
+        syn::Item::Static(item) => Some(&item.vis),
+        syn::Item::Struct(item) => Some(&item.vis),
+        syn::Item::Trait(item) => Some(&item.vis),
+        syn::Item::Type(item) => Some(&item.vis),
+        syn::Item::Union(item) => Some(&item.vis),
+        syn::Item::Use(item) => Some(&item.vis),
+        _ => None,
+    }
+}
+
+fn wrap_in_module(ts: TokenStream2, module: &SelfModule) -> TokenStream2 {
+    match module {
+        Some((vis, name)) => quote! {
+            #vis mod #name {
+                #[allow(unused_imports)]
+                use super::*;
+                #ts
+            }
+        },
+        None => ts,
+    }
+}
+
+/// Tells the `dyn` variants the names identifiers get in the (first) async variant, so that the
+/// traits can be implemented for all implementors of the async ones, and other identifiers are
+/// named the same way as in the async variant.
+fn dyn_from_set(params: &mut MacroParameters, input: &TokenStream) -> syn::Result<()> {
+    if !params
+        .versions
+        .iter()
+        .any(|version| matches!(version.kind, ConvertMode::IntoDyn))
+    {
+        return Ok(());
+    }
+
+    let file = match syn::parse::<syn::File>(input.clone()) {
+        Ok(file) => file,
+        // the error will be reported during conversion
+        Err(_) => return Ok(()),
+    };
+
+    let mut async_params = match params
+        .versions
+        .iter()
+        .find(|version| matches!(version.kind, ConvertMode::IntoAsync))
+    {
+        Some(version) => version.params.clone(),
+        None => return Ok(()),
+    };
+    let traits = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Trait(item) => Some(item.ident.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // the blanket impl of a `Send` dyn trait boxes the futures of the async methods of the async
+    // trait, which have to be `Send` as well
+    let async_send = if async_params.impl_future_get() {
+        async_params.send_get() != Some(false)
+    } else {
+        async_params.send_get() == Some(true)
+            && async_params.trait_variant_get().is_none()
+            && async_params.dyn_compat_get().is_none()
+    };
+    let has_async_methods = file.items.iter().any(|item| match item {
+        syn::Item::Trait(item) => item.items.iter().any(
+            |item| matches!(item, syn::TraitItem::Method(method) if method.sig.asyncness.is_some()),
+        ),
+        _ => false,
+    });
+    let dyn_send = params.versions.iter().any(|version| {
+        matches!(version.kind, ConvertMode::IntoDyn) && version.params.send_get() != Some(false)
+    });
+    if dyn_send && has_async_methods && !async_send {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "The futures of the `dyn` variant are `Send`, but the ones of the `async` variant are \
+            not, so it cannot implement the `dyn` trait; specify `send = \"Send\"` for the \
+            `async` variant, or `send = \"?Send\"` for the `dyn` one",
+        ));
+    }
+
+    for name in &traits {
+        async_params.original_self_name_set(name, false);
+    }
+    let renamed = async_params.idents_renamed(ConvertMode::IntoAsync);
+
+    for version in &mut params.versions {
+        if let ConvertMode::IntoDyn = version.kind {
+            for (name, target, _) in &renamed {
+                version.params.dyn_from_set(name.clone(), target.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Tells the variants with the `delegate` (or `bridge`) parameter the names functions (and traits)
+/// get in the (first) variant of the other kind (`async` for `sync` variants and vice versa), so
+/// that their bodies can call these functions (and the traits can be bridged).
+fn delegate_set(params: &mut MacroParameters, input: &TokenStream) -> syn::Result<()> {
+    let delegates = |version: &MacroParameterVersion| {
+        version.params.delegate_get().is_some() || version.params.bridge_get().is_some()
+    };
+    if !params.versions.iter().any(delegates) {
+        return Ok(());
+    }
+
+    let file = match syn::parse::<syn::File>(input.clone()) {
+        Ok(file) => file,
+        // the error will be reported during conversion
+        Err(_) => return Ok(()),
+    };
+
+    for (kind, other) in [
+        (ConvertMode::IntoSync, ConvertMode::IntoAsync),
+        (ConvertMode::IntoAsync, ConvertMode::IntoSync),
+    ] {
+        if !params
+            .versions
+            .iter()
+            .any(|version| version.kind == kind && delegates(version))
+        {
+            continue;
+        }
+
+        let mut other_params = match params.versions.iter().find(|version| version.kind == other) {
+            Some(version) => version.params.clone(),
+            None => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "The `delegate` and `bridge` parameters require a `{}` variant to \
+                        delegate to",
+                        other.to_str()
+                    ),
+                ))
+            }
+        };
+        for item in &file.items {
+            match item {
+                syn::Item::Fn(item) => {
+                    other_params.original_self_name_set(item.sig.ident.to_string(), true)
+                }
+                syn::Item::Trait(item) => {
+                    other_params.original_self_name_set(item.ident.to_string(), false)
+                }
+                _ => {}
+            }
+        }
+        let renamed = other_params.idents_renamed(other);
+
+        let other_cfg = other_params.cfg_get();
+        for version in &mut params.versions {
+            if version.kind == kind && version.params.bridge_get().is_some() {
+                version.params.bridge_cfg_set(other_cfg.clone());
+            }
+            if version.kind == kind && delegates(version) {
+                for (name, target, _) in &renamed {
+                    version
+                        .params
+                        .delegate_target_set(name.clone(), target.clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts all variants right away and emits code that is identical in several variants only once,
+/// under the union of their conditions.
+fn maybe_dedup(
+    params: &MacroParameters,
+    modules: &[SelfModule],
+    input: TokenStream,
+) -> syn::Result<TokenStream> {
+    // (code as string, code, conditions), in order of first appearance
+    let mut groups: Vec<(String, TokenStream2, Vec<Option<syn::Meta>>)> = vec![];
+
+    for (version, module) in params.versions.iter().zip(modules) {
+        let mut ts = TokenStream2::new();
+        version
+            .params
+            .extend_tokenstream2_with_outer_attrs(&mut ts)?;
+        ts.extend(make_deprecated_attr(params, version, &input)?);
+
+        let mut item = TokenStream2::new();
+        version
+            .params
+            .extend_tokenstream2_with_inner_attrs(&mut item)?;
+        item.extend(TokenStream2::from(input.clone()));
+
+        let args = version.params.to_tokens(Some(version.kind));
+        let item = TokenStream2::from(maybe(args.into(), item.into())?);
+        ts.extend(wrap_in_module(item, module));
+        ts.extend(make_aliases(params, version, &input));
+
+        let code = ts.to_string();
+        let cfg = version.params.cfg_get();
+        match groups.iter_mut().find(|(c, _, _)| *c == code) {
+            Some((_, _, cfgs)) => cfgs.push(cfg),
+            None => groups.push((code, ts, vec![cfg])),
+        }
+    }
+
+    let mut tokens = TokenStream2::new();
+
+    for (_, ts, cfgs) in groups {
+        // a variant without condition makes the whole group unconditional
+        if cfgs.iter().all(Option::is_some) {
+            let cfgs = cfgs.into_iter().flatten().collect::<Vec<_>>();
+            let cfg = if cfgs.len() == 1 {
+                let cfg = &cfgs[0];
+                quote!(#cfg)
+            } else {
+                quote!(any(#(#cfgs),*))
+            };
+            tokens.extend(quote!(#[cfg(#cfg)]));
+            if params.doc_cfg_get() {
+                tokens.extend(quote!(#[cfg_attr(docsrs, doc(cfg(#cfg)))]));
+            }
+        }
+        tokens.extend(ts);
+    }
+    tokens.extend(make_dispatch(params, modules, &input)?);
+    tokens.extend(tracked_inputs(&input));
+
+    dump_tokens2!("maybe after dedup", &tokens);
+
+    Ok(tokens.into())
+}
+
+/// Generates the enums over the (first) sync and async variants of the inherent impl blocks and
+/// traits, with the methods forwarding to the variants, when the `dispatch` parameter is given.
+fn make_dispatch(
+    params: &MacroParameters,
+    modules: &[SelfModule],
+    input: &TokenStream,
+) -> syn::Result<TokenStream2> {
+    let name = match params.dispatch_get() {
+        Some(name) => name,
+        None => return Ok(TokenStream2::new()),
+    };
+
+    let find = |kind: ConvertMode| {
+        params
+            .versions
+            .iter()
+            .zip(modules)
+            .find(|(version, _)| version.kind == kind)
+    };
+    let (sync_version, sync_module, async_version, async_module) =
+        match (find(ConvertMode::IntoSync), find(ConvertMode::IntoAsync)) {
+            (Some((sync_version, sync_module)), Some((async_version, async_module))) => {
+                (sync_version, sync_module, async_version, async_module)
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "The `dispatch` parameter requires a `sync` and an `async` variant",
+                ))
+            }
+        };
+
+    let original = syn::parse::<syn::File>(input.clone())?;
+    let convert_version = |version: &MacroParameterVersion| -> syn::Result<syn::File> {
+        let args = version.params.to_tokens(Some(version.kind));
+        syn::parse(maybe(args.into(), input.clone())?)
+    };
+    let sync_file = convert_version(sync_version)?;
+    let async_file = convert_version(async_version)?;
+
+    let cfg = match (
+        sync_version.params.cfg_get(),
+        async_version.params.cfg_get(),
+    ) {
+        (Some(sync_cfg), Some(async_cfg)) => quote!(#[cfg(all(#sync_cfg, #async_cfg))]),
+        (Some(cfg), None) | (None, Some(cfg)) => quote!(#[cfg(#cfg)]),
+        (None, None) => quote!(),
+    };
+
+    let dispatchable = |item: &syn::Item| match item {
+        syn::Item::Impl(item) => item.trait_.is_none(),
+        syn::Item::Trait(_) => true,
+        _ => false,
+    };
+    let pick = |file: &syn::File, index: usize| {
+        file.items
+            .iter()
+            .filter(|item| dispatchable(item))
+            .nth(index)
+            .cloned()
+    };
+
+    let items = original
+        .items
+        .iter()
+        .filter(|item| dispatchable(item))
+        .collect::<Vec<_>>();
+    if name.is_some() && items.len() > 1 {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "The name of the dispatch enum can only be given for a single impl block or trait",
+        ));
+    }
+
+    let mut tokens = TokenStream2::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let (sync_item, async_item) = match (pick(&sync_file, index), pick(&async_file, index)) {
+            (Some(sync_item), Some(async_item)) => (sync_item, async_item),
+            // removed from one of the variants
+            _ => continue,
+        };
+        let original_name = match item {
+            syn::Item::Impl(item) => impl_self_ident(&item.self_ty).cloned().ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &item.self_ty,
+                    "The `dispatch` parameter cannot be applied to this impl block: the \
+                    implementing type has to be a path",
+                )
+            })?,
+            syn::Item::Trait(item) => item.ident.clone(),
+            _ => unreachable!(),
+        };
+        let name = match name {
+            Some(name) => Ident::new(name, Span::call_site()),
+            None => quote::format_ident!("{}Either", original_name),
+        };
+        let (generics, sync_target, async_target) = match (&sync_item, &async_item) {
+            (syn::Item::Impl(sync_item), syn::Item::Impl(async_item)) => {
+                if !sync_item.generics.params.is_empty() || !async_item.generics.params.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        item,
+                        "The `dispatch` parameter cannot be applied to generic impl blocks",
+                    ));
+                }
+                (
+                    None,
+                    module_type(&sync_item.self_ty, sync_module),
+                    module_type(&async_item.self_ty, async_module),
+                )
+            }
+            (syn::Item::Trait(sync_item), syn::Item::Trait(async_item)) => {
+                if !sync_item.generics.params.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        item,
+                        "The `dispatch` parameter cannot be applied to generic traits",
+                    ));
+                }
+                let sync_trait = module_type(&ident_type(&sync_item.ident), sync_module);
+                let async_trait = module_type(&ident_type(&async_item.ident), async_module);
+                (
+                    Some((sync_trait, async_trait)),
+                    quote!(__MaybeSync),
+                    quote!(__MaybeAsync),
+                )
+            }
+            _ => continue,
+        };
+
+        let sync_sigs = item_signatures(&sync_item);
+        let mut vis = None;
+        let mut methods = vec![];
+        for (attrs, method_vis, async_sig) in item_signatures(&async_item) {
+            let sync_sig = match sync_sigs
+                .iter()
+                .find(|(_, _, sig)| sig.ident == async_sig.ident)
+            {
+                Some((_, _, sig)) => sig,
+                None => continue,
+            };
+            if let Some(method) = make_dispatch_method(attrs, &method_vis, sync_sig, &async_sig) {
+                if vis.is_none() || matches!(method_vis, syn::Visibility::Public(_)) {
+                    vis = Some(method_vis);
+                }
+                methods.push(method);
+            }
+        }
+        let vis = match (&async_item, vis) {
+            (syn::Item::Trait(item), _) => item.vis.clone(),
+            (_, vis) => vis.unwrap_or(syn::Visibility::Inherited),
+        };
+
+        let doc = format!(
+            "Either the sync or the async variant of `{}`, chosen at runtime.",
+            original_name
+        );
+        tokens.extend(match generics {
+            None => quote! {
+                #cfg
+                #[doc = #doc]
+                #vis enum #name {
+                    Sync(#sync_target),
+                    Async(#async_target),
+                }
+                #cfg
+                impl #name {
+                    #(#methods)*
+                }
+            },
+            Some((sync_trait, async_trait)) => quote! {
+                #cfg
+                #[doc = #doc]
+                #vis enum #name<#sync_target, #async_target> {
+                    Sync(#sync_target),
+                    Async(#async_target),
+                }
+                #cfg
+                impl<#sync_target: #sync_trait, #async_target: #async_trait>
+                    #name<#sync_target, #async_target>
+                {
+                    #(#methods)*
+                }
+            },
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn ident_type(ident: &Ident) -> syn::Type {
+    syn::parse_quote!(#ident)
+}
+
+/// Refers to a type (or trait) of a variant from outside of its module (see `self_path`).
+fn module_type(ty: &syn::Type, module: &SelfModule) -> TokenStream2 {
+    match (module, ty) {
+        (Some((_, module)), syn::Type::Path(path))
+            if path.qself.is_none() && path.path.leading_colon.is_none() =>
+        {
+            quote!(#module::#ty)
+        }
+        _ => quote!(#ty),
+    }
+}
+
+/// The methods of an inherent impl block or a trait: their attributes, visibility and signature.
+fn item_signatures(
+    item: &syn::Item,
+) -> Vec<(Vec<syn::Attribute>, syn::Visibility, syn::Signature)> {
+    match item {
+        syn::Item::Impl(item) => item
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::ImplItem::Method(method) => {
+                    Some((method.attrs.clone(), method.vis.clone(), method.sig.clone()))
+                }
+                _ => None,
+            })
+            .collect(),
+        syn::Item::Trait(trait_item) => trait_item
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::TraitItem::Method(method) => Some((
+                    method.attrs.clone(),
+                    trait_item.vis.clone(),
+                    method.sig.clone(),
+                )),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Whether the tokens mention `Self`, which is a different type in each variant.
+fn mentions_self(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == "Self",
+        proc_macro2::TokenTree::Group(group) => mentions_self(group.stream()),
+        _ => false,
+    })
+}
+
+/// Makes a method of the dispatch enum, which calls the method of the variant it holds. Only the
+/// methods with a receiver and the same signature in both variants (apart from the name and
+/// asyncness), not mentioning `Self`, can be forwarded.
+fn make_dispatch_method(
+    attrs: Vec<syn::Attribute>,
+    vis: &syn::Visibility,
+    sync_sig: &syn::Signature,
+    async_sig: &syn::Signature,
+) -> Option<TokenStream2> {
+    let receiver = match async_sig.inputs.first() {
+        Some(syn::FnArg::Receiver(receiver)) if receiver.attrs.is_empty() => receiver,
+        _ => return None,
+    };
+    let signature = |sig: &syn::Signature| {
+        let generics = &sig.generics;
+        let where_clause = &sig.generics.where_clause;
+        let inputs = sig.inputs.iter().skip(1);
+        let output = &sig.output;
+        quote!(#generics (#(#inputs),*) #output #where_clause).to_string()
+    };
+    let types = async_sig
+        .inputs
+        .iter()
+        .skip(1)
+        .map(|arg| match arg {
+            syn::FnArg::Typed(arg) => Some(&arg.ty),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let output = &async_sig.output;
+    if signature(sync_sig) != signature(async_sig)
+        || sync_sig.asyncness.is_some()
+        || mentions_self(quote!(#(#types)* #output))
+    {
+        return None;
+    }
+
+    let docs = attrs.iter().filter(|attr| attr.path.is_ident("doc"));
+    let asyncness = &async_sig.asyncness;
+    let ident = &async_sig.ident;
+    let generics = &async_sig.generics;
+    let where_clause = &async_sig.generics.where_clause;
+    let args = (0..types.len())
+        .map(|index| quote::format_ident!("__arg{}", index + 1))
+        .collect::<Vec<_>>();
+    let sync_ident = &sync_sig.ident;
+    let await_token = asyncness.map(|_| quote!(.await));
+    Some(quote! {
+        #(#docs)*
+        #vis #asyncness fn #ident #generics (#receiver, #(#args: #types),*) #output #where_clause {
+            match self {
+                Self::Sync(inner) => inner.#sync_ident(#(#args),*),
+                Self::Async(inner) => inner.#ident(#(#args),*) #await_token,
+            }
+        }
+    })
+}
+
+/// Warns about `send` parameters that have no effect. Warnings for functions are inserted into
+/// their bodies, the other ones are returned.
+fn check_send(params: &MacroParameters, file: &mut syn::File) -> Vec<syn::Block> {
+    // the send policy of a preset applies only where it has effect
+    let explicit_send = params.send_explicit_get();
+    let mut warnings = vec![];
+
+    for item in &mut file.items {
+        match item {
+            syn::Item::Trait(item) => {
+                let has_async_methods = item.items.iter().any(|item| match item {
+                    syn::TraitItem::Method(method) => method.sig.asyncness.is_some(),
+                    _ => false,
+                });
+                if explicit_send.is_some() && !has_async_methods {
+                    warnings.push(make_warning_block(
+                        "`send` has no effect on a trait without async methods",
+                    ));
+                }
+            }
+            // `send = "Send"` makes the future of an async function `Send`, see `make_send_fn`
+            syn::Item::Fn(item)
+                if explicit_send.is_some()
+                    && (explicit_send == Some(false) || item.sig.asyncness.is_none()) =>
+            {
+                let warning = make_warning_block(
+                    "`send` has no effect on this function, only `send = \"Send\"` on async \
+                    functions makes their futures `Send`",
+                );
+                item.block.stmts.insert(0, syn::parse_quote!(#warning));
+            }
+            syn::Item::Struct(_) | syn::Item::Enum(_) | syn::Item::Use(_)
+                if explicit_send.is_some() =>
+            {
+                warnings.push(make_warning_block(
+                    "`send` has no effect here, only traits and impl blocks get the `async_trait` \
+                    attribute",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    warnings
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 pub fn convert(
@@ -77,110 +1354,591 @@ pub fn convert(
     dump_tokens!("convert before", &input);
 
     let mut file = syn::parse_macro_input::parse::<syn::File>(input)?;
+    let warnings = match convert_mode {
+        ConvertMode::IntoAsync => check_send(&params, &mut file),
+        _ => vec![],
+    };
+
+    if let ConvertMode::IntoDyn = convert_mode {
+        // only traits have a `dyn` variant
+        file.items
+            .retain(|item| matches!(item, syn::Item::Trait(_)));
+
+        let traits = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Trait(item) => Some(item.ident.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        params.dyn_idents_apply(&traits);
+    }
+
+    let blocking_ext = match convert_mode {
+        ConvertMode::IntoAsync => params.blocking_ext_get(),
+        ConvertMode::IntoSync | ConvertMode::IntoDyn => None,
+    };
+    let delegate = match convert_mode {
+        ConvertMode::IntoSync | ConvertMode::IntoAsync => params.delegate_get(),
+        ConvertMode::IntoDyn => None,
+    };
+    let bridge = params.bridge_get();
+
+    let mut generated = vec![];
     for item in &mut file.items {
+        if !params.generics_is_empty() {
+            generics_apply(&params, item)?;
+        }
+
+        if params.constness_get() && !matches!(item, syn::Item::Fn(_)) {
+            return Err(syn::Error::new_spanned(
+                &item,
+                "The `constness` parameter can only be applied to functions",
+            ));
+        }
+
+        if delegate.is_some() && !matches!(item, syn::Item::Fn(_)) {
+            return Err(syn::Error::new_spanned(
+                &item,
+                "The `delegate` parameter can only be applied to functions",
+            ));
+        }
+
+        if bridge.is_some() && !matches!(item, syn::Item::Trait(_)) {
+            return Err(syn::Error::new_spanned(
+                &item,
+                "The `bridge` parameter can only be applied to traits",
+            ));
+        }
+
         match item {
-            syn::Item::Impl(item) => convert_impl(&mut params, item, convert_mode),
-            syn::Item::Struct(item) => convert_struct(&mut params, item, convert_mode),
-            syn::Item::Enum(item) => convert_enum(&mut params, item, convert_mode),
-            syn::Item::Trait(item) => convert_trait(&mut params, item, convert_mode),
-            syn::Item::Fn(item) => convert_fn(&mut params, item, convert_mode),
-            syn::Item::Use(item) => convert_use(&mut params, item, convert_mode),
-            syn::Item::Mod(item) => convert_mod(&mut params, item, convert_mode),
+            syn::Item::Impl(item) => {
+                let name = impl_self_ident(&item.self_ty).map(|ident| ident.to_string());
+                convert_impl(&mut params, item, convert_mode)?;
+                if let Some(block_on) = &blocking_ext {
+                    if item.trait_.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            &item.self_ty,
+                            "The `blocking_ext` parameter cannot be applied to trait \
+                            implementations, apply it to the trait instead",
+                        ));
+                    }
+                    let name = name.ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            &item.self_ty,
+                            "The `blocking_ext` parameter cannot be applied to this impl block: \
+                            the implementing type has to be a path",
+                        )
+                    })?;
+                    if let Some((ext, ext_impl)) = make_blocking_ext_impl(item, &name, block_on) {
+                        generated.push(syn::Item::Trait(ext));
+                        generated.push(syn::Item::Impl(ext_impl));
+                    }
+                }
+            }
+            syn::Item::Struct(item) => convert_struct(&mut params, item, convert_mode)?,
+            syn::Item::Enum(item) => convert_enum(&mut params, item, convert_mode)?,
+            syn::Item::Trait(item) => {
+                let name = item.ident.to_string();
+                let async_methods = item
+                    .items
+                    .iter()
+                    .filter_map(|inner| match inner {
+                        syn::TraitItem::Method(method) if method.sig.asyncness.is_some() => {
+                            Some(method.sig.ident.to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                convert_trait(&mut params, item, convert_mode)?;
+                if let Some((wrapper, block_on)) = &bridge {
+                    let other = params.delegate_target_get(&name).unwrap_or(&name);
+                    let other = Ident::new(other, item.ident.span());
+                    // only the sync variant blocks on the futures of the other one
+                    let block_on = match convert_mode {
+                        ConvertMode::IntoSync => block_on.as_ref(),
+                        ConvertMode::IntoAsync | ConvertMode::IntoDyn => None,
+                    };
+                    let mut bridge_impl = make_bridge_impl(
+                        item,
+                        &other,
+                        wrapper,
+                        block_on,
+                        &async_methods,
+                        params.async_trait_path_get(),
+                    );
+                    // the impl block names the trait of the other variant, which has to be enabled
+                    // as well
+                    if let Some(cfg) = params.bridge_cfg_get() {
+                        bridge_impl.attrs.insert(0, syn::parse_quote!(#[cfg(#cfg)]));
+                    }
+                    generated.push(syn::Item::Impl(bridge_impl));
+                }
+                if let ConvertMode::IntoDyn = convert_mode {
+                    if let Some(from) = params.dyn_from_get(&name) {
+                        let blanket_impl = make_dyn_blanket_impl(item, from, params.send_get())?;
+                        generated.push(syn::Item::Impl(blanket_impl));
+                    }
+                }
+                if let Some(block_on) = &blocking_ext {
+                    if let Some((ext, ext_impl)) = make_blocking_ext_trait(item, &name, block_on) {
+                        generated.push(syn::Item::Trait(ext));
+                        generated.push(syn::Item::Impl(ext_impl));
+                    }
+                }
+            }
+            syn::Item::Fn(item) if delegate.is_some() => {
+                let target = params
+                    .delegate_target_get(item.sig.ident.to_string())
+                    .map(|name| Ident::new(name, Span::call_site()))
+                    .unwrap_or_else(|| item.sig.ident.clone());
+                *item.block = syn::parse_quote!({});
+                convert_fn(&mut params, item, convert_mode)?;
+                make_delegate_fn(item, &target, delegate.as_ref().unwrap(), convert_mode);
+            }
+            syn::Item::Fn(item) => {
+                convert_fn(&mut params, item, convert_mode)?;
+                // `send_bounds` makes the future `Send` as well
+                let send = match params.send_explicit_get() {
+                    None if params.send_bounds_get() => Some(true),
+                    send => send,
+                };
+                if let (ConvertMode::IntoAsync, Some(true)) = (convert_mode, send) {
+                    if item.sig.asyncness.is_some() {
+                        make_send_fn(item);
+                    }
+                }
+            }
+            syn::Item::Use(item) => convert_use(&mut params, item, convert_mode)?,
+            syn::Item::Mod(item) => convert_mod(&mut params, item, convert_mode)?,
             _ => {
-                return Err(syn::Error::new(
-                    item.span(),
-                    "Allowed impl, struct, enum, trait, fn or use items only",
-                ));
+                let kind = unsupported_item_kind(item).unwrap_or("this kind of items");
+                return Err(unsupported_item_error(item, kind));
             }
         }
     }
-    let ts = quote!(#file);
+    file.items.extend(generated);
+    if let ConvertMode::IntoSync = convert_mode {
+        check_left_await(&file)?;
+    }
+    let ts = quote!(#file #(const _: () = #warnings;)*);
 
     dump_tokens2!("convert after", &ts);
     Ok(ts.into())
 }
 
-fn convert_impl(params: &mut MacroParameters, item: &mut syn::ItemImpl, convert_mode: ConvertMode) {
-    match &mut *item.self_ty {
-        syn::Type::Path(syn::TypePath { path, .. }) => {
-            if let Some(last) = path.segments.last_mut() {
-                params.original_self_name_set(last.ident.to_string(), false);
-            }
+fn convert_impl(
+    params: &mut MacroParameters,
+    item: &mut syn::ItemImpl,
+    convert_mode: ConvertMode,
+) -> syn::Result<()> {
+    match impl_self_ident(&item.self_ty) {
+        Some(ident) => params.original_self_name_set(ident.to_string(), false),
+        None if params.self_name_get().is_some() => {
+            return Err(syn::Error::new_spanned(
+                &item.self_ty,
+                "The `self` parameter cannot be applied to this impl block: the implementing type \
+                has to be a path (optionally behind a reference)",
+            ));
         }
-        _ => {}
+        None => {}
     };
 
-    if !params.recursive_asyncness_removal_get() {
-        remove_asyncness_on_impl(item, convert_mode, params.send_get());
-    }
-
     let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_impl_mut(item)
+    visitor.visit_item_impl_mut(item);
+    visitor.result()
 }
 
 fn convert_struct(
     params: &mut MacroParameters,
     item: &mut syn::ItemStruct,
     convert_mode: ConvertMode,
-) {
+) -> syn::Result<()> {
     params.original_self_name_set(item.ident.to_string(), false);
 
     let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_struct_mut(item)
+    visitor.visit_item_struct_mut(item);
+    visitor.result()
 }
 
-fn convert_enum(params: &mut MacroParameters, item: &mut syn::ItemEnum, convert_mode: ConvertMode) {
+fn convert_enum(
+    params: &mut MacroParameters,
+    item: &mut syn::ItemEnum,
+    convert_mode: ConvertMode,
+) -> syn::Result<()> {
     params.original_self_name_set(item.ident.to_string(), false);
 
     let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_enum_mut(item)
+    visitor.visit_item_enum_mut(item);
+    visitor.result()
 }
 
 fn convert_trait(
     params: &mut MacroParameters,
     item: &mut syn::ItemTrait,
     convert_mode: ConvertMode,
-) {
+) -> syn::Result<()> {
     params.original_self_name_set(item.ident.to_string(), false);
 
-    if !params.recursive_asyncness_removal_get() {
-        remove_asyncness_on_trait(item, convert_mode);
-    }
-
     let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_trait_mut(item)
+    visitor.visit_item_trait_mut(item);
+    visitor.result()
 }
 
-fn convert_fn(params: &mut MacroParameters, item: &mut syn::ItemFn, convert_mode: ConvertMode) {
+fn convert_fn(
+    params: &mut MacroParameters,
+    item: &mut syn::ItemFn,
+    convert_mode: ConvertMode,
+) -> syn::Result<()> {
     params.original_self_name_set(item.sig.ident.to_string(), true);
 
-    if !params.recursive_asyncness_removal_get() {
-        remove_asyncness_on_fn(item, convert_mode);
+    let constness = params.constness_get();
+    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
+    visitor.visit_item_fn_mut(item);
+    visitor.result()?;
+
+    if constness {
+        make_const_fn(item)?;
     }
 
-    let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_fn_mut(item)
+    Ok(())
+}
+
+/// Applies the `add_generics`, `drop_generics`, `add_bounds` and `drop_bounds` parameters.
+fn generics_apply(params: &MacroParameters, item: &mut syn::Item) -> syn::Result<()> {
+    let generics =
+        match item {
+            syn::Item::Impl(item) => &mut item.generics,
+            syn::Item::Struct(item) => &mut item.generics,
+            syn::Item::Enum(item) => &mut item.generics,
+            syn::Item::Trait(item) => {
+                params.bounds_drop(&mut item.supertraits);
+                if item.supertraits.is_empty() {
+                    item.colon_token = None;
+                }
+                &mut item.generics
+            }
+            syn::Item::Fn(item) => &mut item.sig.generics,
+            _ => return Err(syn::Error::new_spanned(
+                &item,
+                "The `add_generics`, `drop_generics`, `add_bounds` and `drop_bounds` parameters \
+                can only be applied to items with generics",
+            )),
+        };
+    params.generics_apply(generics);
+    Ok(())
 }
 
-fn convert_use(params: &mut MacroParameters, item: &mut syn::ItemUse, convert_mode: ConvertMode) {
+/// Marks the converted function `const fn`, checking that no asynchronous code is left in it.
+fn make_const_fn(item: &mut syn::ItemFn) -> syn::Result<()> {
+    struct FindAsync(Option<syn::Error>);
+
+    impl VisitMut for FindAsync {
+        fn visit_expr_mut(&mut self, node: &mut syn::Expr) {
+            match node {
+                syn::Expr::Await(_) | syn::Expr::Async(_) if self.0.is_none() => {
+                    self.0 = Some(syn::Error::new_spanned(
+                        &node,
+                        "The `constness` parameter requires no asynchronous code to be left \
+                        in the sync variant",
+                    ));
+                }
+                _ => syn::visit_mut::visit_expr_mut(self, node),
+            }
+        }
+    }
+
+    if let Some(asyncness) = &item.sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "The `constness` parameter cannot be applied to async functions",
+        ));
+    }
+
+    let mut finder = FindAsync(None);
+    finder.visit_block_mut(&mut item.block);
+    if let Some(err) = finder.0 {
+        return Err(err);
+    }
+
+    item.sig.constness = Some(Default::default());
+    Ok(())
+}
+
+fn convert_use(
+    params: &mut MacroParameters,
+    item: &mut syn::ItemUse,
+    convert_mode: ConvertMode,
+) -> syn::Result<()> {
     let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_use_mut(item)
+    visitor.visit_item_use_mut(item);
+    visitor.result()
 }
 
-fn convert_mod(params: &mut MacroParameters, item: &mut syn::ItemMod, convert_mode: ConvertMode) {
+fn convert_mod(
+    params: &mut MacroParameters,
+    item: &mut syn::ItemMod,
+    convert_mode: ConvertMode,
+) -> syn::Result<()> {
     params.original_self_name_set(item.ident.to_string(), true);
 
     let mut visitor = Visitor::new(AsyncAwaitVisitor::new(params, convert_mode));
-    visitor.visit_item_mod_mut(item)
+    visitor.visit_item_mod_mut(item);
+    visitor.result()
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Emits `compile_error!` under the conditions where several variants are enabled at once
+/// (`exclusive`) or none of them is (`required`). Both are checked unless one of them is specified.
+pub fn guard(body: TokenStream) -> syn::Result<TokenStream> {
+    let args = syn::parse_macro_input::parse::<syn::AttributeArgs>(body)?;
+
+    let mut exclusive = false;
+    let mut required = false;
+    let mut rest = vec![];
+    for arg in args {
+        match &arg {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("exclusive") => {
+                exclusive = true
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("required") => {
+                required = true
+            }
+            _ => rest.push(arg),
+        }
+    }
+    if !exclusive && !required {
+        exclusive = true;
+        required = true;
+    }
+
+    let params = MacroParameters::from_args(&rest)?;
+    let params = crate_defaults_apply(params)?;
+
+    // variants without conditions are always enabled, so they cannot be checked
+    let conds = params
+        .versions
+        .iter()
+        .map(|version| {
+            let key = version.params.key_get().unwrap_or_default().to_string();
+            version.params.cfg_get().map(|cfg| (key, cfg))
+        })
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+
+    let mut ts = TokenStream2::new();
+    if exclusive {
+        for (i, (key1, cfg1)) in conds.iter().enumerate() {
+            for (key2, cfg2) in &conds[i + 1..] {
+                let msg = format!(
+                    "The `{}` and `{}` variants are enabled at the same time (`{}` and `{}`), \
+                    only one of them can be enabled",
+                    key1,
+                    key2,
+                    cfg1.to_token_stream(),
+                    cfg2.to_token_stream()
+                );
+                ts.extend(quote!(#[cfg(all(#cfg1, #cfg2))] compile_error!(#msg);));
+            }
+        }
+    }
+    if required && !conds.is_empty() {
+        let keys = conds
+            .iter()
+            .map(|(key, cfg)| format!("`{}` (`{}`)", key, cfg.to_token_stream()))
+            .collect::<Vec<_>>();
+        let msg = format!(
+            "None of the variants is enabled, one of them has to be: {}",
+            keys.join(", ")
+        );
+        let cfgs = conds.iter().map(|(_, cfg)| cfg);
+        ts.extend(quote!(#[cfg(not(any(#(#cfgs),*)))] compile_error!(#msg);));
+    }
+
+    dump_tokens2!("guard after", &ts);
+    Ok(ts.into())
+}
+
+/// Re-exports the variants of the items under the original names, each under the condition of its
+/// variant.
+pub fn facade(body: TokenStream) -> syn::Result<TokenStream> {
+    let Facade { vis, paths, args } = syn::parse(body)?;
+
+    let params = MacroParameters::from_args(&args)?;
+    let params = crate_defaults_apply(params)?;
+    if params.versions.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "Expected variants, either in the parameters or in the crate-wide defaults",
+        ));
+    }
+
+    let mut ts = TokenStream2::new();
+    for (i, version) in params.versions.iter().enumerate() {
+        // the names are re-exported once, from the first of the enabled variants
+        let earlier = params.versions[..i]
+            .iter()
+            .map(|v| v.params.cfg_get())
+            .collect::<Vec<_>>();
+        let cfg = match first_enabled_cfg(version.params.cfg_get(), &earlier) {
+            Some(cfg) => cfg.map(|cfg| quote!(#[cfg(#cfg)])),
+            None => break,
+        };
+        for path in &paths {
+            let ident = match path.segments.last() {
+                Some(segment) => &segment.ident,
+                None => continue,
+            };
+            // functions and modules are snake-cased
+            let name = ident.to_string();
+            let snake_case = name.starts_with(|c: char| c.is_lowercase());
+            let renamed = version
+                .params
+                .self_name_renamed(&name, snake_case, version.kind);
+
+            let mut target = path.clone();
+            if let Some(last) = target.segments.last_mut() {
+                last.ident = Ident::new(&renamed, ident.span());
+            }
+            if *ident == renamed {
+                ts.extend(quote!(#cfg #vis use #target;));
+            } else {
+                ts.extend(quote!(#cfg #vis use #target as #ident;));
+            }
+        }
+    }
+
+    dump_tokens2!("facade after", &ts);
+    Ok(ts.into())
+}
+
+/// Makes the `MaybeSend` and `MaybeSync` marker traits, requiring `Send` and `Sync` in the `async`
+/// variants (unless they are `?Send`) and nothing in the other ones.
+pub fn maybe_send_alias(body: TokenStream) -> syn::Result<TokenStream> {
+    let MaybeSendAlias { vis, args } = syn::parse(body)?;
+
+    let params = MacroParameters::from_args(&args)?;
+    let params = crate_defaults_apply(params)?;
+    if params.versions.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "Expected variants, either in the parameters or in the crate-wide defaults",
+        ));
+    }
+
+    // the conditions of the variants with and without the markers, `None` for the one without
+    // a condition (always enabled)
+    let mut send_cfgs = vec![];
+    let mut plain_cfgs = vec![];
+    for version in &params.versions {
+        let send = match version.kind {
+            ConvertMode::IntoSync => false,
+            ConvertMode::IntoAsync | ConvertMode::IntoDyn => {
+                version.params.send_get() != Some(false)
+            }
+        };
+        let cfg = version.params.cfg_get();
+        if send {
+            send_cfgs.push(cfg);
+        } else {
+            plain_cfgs.push(cfg);
+        }
+    }
+
+    // the traits are defined once, the markers are required if any of such variants is enabled
+    let any_cfg = |cfgs: &[Option<syn::Meta>]| -> Option<Option<TokenStream2>> {
+        if cfgs.is_empty() {
+            None
+        } else if cfgs.iter().any(Option::is_none) {
+            Some(None)
+        } else {
+            let cfgs = cfgs.iter().flatten();
+            Some(Some(quote!(any(#(#cfgs),*))))
+        }
+    };
+    let send_cfg = any_cfg(&send_cfgs);
+    let plain_cfg = match (any_cfg(&plain_cfgs), &send_cfg) {
+        (None, _) | (_, Some(None)) => None,
+        (plain, None) => plain,
+        (Some(None), Some(Some(send))) => Some(Some(quote!(not(#send)))),
+        (Some(Some(plain)), Some(Some(send))) => Some(Some(quote!(all(#plain, not(#send))))),
+    };
+
+    let mut ts = TokenStream2::new();
+    for (name, marker) in [("MaybeSend", "Send"), ("MaybeSync", "Sync")] {
+        let name = Ident::new(name, Span::call_site());
+        let marker = Ident::new(marker, Span::call_site());
+        if let Some(cfg) = &send_cfg {
+            let cfg = cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+            ts.extend(quote! {
+                #cfg
+                #vis trait #name: #marker {}
+                #cfg
+                impl<T: #marker + ?Sized> #name for T {}
+            });
+        }
+        if let Some(cfg) = &plain_cfg {
+            let cfg = cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+            ts.extend(quote! {
+                #cfg
+                #vis trait #name {}
+                #cfg
+                impl<T: ?Sized> #name for T {}
+            });
+        }
+    }
+
+    dump_tokens2!("maybe_send_alias after", &ts);
+    Ok(ts.into())
+}
+
+/// Generates a `macro_rules!` macro which wraps its body into `content!` with the specified
+/// parameters as the defaults.
+pub fn define_preset(body: TokenStream) -> syn::Result<TokenStream> {
+    let DefinePreset { vis, name, args } = syn::parse(body)?;
+
+    // the parameters are checked here to report errors at the definition
+    let params = MacroParameters::from_args(&args.args)?;
+    let prefix = Ident::new(params.prefix_get(), Span::call_site());
+    let args = args.args;
+
+    let reexport = match &vis {
+        syn::Visibility::Inherited => quote!(),
+        syn::Visibility::Public(_) => {
+            return Err(syn::Error::new_spanned(
+                vis,
+                "A preset cannot be public, use `pub(crate)` instead",
+            ))
+        }
+        vis => quote!(#[allow(unused_imports)] #vis use #name;),
+    };
+
+    let ts = quote! {
+        #[allow(unused_macros)]
+        macro_rules! #name {
+            ($($body:tt)*) => {
+                #prefix::content! {
+                    #![#prefix::default(#args)]
+                    $($body)*
+                }
+            };
+        }
+        #reexport
+    };
+
+    dump_tokens2!("define_preset after", &ts);
+    Ok(ts.into())
+}
+
 pub fn content(body: TokenStream) -> syn::Result<TokenStream> {
     dump_tokens!("content before", &body);
 
+    let body = split_use_trees(body.into());
+
     let mut visitor = Visitor::new(ContentVisitor::new());
-    let ts: TokenStream = visitor.process(body.into())?.into();
+    let ts: TokenStream = visitor.process(body)?.into();
 
     dump_tokens!("content after", &ts);
     Ok(ts)
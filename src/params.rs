@@ -6,21 +6,65 @@ use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens};
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, AttributeArgs, Ident, Lit,
-    LitStr, Meta, MetaList, MetaNameValue, NestedMeta,
+    parse_quote, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, AttributeArgs,
+    Ident, Lit, LitStr, Meta, MetaList, MetaNameValue, NestedMeta,
 };
 
 use crate::{utils::*, DEFAULT_CRATE_NAME, STANDARD_MACROS};
 
 const MODE_INTO_ASYNC: &'static str = "__into_async";
 const MODE_INTO_SYNC: &'static str = "__into_sync";
+const MODE_INTO_DYN: &str = "__into_dyn";
+const DYN_FROM: &str = "__dyn_from";
+const DELEGATE_TARGETS: &str = "__delegate_targets";
+const BRIDGE_CFG: &str = "__bridge_cfg";
+const VARIANT_KEYS: &str = "__variant_keys";
+const DEFAULT_SPAWN: &str = "::std::thread::spawn";
+const LOCK_POLICIES: &[&str] = &["unwrap", "ignore_poison"];
+const YIELD_POLICIES: &[&str] = &["remove", "thread"];
+const DYN_MARKERS_POLICIES: &[&str] = &["auto", "add", "strip"];
+const TIMEOUT_OK: &str = "ok";
+const DYN_COMPAT_DYNOSAUR: &str = "dynosaur";
+const SEND_TRAIT_VARIANT: &str = "trait_variant";
+const DEFAULT_TRAIT_VARIANT: &str = "trait_variant::make";
+const DEFAULT_ASYNC_TRAIT: &str = "async_trait::async_trait";
+const ATTR_POLICIES: &[&str] = &["async", "sync", "keep"];
+/// The attributes which make sense only for async functions and traits, they are dropped in other
+/// variants.
+const ASYNC_ATTRS: &[&str] = &["async_recursion", "async_backtrace::framed", "async_trait"];
+const DEFAULT_CHANNELS: &[(&str, &str)] = &[
+    (
+        "tokio::sync::mpsc::channel",
+        "std::sync::mpsc::sync_channel",
+    ),
+    (
+        "tokio::sync::mpsc::unbounded_channel",
+        "std::sync::mpsc::channel",
+    ),
+    ("tokio::sync::mpsc::Sender", "std::sync::mpsc::SyncSender"),
+    (
+        "tokio::sync::mpsc::UnboundedSender",
+        "std::sync::mpsc::Sender",
+    ),
+    ("tokio::sync::mpsc::Receiver", "std::sync::mpsc::Receiver"),
+    (
+        "tokio::sync::mpsc::UnboundedReceiver",
+        "std::sync::mpsc::Receiver",
+    ),
+    (
+        "tokio::sync::mpsc::error::SendError",
+        "std::sync::mpsc::SendError",
+    ),
+];
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
 pub enum ConvertMode {
     IntoSync,
     IntoAsync,
+    IntoDyn,
 }
 
 impl ConvertMode {
@@ -28,16 +72,406 @@ impl ConvertMode {
         match s.as_ref() {
             "sync" => Some(Self::IntoSync),
             "async" => Some(Self::IntoAsync),
+            "dyn" => Some(Self::IntoDyn),
             _ => None,
         }
     }
 
-    fn to_str(&self) -> &'static str {
+    pub fn to_str(&self) -> &'static str {
         match self {
             Self::IntoSync => "sync",
             Self::IntoAsync => "async",
+            Self::IntoDyn => "dyn",
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Parameters bundled by a `preset` of a variant.
+#[derive(Debug)]
+pub struct Preset {
+    pub name: &'static str,
+    pub mode: ConvertMode,
+    /// Replacement for the `#[test]` attribute of functions.
+    pub test_attr: Option<&'static str>,
+    /// Attribute added to the `main` function.
+    pub main_attr: Option<&'static str>,
+    pub send: Option<bool>,
+    /// Condition added to the condition of the variant.
+    pub cfg: Option<&'static str>,
+    /// Paths replaced in the variant (as the ones of the `types` parameter).
+    pub paths: &'static [(&'static str, &'static str)],
+}
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        name: "tokio",
+        mode: ConvertMode::IntoAsync,
+        test_attr: Some("tokio::test"),
+        main_attr: Some("tokio::main"),
+        send: Some(true),
+        cfg: None,
+        paths: &[],
+    },
+    Preset {
+        name: "async-std",
+        mode: ConvertMode::IntoAsync,
+        test_attr: Some("async_std::test"),
+        main_attr: Some("async_std::main"),
+        send: Some(true),
+        cfg: None,
+        paths: &[],
+    },
+    Preset {
+        name: "wasm",
+        mode: ConvertMode::IntoAsync,
+        test_attr: Some("wasm_bindgen_test::wasm_bindgen_test"),
+        main_attr: None,
+        send: Some(false),
+        cfg: Some("target_arch = \"wasm32\""),
+        paths: &[],
+    },
+    Preset {
+        name: "std",
+        mode: ConvertMode::IntoSync,
+        test_attr: None,
+        main_attr: None,
+        send: None,
+        cfg: None,
+        paths: &[],
+    },
+    Preset {
+        name: "reqwest",
+        mode: ConvertMode::IntoSync,
+        test_attr: None,
+        main_attr: None,
+        send: None,
+        cfg: None,
+        paths: &[
+            ("reqwest::Client", "reqwest::blocking::Client"),
+            ("reqwest::ClientBuilder", "reqwest::blocking::ClientBuilder"),
+            ("reqwest::Request", "reqwest::blocking::Request"),
+            (
+                "reqwest::RequestBuilder",
+                "reqwest::blocking::RequestBuilder",
+            ),
+            ("reqwest::Response", "reqwest::blocking::Response"),
+            ("reqwest::Body", "reqwest::blocking::Body"),
+            ("reqwest::multipart", "reqwest::blocking::multipart"),
+            ("reqwest::get", "reqwest::blocking::get"),
+        ],
+    },
+    Preset {
+        name: "fs",
+        mode: ConvertMode::IntoSync,
+        test_attr: None,
+        main_attr: None,
+        send: None,
+        cfg: None,
+        paths: &[
+            ("tokio::fs", "std::fs"),
+            ("async_std::fs", "std::fs"),
+            ("tokio::io::AsyncReadExt", "std::io::Read"),
+            ("tokio::io::AsyncWriteExt", "std::io::Write"),
+            ("tokio::io::AsyncBufReadExt", "std::io::BufRead"),
+            ("tokio::io::AsyncSeekExt", "std::io::Seek"),
+            ("tokio::io::BufReader", "std::io::BufReader"),
+            ("tokio::io::BufWriter", "std::io::BufWriter"),
+            ("async_std::io::ReadExt", "std::io::Read"),
+            ("async_std::io::WriteExt", "std::io::Write"),
+            ("async_std::io::BufReadExt", "std::io::BufRead"),
+            ("async_std::io::SeekExt", "std::io::Seek"),
+            ("async_std::io::BufReader", "std::io::BufReader"),
+            ("async_std::io::BufWriter", "std::io::BufWriter"),
+            ("async_std::io::prelude", "std::io::prelude"),
+            ("async_std::prelude", "std::io::prelude"),
+        ],
+    },
+];
+
+impl Preset {
+    fn find(name: &str) -> Option<&'static Preset> {
+        PRESETS.iter().find(|preset| preset.name == name)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An entry of an axis of a `matrix`.
+#[derive(Clone)]
+struct MatrixEntry {
+    kind: Option<ConvertMode>,
+    key: String,
+    suffix: String,
+    conds: Vec<Meta>,
+    args: Vec<NestedMeta>,
+}
+
+impl MatrixEntry {
+    fn from_nestedmeta(nm: &NestedMeta) -> syn::Result<Self> {
+        let list = match nm {
+            NestedMeta::Meta(Meta::List(list)) => list,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    nm.to_token_stream(),
+                    "Expected `name(...)` entry",
+                ))
+            }
+        };
+        let name = list
+            .path
+            .get_ident()
+            .ok_or_else(|| syn::Error::new_spanned(list.path.to_token_stream(), "Expected name"))?
+            .to_string();
+
+        let mut kind = ConvertMode::from_str(&name);
+        let mut key = None;
+        let mut suffix = None;
+        let mut preset = None;
+        let mut conds = vec![];
+        let mut args = vec![];
+        for nm in &list.nested {
+            match nm {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(value),
+                    ..
+                })) if path.is_ident("mode") => {
+                    kind = Some(ConvertMode::from_str(value.value()).ok_or_else(|| {
+                        syn::Error::new_spanned(value, "Expected `sync`, `async` or `dyn`")
+                    })?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(value),
+                    ..
+                })) if path.is_ident("key") => key = Some(value.value()),
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(value),
+                    ..
+                })) if path.is_ident("suffix") => suffix = Some(value.value()),
+                NestedMeta::Meta(meta @ Meta::NameValue(MetaNameValue { path, .. }))
+                    if path.is_ident("feature") =>
+                {
+                    conds.push(meta.clone())
+                }
+                NestedMeta::Meta(Meta::List(cfg)) if cfg.path.is_ident("cfg") => {
+                    match cfg.nested.first() {
+                        Some(NestedMeta::Meta(cond)) if cfg.nested.len() == 1 => {
+                            conds.push(cond.clone())
+                        }
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                cfg.to_token_stream(),
+                                "Expected condition",
+                            ))
+                        }
+                    }
+                }
+                NestedMeta::Meta(meta @ Meta::List(cond))
+                    if ["any", "all", "not"]
+                        .iter()
+                        .any(|name| cond.path.is_ident(name)) =>
+                {
+                    conds.push(meta.clone())
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(value),
+                    ..
+                })) if path.is_ident("preset") => {
+                    preset.get_or_insert(value.value());
+                    args.push(nm.clone());
+                }
+                _ => args.push(nm.clone()),
+            }
+        }
+
+        // `variant(...)` entries are named by their key or preset, other entries by their name
+        let key = match (key, preset) {
+            (Some(key), _) | (None, Some(key)) => key,
+            (None, None) if name != "variant" => name,
+            (None, None) => {
+                return Err(syn::Error::new_spanned(
+                    list.to_token_stream(),
+                    "Expected `key` parameter",
+                ))
+            }
+        };
+        let suffix = suffix.unwrap_or_else(|| to_camel_case(&key));
+
+        Ok(Self {
+            kind,
+            key,
+            suffix,
+            conds,
+            args,
+        })
+    }
+
+    /// Joins the entries of a combination of the axes.
+    fn combine(entries: &[&Self]) -> Self {
+        let mut combined = Self {
+            kind: None,
+            key: String::new(),
+            suffix: String::new(),
+            conds: vec![],
+            args: vec![],
+        };
+        for entry in entries {
+            combined.kind = combined.kind.or(entry.kind);
+            if !combined.key.is_empty() {
+                combined.key.push('_');
+            }
+            combined.key.push_str(&entry.key);
+            combined.suffix.push_str(&entry.suffix);
+            combined.conds.extend(entry.conds.iter().cloned());
+            combined.args.extend(entry.args.iter().cloned());
+        }
+        combined
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The `deprecate(...)` parameter of a variant.
+#[derive(Debug, Clone, Default)]
+pub struct Deprecation {
+    /// The message with `{...}` placeholders.
+    pub note: Option<String>,
+    pub since: Option<String>,
+}
+
+impl Deprecation {
+    /// Replaces the placeholders of the message: `{key}` with the key of the variant, `{name}` with
+    /// the name of the item in the variant, `{original}` with the original name of the item, and
+    /// `{name:KEY}` with the name of the item in the variant with the `KEY` key.
+    pub fn interpolate(
+        note: &str,
+        key: &str,
+        original: &str,
+        name_in: impl Fn(&str) -> Option<String>,
+    ) -> Result<String, String> {
+        let mut result = String::new();
+        let mut rest = note;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| "Unclosed `{` in the message".to_string())?
+                + start;
+            let placeholder = &rest[start + 1..end];
+            let value = match placeholder.split_once(':') {
+                _ if placeholder == "key" => Some(key.to_string()),
+                _ if placeholder == "original" => Some(original.to_string()),
+                _ if placeholder == "name" => name_in(key),
+                Some(("name", other)) => name_in(other),
+                _ => return Err(format!("Unknown placeholder `{{{}}}`", placeholder)),
+            };
+            let value = value
+                .ok_or_else(|| format!("No variant for the placeholder `{{{}}}`", placeholder))?;
+            result.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Places where asyncness is removed in the sync variant.
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncnessRemoval {
+    /// The marked function itself.
+    pub signature: bool,
+    /// Methods of the marked trait or impl.
+    pub members: bool,
+    /// Functions and methods nested in bodies and modules.
+    pub nested: bool,
+    /// Async blocks and async closures.
+    pub closures: bool,
+}
+
+impl AsyncnessRemoval {
+    const NAMES: [&'static str; 4] = ["signature", "members", "nested", "closures"];
+
+    pub fn none() -> Self {
+        Self {
+            signature: false,
+            members: false,
+            nested: false,
+            closures: false,
+        }
+    }
+
+    fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "signature" => Some(&mut self.signature),
+            "members" => Some(&mut self.members),
+            "nested" => Some(&mut self.nested),
+            "closures" => Some(&mut self.closures),
+            _ => None,
+        }
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        let flags = [self.signature, self.members, self.nested, self.closures];
+        Self::NAMES
+            .iter()
+            .zip(flags.iter())
+            .filter_map(|(name, flag)| if *flag { Some(*name) } else { None })
+            .collect()
+    }
+}
+
+impl Default for AsyncnessRemoval {
+    fn default() -> Self {
+        Self {
+            signature: true,
+            members: true,
+            nested: true,
+            closures: true,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Functions and methods whose calls get `.await` in the async variant (for sync-first code).
+#[derive(Debug, Clone, Default)]
+pub struct AddAwait {
+    pub fns: Vec<String>,
+    pub methods: Vec<String>,
+}
+
+impl AddAwait {
+    pub fn is_empty(&self) -> bool {
+        self.fns.is_empty() && self.methods.is_empty()
+    }
+
+    fn names_mut(&mut self, kind: &str) -> Option<&mut Vec<String>> {
+        match kind {
+            "fns" => Some(&mut self.fns),
+            "methods" => Some(&mut self.methods),
+            _ => None,
         }
     }
+
+    fn extend(&mut self, other: &AddAwait) {
+        self.fns.extend_from_slice(&other.fns);
+        self.methods.extend_from_slice(&other.methods);
+    }
+}
+
+/// The wrapper type a trait is implemented for on top of its variant of the other kind, and the
+/// `block_on` function used by the sync variant (the `bridge` parameter).
+#[derive(Debug, Clone)]
+pub struct Bridge {
+    pub wrapper: String,
+    pub block_on: Option<String>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -80,6 +514,8 @@ impl IdentRecord {
         ident: &Ident,
         convert_mode: ConvertMode,
         version_name: Option<&str>,
+        primary: bool,
+        suffix: Option<&str>,
     ) -> Ident {
         if self.keep {
             return ident.clone();
@@ -104,18 +540,54 @@ impl IdentRecord {
                     return Ident::new(&name, ident.span());
                 }
             }
+            ConvertMode::IntoDyn => {}
         };
 
+        if primary {
+            return ident.clone();
+        }
+
+        if let Some(suffix) = suffix {
+            return match self.snake_case {
+                false => Ident::new(&format!("{}{}", ident, suffix), ident.span()),
+                true => Ident::new(
+                    &format!("{}_{}", ident, camel_to_snake_case(suffix)),
+                    ident.span(),
+                ),
+            };
+        }
+
         let suffix = match (self.snake_case, convert_mode) {
             (false, ConvertMode::IntoAsync) => "Async",
             (false, ConvertMode::IntoSync) => "Sync",
             (true, ConvertMode::IntoAsync) => "_async",
             (true, ConvertMode::IntoSync) => "_sync",
+            (false, ConvertMode::IntoDyn) => "Dyn",
+            (true, ConvertMode::IntoDyn) => "_dyn",
         };
 
         Ident::new(&format!("{}{}", ident, suffix), ident.span())
     }
 
+    /// Whether the name in the given variant is specified explicitly rather than generated.
+    pub fn has_explicit_name(&self, convert_mode: ConvertMode, version_name: Option<&str>) -> bool {
+        if self.keep {
+            return true;
+        }
+
+        if let (Some(version_name), Some(idents)) = (version_name, self.idents.as_ref()) {
+            if idents.contains_key(version_name) {
+                return true;
+            }
+        }
+
+        match convert_mode {
+            ConvertMode::IntoSync => self.ident_sync.is_some(),
+            ConvertMode::IntoAsync => self.ident_async.is_some(),
+            ConvertMode::IntoDyn => false,
+        }
+    }
+
     pub fn to_nestedmeta(&self, name: &str) -> syn::NestedMeta {
         let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
 
@@ -174,20 +646,80 @@ pub struct MacroParameters {
     disable: bool,
     key: Option<String>,
     self_name: Option<String>,
+    self_path: Option<String>,
     keep_self: bool,
+    primary: bool,
+    doc_hidden: bool,
+    test_only: Option<Option<String>>,
+    dedup: bool,
+    dispatch: Option<Option<String>>,
+    alias: bool,
+    doc_cfg: bool,
+    register: Option<String>,
+    use_registered: Option<String>,
     // settings
     prefix: Option<String>,
     idents: HashMap<String, IdentRecord>,
     send: Option<bool>,
-    recursive_asyncness_removal: bool,
+    asyncness_removal: Option<AsyncnessRemoval>,
+    stream_macro: Option<String>,
+    dyn_compat: Option<String>,
+    trait_variant: Option<String>,
+    async_trait_path: Option<String>,
+    spawn: Option<String>,
+    locks: Option<String>,
+    lock_methods: Vec<String>,
+    yield_now: Option<String>,
+    dyn_markers: Option<String>,
+    timeout: Option<String>,
+    channels: Option<Vec<(String, String)>>,
+    types: Vec<(String, String)>,
+    crates: Vec<(String, String)>,
+    calls: Vec<(String, String)>,
+    receiver: Option<String>,
+    presets: Vec<String>,
+    test_attr: Option<String>,
+    main_attr: Option<String>,
+    suffix: Option<String>,
+    blocking_ext: Option<String>,
+    delegate: Option<String>,
+    bridge: Option<Bridge>,
+    deprecate: Option<Deprecation>,
+    dyn_from: HashMap<String, String>,
+    delegate_targets: HashMap<String, String>,
+    bridge_cfg: Option<Meta>,
+    variant_keys: Vec<String>,
     // groups
     cfg: Option<Meta>,
+    cond: Option<String>,
+    conditions: HashMap<String, String>,
+    unconditional: bool,
+    merge_cfgs: bool,
+    constness: bool,
+    process_all_macros: bool,
+    impl_future: bool,
+    send_bounds: bool,
+    item_cfgs: Vec<Meta>,
     outer_attrs: Punctuated<NestedMeta, Comma>,
     inner_attrs: Punctuated<NestedMeta, Comma>,
     drop_attrs: Vec<String>,
+    attr_policies: Vec<(String, String)>,
+    strip_methods: Vec<String>,
+    into_future_map: Vec<(String, String)>,
+    methods: Vec<(String, String)>,
+    process_macros: Vec<String>,
+    no_macro_processing: Option<Vec<String>>,
+    add_await: AddAwait,
+    add_generics: Vec<String>,
+    drop_generics: Vec<String>,
+    add_bounds: Vec<String>,
+    drop_bounds: Vec<String>,
+    relax_bounds: Vec<String>,
     replace_features: HashMap<String, String>,
     // versions
     pub versions: Vec<MacroParameterVersion>,
+    default_variant: Option<String>,
+    only: Option<Vec<String>>,
 }
 
 impl std::fmt::Debug for MacroParameters {
@@ -197,15 +729,57 @@ impl std::fmt::Debug for MacroParameters {
             .field("disable", &self.disable)
             .field("key", &self.key)
             .field("self_name", &self.self_name)
+            .field("self_path", &self.self_path)
             .field("prefix", &self.prefix)
             .field("idents", &self.idents)
             .field("send", &self.send)
-            .field(
-                "recursive_asyncness_removal",
-                &self.recursive_asyncness_removal,
-            )
+            .field("asyncness_removal", &self.asyncness_removal)
             .field("keep_self", &self.keep_self)
+            .field("stream_macro", &self.stream_macro)
+            .field("dyn_compat", &self.dyn_compat)
+            .field("trait_variant", &self.trait_variant)
+            .field("async_trait_path", &self.async_trait_path)
+            .field("spawn", &self.spawn)
+            .field("locks", &self.locks)
+            .field("lock_methods", &self.lock_methods)
+            .field("yield_now", &self.yield_now)
+            .field("dyn_markers", &self.dyn_markers)
+            .field("timeout", &self.timeout)
+            .field("channels", &self.channels)
+            .field("types", &self.types)
+            .field("crates", &self.crates)
+            .field("calls", &self.calls)
+            .field("receiver", &self.receiver)
+            .field("presets", &self.presets)
+            .field("test_attr", &self.test_attr)
+            .field("main_attr", &self.main_attr)
+            .field("suffix", &self.suffix)
+            .field("blocking_ext", &self.blocking_ext)
+            .field("delegate", &self.delegate)
+            .field("bridge", &self.bridge)
+            .field("deprecate", &self.deprecate)
+            .field("dyn_from", &self.dyn_from)
+            .field("delegate_targets", &self.delegate_targets)
+            .field("bridge_cfg", &self.bridge_cfg)
+            .field("variant_keys", &self.variant_keys)
+            .field("primary", &self.primary)
+            .field("doc_hidden", &self.doc_hidden)
+            .field("test_only", &self.test_only)
+            .field("dedup", &self.dedup)
+            .field("dispatch", &self.dispatch)
+            .field("alias", &self.alias)
+            .field("doc_cfg", &self.doc_cfg)
+            .field("register", &self.register)
+            .field("use_registered", &self.use_registered)
             .field("cfg", &OptionToTokens(self.cfg.as_ref()))
+            .field("cond", &self.cond)
+            .field("conditions", &self.conditions)
+            .field("unconditional", &self.unconditional)
+            .field("merge_cfgs", &self.merge_cfgs)
+            .field("constness", &self.constness)
+            .field("process_all_macros", &self.process_all_macros)
+            .field("impl_future", &self.impl_future)
+            .field("send_bounds", &self.send_bounds)
             .field(
                 "outer_attrs",
                 &DebugByDisplay(self.outer_attrs.to_token_stream()),
@@ -219,8 +793,22 @@ impl std::fmt::Debug for MacroParameters {
                 &DebugByDisplay(self.outer_attrs.to_token_stream()),
             )
             .field("drop_attrs", &self.drop_attrs)
+            .field("attr_policies", &self.attr_policies)
+            .field("strip_methods", &self.strip_methods)
+            .field("into_future_map", &self.into_future_map)
+            .field("methods", &self.methods)
+            .field("process_macros", &self.process_macros)
+            .field("no_macro_processing", &self.no_macro_processing)
+            .field("add_await", &self.add_await)
+            .field("add_generics", &self.add_generics)
+            .field("drop_generics", &self.drop_generics)
+            .field("add_bounds", &self.add_bounds)
+            .field("drop_bounds", &self.drop_bounds)
+            .field("relax_bounds", &self.relax_bounds)
             .field("replace_features", &self.replace_features)
             .field("versions", &self.versions)
+            .field("default_variant", &self.default_variant)
+            .field("only", &self.only)
             .finish()
     }
 }
@@ -251,7 +839,7 @@ impl MacroParameters {
         MacroParametersBuilder::new().build().unwrap()
     }
 
-    fn from_args<'i>(args: impl IntoIterator<Item = &'i NestedMeta>) -> syn::Result<Self> {
+    pub fn from_args<'i>(args: impl IntoIterator<Item = &'i NestedMeta>) -> syn::Result<Self> {
         let mut builder = MacroParametersBuilder::new();
 
         for arg in args {
@@ -268,11 +856,76 @@ impl MacroParameters {
                         match name.as_str() {
                             "key" => lit_str!(lit, builder, key, "Expected string literal"),
                             "self" => lit_str!(lit, builder, self_name, "Expected string literal"),
+                            "self_path" => {
+                                lit_str!(lit, builder, self_path, "Expected string literal")
+                            }
                             "prefix" => lit_str!(lit, builder, prefix, "Expected string literal"),
                             "send" => lit_str!(lit, builder, send, "Expected string literal"),
+                            "spawn" => lit_str!(lit, builder, spawn, "Expected string literal"),
+                            "locks" => lit_str!(lit, builder, locks, "Expected string literal"),
+                            "yield_now" => {
+                                lit_str!(lit, builder, yield_now, "Expected string literal")
+                            }
+                            "dyn_markers" => {
+                                lit_str!(lit, builder, dyn_markers, "Expected string literal")
+                            }
+                            "timeout" => {
+                                lit_str!(lit, builder, timeout, "Expected string literal")
+                            }
+                            "stream_macro" => {
+                                lit_str!(lit, builder, stream_macro, "Expected string literal")
+                            }
+                            "dyn_compat" => {
+                                lit_str!(lit, builder, dyn_compat, "Expected string literal")
+                            }
+                            "trait_variant" => {
+                                lit_str!(lit, builder, trait_variant, "Expected string literal")
+                            }
+                            "async_trait_path" => {
+                                lit_str!(lit, builder, async_trait_path, "Expected string literal")
+                            }
+                            "register" => {
+                                lit_str!(lit, builder, register, "Expected string literal")
+                            }
+                            "receiver" => {
+                                lit_str!(lit, builder, receiver, "Expected string literal")
+                            }
+                            "suffix" => lit_str!(lit, builder, suffix, "Expected string literal"),
+                            "preset" => lit_str!(lit, builder, preset, "Expected string literal"),
+                            "test_attr" => {
+                                lit_str!(lit, builder, test_attr, "Expected string literal")
+                            }
+                            "main_attr" => {
+                                lit_str!(lit, builder, main_attr, "Expected string literal")
+                            }
+                            "default_variant" => {
+                                lit_str!(lit, builder, default_variant, "Expected string literal")
+                            }
+                            "blocking_ext" => {
+                                lit_str!(lit, builder, blocking_ext, "Expected string literal")
+                            }
+                            "delegate" => {
+                                lit_str!(lit, builder, delegate, "Expected string literal")
+                            }
+                            "dispatch" => {
+                                lit_str!(lit, builder, dispatch_name, "Expected string literal")
+                            }
+                            "test_only" => {
+                                lit_str!(lit, builder, test_only_feature, "Expected string literal")
+                            }
                             "feature" => {
                                 lit_meta!(lit, meta, builder, feature, "Expected string literal")
                             }
+                            "cond" => lit_str!(lit, builder, cond, "Expected string literal"),
+                            "constness" => match lit {
+                                syn::Lit::Bool(value) => builder.constness(value.value),
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        lit.to_token_stream(),
+                                        "Expected `true` or `false`",
+                                    ))
+                                }
+                            },
                             _ => {
                                 return Err(syn::Error::new_spanned(
                                     meta.to_token_stream(),
@@ -292,6 +945,8 @@ impl MacroParameters {
                             .to_string();
                         match name.as_str() {
                             "cfg" => builder.cfg_list(list)?,
+                            "cond" => builder.cond_list(list)?,
+                            "conditions" => builder.conditions(&list.nested)?,
                             "idents" => MacroParametersBuilder::idents(
                                 &mut builder.params.idents,
                                 &list.nested,
@@ -301,6 +956,37 @@ impl MacroParameters {
                             "inner" => builder.inner_attrs(&list.nested)?,
                             "replace_feature" => builder.replace_feature(&list.nested)?,
                             "drop_attrs" => builder.drop_attrs(&list.nested)?,
+                            "attr_policy" => builder.attr_policy(&list.nested)?,
+                            "strip_methods" => builder.strip_methods(&list.nested)?,
+                            "lock_methods" => builder.lock_methods(&list.nested)?,
+                            "into_future_map" => builder.map_into_future(&list.nested)?,
+                            "methods" => builder.methods(&list.nested)?,
+                            "process_macros" => builder.process_macros(&list.nested)?,
+                            "no_macro_processing" => {
+                                builder.no_macro_processing(Some(&list.nested))?
+                            }
+                            "add_await" => builder.add_await(&list.nested)?,
+                            "add_generics" => builder.add_generics(&list.nested)?,
+                            "drop_generics" => builder.drop_generics(&list.nested)?,
+                            "add_bounds" => builder.add_bounds(&list.nested)?,
+                            "drop_bounds" => builder.drop_bounds(&list.nested)?,
+                            "relax_bounds" => builder.relax_bounds(&list.nested)?,
+                            "use_registered" => builder.use_registered(list)?,
+                            "only" => builder.only(list)?,
+                            "deprecate" => builder.deprecate(list)?,
+                            "variant" => builder.variant(list)?,
+                            "matrix" => builder.matrix(list)?,
+                            "exclusive" => builder.exclusive(list)?,
+                            "asyncness_removal" => builder.asyncness_removal(list)?,
+                            "channels" => builder.channels(Some(&list.nested))?,
+                            "types" => builder.types(&list.nested)?,
+                            "crates" => builder.crates(&list.nested)?,
+                            "calls" => builder.calls(&list.nested)?,
+                            DYN_FROM => builder.dyn_from(&list.nested)?,
+                            DELEGATE_TARGETS => builder.delegate_targets(&list.nested)?,
+                            BRIDGE_CFG => builder.bridge_cfg(&list.nested)?,
+                            "bridge" => builder.bridge(&list.nested)?,
+                            VARIANT_KEYS => builder.variant_keys(&list.nested)?,
                             name @ _ => builder.version_or_inner_attr(name, &list.nested, meta)?,
                         }
                     }
@@ -309,8 +995,30 @@ impl MacroParameters {
                             match name.as_str() {
                                 MODE_INTO_ASYNC => builder.mode_into_async()?,
                                 MODE_INTO_SYNC => builder.mode_into_sync()?,
+                                MODE_INTO_DYN => builder.mode_into_dyn()?,
                                 "disable" => builder.disable(),
                                 "keep_self" => builder.keep_self(),
+                                "primary" => builder.primary(),
+                                "doc_hidden" => builder.doc_hidden(),
+                                "test_only" => builder.test_only(),
+                                "dedup" => builder.dedup(),
+                                "dispatch" => builder.dispatch(),
+                                "alias" => builder.alias(),
+                                "no_cfg" => builder.no_cfg(),
+                                "doc_cfg" => builder.doc_cfg(),
+                                "merge_cfgs" => builder.merge_cfgs(),
+                                "constness" => builder.constness(true),
+                                "process_all_macros" => builder.process_all_macros(),
+                                "impl_future" => builder.impl_future(),
+                                "send_bounds" => builder.send_bounds(),
+                                "no_macro_processing" => builder.no_macro_processing(None)?,
+                                "spawn" => builder.spawn(DEFAULT_SPAWN.to_string())?,
+                                "locks" => builder.locks(LOCK_POLICIES[0].to_string())?,
+                                "dyn_markers" => {
+                                    builder.dyn_markers(DYN_MARKERS_POLICIES[0].to_string())?
+                                }
+                                "timeout" => builder.timeout(TIMEOUT_OK.to_string())?,
+                                "channels" => builder.channels(None)?,
                                 _ => builder.inner_attr(meta)?,
                             }
                         } else {
@@ -369,6 +1077,9 @@ impl MacroParameters {
                 ConvertMode::IntoSync => {
                     args.push(NestedMeta::Meta(Meta::Path(make_path(MODE_INTO_SYNC))))
                 }
+                ConvertMode::IntoDyn => {
+                    args.push(NestedMeta::Meta(Meta::Path(make_path(MODE_INTO_DYN))))
+                }
             }
         }
 
@@ -380,10 +1091,111 @@ impl MacroParameters {
             args.push(NestedMeta::Meta(Meta::Path(make_path("keep_self"))));
         }
 
+        if self.primary {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("primary"))));
+        }
+
+        if self.doc_hidden {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("doc_hidden"))));
+        }
+
+        match &self.test_only {
+            Some(Some(feature)) => args.push(make_nestedmeta_namevalue("test_only", feature)),
+            Some(None) => args.push(NestedMeta::Meta(Meta::Path(make_path("test_only")))),
+            None => {}
+        }
+
+        if self.dedup {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("dedup"))));
+        }
+
+        match &self.dispatch {
+            Some(Some(name)) => args.push(make_nestedmeta_namevalue("dispatch", name)),
+            Some(None) => args.push(NestedMeta::Meta(Meta::Path(make_path("dispatch")))),
+            None => {}
+        }
+
+        if self.alias {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("alias"))));
+        }
+
+        if self.unconditional {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("no_cfg"))));
+        }
+
+        if self.doc_cfg {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("doc_cfg"))));
+        }
+
+        if self.merge_cfgs {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("merge_cfgs"))));
+        }
+
+        if self.constness {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("constness"))));
+        }
+
+        if self.process_all_macros {
+            args.push(NestedMeta::Meta(Meta::Path(make_path(
+                "process_all_macros",
+            ))));
+        }
+
+        if self.impl_future {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("impl_future"))));
+        }
+
+        if self.send_bounds {
+            args.push(NestedMeta::Meta(Meta::Path(make_path("send_bounds"))));
+        }
+
         if let Some(key) = &self.key {
             args.push(make_nestedmeta_namevalue("key", key.as_str()));
         }
 
+        if let Some(register) = &self.register {
+            args.push(make_nestedmeta_namevalue("register", register.as_str()));
+        }
+
+        if let Some(deprecate) = &self.deprecate {
+            let mut nested = Punctuated::new();
+            if let Some(note) = &deprecate.note {
+                nested.push(make_nestedmeta_namevalue("note", note));
+            }
+            if let Some(since) = &deprecate.since {
+                nested.push(make_nestedmeta_namevalue("since", since));
+            }
+            args.push(make_nestedmeta_list("deprecate", nested));
+        }
+
+        if let Some(default_variant) = &self.default_variant {
+            args.push(make_nestedmeta_namevalue(
+                "default_variant",
+                default_variant,
+            ));
+        }
+
+        if let Some(only) = &self.only {
+            let nested = only
+                .iter()
+                .map(|key| NestedMeta::Lit(Lit::Str(LitStr::new(key, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("only", nested));
+        }
+
+        if let Some(use_registered) = &self.use_registered {
+            let mut nested = Punctuated::new();
+            nested.push(NestedMeta::Lit(Lit::Str(LitStr::new(
+                use_registered.as_str(),
+                Span::call_site(),
+            ))));
+            args.push(make_nestedmeta_list("use_registered", nested));
+        }
+
+        if let Some(self_path) = &self.self_path {
+            args.push(make_nestedmeta_namevalue("self_path", self_path.as_str()));
+        }
+
         if let Some(self_name) = &self.self_name {
             args.push(make_nestedmeta_namevalue("self", self_name.as_str()));
         }
@@ -394,83 +1206,428 @@ impl MacroParameters {
 
         if let Some(send) = &self.send {
             args.push(make_nestedmeta_namevalue(
-                "prefix",
+                "send",
                 if *send { "Send" } else { "?Send" },
             ));
         }
 
-        if let Some(cfg) = &self.cfg {
-            let mut nested = Punctuated::new();
-            nested.push(NestedMeta::Meta(cfg.clone()));
-            args.push(make_nestedmeta_list("cfg", nested));
+        if let Some(stream_macro) = &self.stream_macro {
+            args.push(make_nestedmeta_namevalue(
+                "stream_macro",
+                stream_macro.as_str(),
+            ));
         }
 
-        if !self.outer_attrs.is_empty() {
-            args.push(make_nestedmeta_list("outer", self.outer_attrs.clone()));
+        if let Some(dyn_compat) = &self.dyn_compat {
+            args.push(make_nestedmeta_namevalue("dyn_compat", dyn_compat.as_str()));
         }
 
-        if !self.inner_attrs.is_empty() {
-            args.push(make_nestedmeta_list("inner", self.inner_attrs.clone()));
+        if let Some(trait_variant) = &self.trait_variant {
+            args.push(make_nestedmeta_namevalue(
+                "trait_variant",
+                trait_variant.as_str(),
+            ));
         }
 
-        if !self.idents.is_empty() {
-            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
-            for (name, value) in &self.idents {
-                nested.push(value.to_nestedmeta(name.as_str()));
-            }
-            let arg = make_nestedmeta_list("idents", nested);
-            args.push(arg);
+        if let Some(async_trait_path) = &self.async_trait_path {
+            args.push(make_nestedmeta_namevalue(
+                "async_trait_path",
+                async_trait_path.as_str(),
+            ));
         }
 
-        if !self.drop_attrs.is_empty() {
+        if let Some(spawn) = &self.spawn {
+            args.push(make_nestedmeta_namevalue("spawn", spawn.as_str()));
+        }
+
+        if let Some(locks) = &self.locks {
+            args.push(make_nestedmeta_namevalue("locks", locks.as_str()));
+        }
+
+        if !self.lock_methods.is_empty() {
             let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
-            for name in &self.drop_attrs {
+            for name in &self.lock_methods {
                 nested.push(NestedMeta::Meta(Meta::Path(make_path(name.as_str()))));
             }
-            let arg = make_nestedmeta_list("drop_attrs", nested);
+            let arg = make_nestedmeta_list("lock_methods", nested);
             args.push(arg);
         }
 
-        if !self.replace_features.is_empty() {
-            for (name, value) in &self.replace_features {
-                let mut inner = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
-                inner.push(NestedMeta::Lit(Lit::Str(LitStr::new(
-                    name.as_str(),
-                    Span::call_site(),
-                ))));
-                inner.push(NestedMeta::Lit(Lit::Str(LitStr::new(
-                    value.as_str(),
-                    Span::call_site(),
-                ))));
-                let arg = make_nestedmeta_list("replace_feature", inner);
-                args.push(arg);
+        if let Some(dyn_markers) = &self.dyn_markers {
+            args.push(make_nestedmeta_namevalue(
+                "dyn_markers",
+                dyn_markers.as_str(),
+            ));
+        }
+
+        if let Some(yield_now) = &self.yield_now {
+            args.push(make_nestedmeta_namevalue("yield_now", yield_now.as_str()));
+        }
+
+        if let Some(timeout) = &self.timeout {
+            args.push(make_nestedmeta_namevalue("timeout", timeout.as_str()));
+        }
+
+        if let Some(channels) = &self.channels {
+            let nested = channels
+                .iter()
+                .map(|(from, to)| {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path: syn::parse_str(from).unwrap(),
+                        eq_token: Default::default(),
+                        lit: Lit::Str(LitStr::new(to, Span::call_site())),
+                    }))
+                })
+                .collect();
+            args.push(make_nestedmeta_list("channels", nested));
+        }
+
+        if !self.types.is_empty() {
+            let nested = self
+                .types
+                .iter()
+                .map(|(from, to)| {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path: syn::parse_str(from).unwrap(),
+                        eq_token: Default::default(),
+                        lit: Lit::Str(LitStr::new(to, Span::call_site())),
+                    }))
+                })
+                .collect();
+            args.push(make_nestedmeta_list("types", nested));
+        }
+
+        if !self.crates.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (name, target) in &self.crates {
+                nested.push(make_nestedmeta_namevalue(name, target));
             }
+            args.push(make_nestedmeta_list("crates", nested));
         }
 
-        for version in &self.versions {
-            let (name, nested) = match version.kind {
-                ConvertMode::IntoSync | ConvertMode::IntoAsync => {
-                    (version.kind.to_str(), version.params.to_nestedmeta(None))
-                }
-            };
+        if !self.calls.is_empty() {
+            let nested = self
+                .calls
+                .iter()
+                .map(|(from, to)| {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path: syn::parse_str(from).unwrap(),
+                        eq_token: Default::default(),
+                        lit: Lit::Str(LitStr::new(to, Span::call_site())),
+                    }))
+                })
+                .collect();
+            args.push(make_nestedmeta_list("calls", nested));
+        }
 
-            let arg = make_nestedmeta_list(name, nested);
-            args.push(arg);
+        if let Some(receiver) = &self.receiver {
+            args.push(make_nestedmeta_namevalue("receiver", receiver.as_str()));
         }
 
-        args
-    }
+        for preset in &self.presets {
+            args.push(make_nestedmeta_namevalue("preset", preset.as_str()));
+        }
 
-    pub fn extend_tokenstream2_with_cfg_outer_attrs(
-        &self,
-        ts: &mut TokenStream2,
-    ) -> syn::Result<()> {
-        if let Some(cfg_cond) = &self.cfg {
-            let cfg_ts = cfg_cond.into_token_stream();
-            ts.extend(quote!(#[cfg(#cfg_ts)]));
-        };
+        if let Some(test_attr) = &self.test_attr {
+            args.push(make_nestedmeta_namevalue("test_attr", test_attr.as_str()));
+        }
 
-        for attr in &self.outer_attrs {
+        if let Some(main_attr) = &self.main_attr {
+            args.push(make_nestedmeta_namevalue("main_attr", main_attr.as_str()));
+        }
+
+        if let Some(suffix) = &self.suffix {
+            args.push(make_nestedmeta_namevalue("suffix", suffix.as_str()));
+        }
+
+        if let Some(blocking_ext) = &self.blocking_ext {
+            args.push(make_nestedmeta_namevalue(
+                "blocking_ext",
+                blocking_ext.as_str(),
+            ));
+        }
+
+        if let Some(delegate) = &self.delegate {
+            args.push(make_nestedmeta_namevalue("delegate", delegate.as_str()));
+        }
+
+        if let Some(bridge) = &self.bridge {
+            let mut nested = Punctuated::new();
+            nested.push(make_nestedmeta_namevalue(
+                "wrapper",
+                bridge.wrapper.as_str(),
+            ));
+            if let Some(block_on) = &bridge.block_on {
+                nested.push(make_nestedmeta_namevalue("block_on", block_on.as_str()));
+            }
+            args.push(make_nestedmeta_list("bridge", nested));
+        }
+
+        if let Some(asyncness_removal) = &self.asyncness_removal {
+            let nested = asyncness_removal
+                .names()
+                .into_iter()
+                .map(|name| NestedMeta::Meta(Meta::Path(make_path(name))))
+                .collect();
+            args.push(make_nestedmeta_list("asyncness_removal", nested));
+        }
+
+        if !self.dyn_from.is_empty() {
+            let mut dyn_from = self.dyn_from.iter().collect::<Vec<_>>();
+            dyn_from.sort();
+            let nested = dyn_from
+                .into_iter()
+                .map(|(name, target)| make_nestedmeta_namevalue(name, target))
+                .collect();
+            args.push(make_nestedmeta_list(DYN_FROM, nested));
+        }
+
+        if !self.delegate_targets.is_empty() {
+            let mut targets = self.delegate_targets.iter().collect::<Vec<_>>();
+            targets.sort();
+            let nested = targets
+                .into_iter()
+                .map(|(name, target)| make_nestedmeta_namevalue(name, target))
+                .collect();
+            args.push(make_nestedmeta_list(DELEGATE_TARGETS, nested));
+        }
+
+        if let Some(cfg) = &self.bridge_cfg {
+            args.push(make_nestedmeta_list(
+                BRIDGE_CFG,
+                Punctuated::from_iter([NestedMeta::Meta(cfg.clone())]),
+            ));
+        }
+
+        if !self.variant_keys.is_empty() {
+            let nested = self
+                .variant_keys
+                .iter()
+                .map(|key| NestedMeta::Lit(Lit::Str(LitStr::new(key, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list(VARIANT_KEYS, nested));
+        }
+
+        if let Some(cfg) = &self.cfg {
+            let mut nested = Punctuated::new();
+            nested.push(NestedMeta::Meta(cfg.clone()));
+            args.push(make_nestedmeta_list("cfg", nested));
+        }
+
+        if let Some(cond) = &self.cond {
+            args.push(make_nestedmeta_namevalue("cond", cond));
+        }
+
+        if !self.conditions.is_empty() {
+            let mut conditions = self.conditions.iter().collect::<Vec<_>>();
+            conditions.sort();
+            let nested = conditions
+                .into_iter()
+                .map(|(name, pred)| make_nestedmeta_namevalue(name, pred))
+                .collect();
+            args.push(make_nestedmeta_list("conditions", nested));
+        }
+
+        if !self.outer_attrs.is_empty() {
+            args.push(make_nestedmeta_list("outer", self.outer_attrs.clone()));
+        }
+
+        if !self.inner_attrs.is_empty() {
+            args.push(make_nestedmeta_list("inner", self.inner_attrs.clone()));
+        }
+
+        if !self.idents.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (name, value) in &self.idents {
+                nested.push(value.to_nestedmeta(name.as_str()));
+            }
+            let arg = make_nestedmeta_list("idents", nested);
+            args.push(arg);
+        }
+
+        if !self.drop_attrs.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for name in &self.drop_attrs {
+                nested.push(NestedMeta::Meta(Meta::Path(make_path(name.as_str()))));
+            }
+            let arg = make_nestedmeta_list("drop_attrs", nested);
+            args.push(arg);
+        }
+
+        if !self.attr_policies.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (name, policy) in &self.attr_policies {
+                nested.push(NestedMeta::Meta(Meta::NameValue(syn::MetaNameValue {
+                    path: syn::parse_str(name).unwrap(),
+                    eq_token: syn::Token![=](Span::call_site()),
+                    lit: Lit::Str(LitStr::new(policy, Span::call_site())),
+                })));
+            }
+            let arg = make_nestedmeta_list("attr_policy", nested);
+            args.push(arg);
+        }
+
+        if !self.strip_methods.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for name in &self.strip_methods {
+                nested.push(NestedMeta::Meta(Meta::Path(make_path(name.as_str()))));
+            }
+            let arg = make_nestedmeta_list("strip_methods", nested);
+            args.push(arg);
+        }
+
+        if !self.into_future_map.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (method, target) in &self.into_future_map {
+                nested.push(make_nestedmeta_namevalue(method, target));
+            }
+            let arg = make_nestedmeta_list("into_future_map", nested);
+            args.push(arg);
+        }
+
+        if !self.methods.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (method, target) in &self.methods {
+                nested.push(make_nestedmeta_namevalue(method, target));
+            }
+            let arg = make_nestedmeta_list("methods", nested);
+            args.push(arg);
+        }
+
+        if !self.process_macros.is_empty() {
+            let nested = self
+                .process_macros
+                .iter()
+                .map(|name| NestedMeta::Lit(Lit::Str(LitStr::new(name, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("process_macros", nested));
+        }
+
+        match &self.no_macro_processing {
+            Some(names) if names.is_empty() => {
+                args.push(NestedMeta::Meta(Meta::Path(make_path(
+                    "no_macro_processing",
+                ))));
+            }
+            Some(names) => {
+                let nested = names
+                    .iter()
+                    .map(|name| NestedMeta::Lit(Lit::Str(LitStr::new(name, Span::call_site()))))
+                    .collect();
+                args.push(make_nestedmeta_list("no_macro_processing", nested));
+            }
+            None => {}
+        }
+
+        if !self.add_await.is_empty() {
+            let mut nested = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+            for (kind, names) in [
+                ("fns", &self.add_await.fns),
+                ("methods", &self.add_await.methods),
+            ] {
+                if !names.is_empty() {
+                    let names = names
+                        .iter()
+                        .map(|name| NestedMeta::Meta(Meta::Path(make_path(name.as_str()))))
+                        .collect();
+                    nested.push(make_nestedmeta_list(kind, names));
+                }
+            }
+            args.push(make_nestedmeta_list("add_await", nested));
+        }
+
+        if !self.add_generics.is_empty() {
+            let nested = self
+                .add_generics
+                .iter()
+                .map(|param| NestedMeta::Lit(Lit::Str(LitStr::new(param, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("add_generics", nested));
+        }
+
+        if !self.drop_generics.is_empty() {
+            let nested = self
+                .drop_generics
+                .iter()
+                .map(|name| NestedMeta::Lit(Lit::Str(LitStr::new(name, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("drop_generics", nested));
+        }
+
+        if !self.add_bounds.is_empty() {
+            let nested = self
+                .add_bounds
+                .iter()
+                .map(|pred| NestedMeta::Lit(Lit::Str(LitStr::new(pred, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("add_bounds", nested));
+        }
+
+        if !self.drop_bounds.is_empty() {
+            let nested = self
+                .drop_bounds
+                .iter()
+                .map(|bound| NestedMeta::Lit(Lit::Str(LitStr::new(bound, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("drop_bounds", nested));
+        }
+
+        if !self.relax_bounds.is_empty() {
+            let nested = self
+                .relax_bounds
+                .iter()
+                .map(|bound| NestedMeta::Lit(Lit::Str(LitStr::new(bound, Span::call_site()))))
+                .collect();
+            args.push(make_nestedmeta_list("relax_bounds", nested));
+        }
+
+        if !self.replace_features.is_empty() {
+            for (name, value) in &self.replace_features {
+                let mut inner = Punctuated::<syn::NestedMeta, syn::token::Comma>::new();
+                inner.push(NestedMeta::Lit(Lit::Str(LitStr::new(
+                    name.as_str(),
+                    Span::call_site(),
+                ))));
+                inner.push(NestedMeta::Lit(Lit::Str(LitStr::new(
+                    value.as_str(),
+                    Span::call_site(),
+                ))));
+                let arg = make_nestedmeta_list("replace_feature", inner);
+                args.push(arg);
+            }
+        }
+
+        for version in &self.versions {
+            let (name, nested) = match version.kind {
+                ConvertMode::IntoSync | ConvertMode::IntoAsync | ConvertMode::IntoDyn => {
+                    (version.kind.to_str(), version.params.to_nestedmeta(None))
+                }
+            };
+
+            let arg = make_nestedmeta_list(name, nested);
+            args.push(arg);
+        }
+
+        args
+    }
+
+    pub fn extend_tokenstream2_with_cfg_outer_attrs(
+        &self,
+        ts: &mut TokenStream2,
+    ) -> syn::Result<()> {
+        if let Some(cfg_cond) = self.cfg_get() {
+            let cfg_ts = cfg_cond.into_token_stream();
+            ts.extend(quote!(#[cfg(#cfg_ts)]));
+            if self.doc_cfg {
+                ts.extend(quote!(#[cfg_attr(docsrs, doc(cfg(#cfg_ts)))]));
+            }
+        };
+
+        self.extend_tokenstream2_with_outer_attrs(ts)
+    }
+
+    pub fn extend_tokenstream2_with_outer_attrs(&self, ts: &mut TokenStream2) -> syn::Result<()> {
+        for attr in &self.outer_attrs {
             match attr {
                 NestedMeta::Meta(_) => {
                     let attr_ts = attr.into_token_stream();
@@ -490,6 +1647,10 @@ impl MacroParameters {
     }
 
     pub fn extend_tokenstream2_with_inner_attrs(&self, ts: &mut TokenStream2) -> syn::Result<()> {
+        if self.doc_hidden {
+            ts.extend(quote!(#[doc(hidden)]));
+        }
+
         for attr in &self.inner_attrs {
             match attr {
                 NestedMeta::Meta(_) => {
@@ -526,22 +1687,169 @@ impl MacroParameters {
             child.keep_self = true;
         }
 
+        if parent.dedup {
+            child.dedup = true;
+        }
+
+        if parent.alias {
+            child.alias = true;
+        }
+
+        if parent.unconditional {
+            child.unconditional = true;
+        }
+
+        if parent.doc_cfg {
+            child.doc_cfg = true;
+        }
+
+        if parent.merge_cfgs {
+            child.merge_cfgs = true;
+        }
+
+        if parent.process_all_macros {
+            child.process_all_macros = true;
+        }
+
+        if parent.impl_future {
+            child.impl_future = true;
+        }
+
+        if parent.send_bounds {
+            child.send_bounds = true;
+        }
+
+        if child.dyn_compat.is_none() {
+            child.dyn_compat = parent.dyn_compat.clone();
+        }
+
+        if child.async_trait_path.is_none() {
+            child.async_trait_path = parent.async_trait_path.clone();
+        }
+
+        if child.stream_macro.is_none() {
+            child.stream_macro = parent.stream_macro.clone();
+        }
+
+        if child.spawn.is_none() {
+            child.spawn = parent.spawn.clone();
+        }
+
+        if child.locks.is_none() {
+            child.locks = parent.locks.clone();
+        }
+
+        if !parent.lock_methods.is_empty() {
+            let mut new_lock_methods = parent.lock_methods.clone();
+            new_lock_methods.extend_from_slice(&child.lock_methods);
+            child.lock_methods = new_lock_methods;
+        }
+
+        if child.dyn_markers.is_none() {
+            child.dyn_markers = parent.dyn_markers.clone();
+        }
+
+        if child.yield_now.is_none() {
+            child.yield_now = parent.yield_now.clone();
+        }
+
+        if child.timeout.is_none() {
+            child.timeout = parent.timeout.clone();
+        }
+
+        if child.channels.is_none() {
+            child.channels = parent.channels.clone();
+        }
+
+        if !parent.types.is_empty() {
+            let mut new_types = parent.types.clone();
+            new_types.extend_from_slice(&child.types);
+            child.types = new_types;
+        }
+
+        if !parent.calls.is_empty() {
+            let mut new_calls = parent.calls.clone();
+            new_calls.extend_from_slice(&child.calls);
+            child.calls = new_calls;
+        }
+
+        if !parent.crates.is_empty() {
+            let mut new_crates = parent.crates.clone();
+            new_crates.extend_from_slice(&child.crates);
+            child.crates = new_crates;
+        }
+
+        if child.receiver.is_none() {
+            child.receiver = parent.receiver.clone();
+        }
+
+        if child.asyncness_removal.is_none() {
+            child.asyncness_removal = parent.asyncness_removal;
+        }
+
         if !parent.idents.is_empty() {
             child.idents.extend(parent.idents.clone());
         }
 
+        if !parent.attr_policies.is_empty() {
+            let mut new_attr_policies = parent.attr_policies.clone();
+            new_attr_policies.extend_from_slice(&child.attr_policies);
+            child.attr_policies = new_attr_policies;
+        }
+
         if !parent.drop_attrs.is_empty() {
             let mut new_drop_attrs = parent.drop_attrs.clone();
             new_drop_attrs.extend_from_slice(&child.drop_attrs);
             child.drop_attrs = new_drop_attrs;
         }
 
+        if !parent.strip_methods.is_empty() {
+            let mut new_strip_methods = parent.strip_methods.clone();
+            new_strip_methods.extend_from_slice(&child.strip_methods);
+            child.strip_methods = new_strip_methods;
+        }
+
+        if child.no_macro_processing.is_none() {
+            child.no_macro_processing = parent.no_macro_processing.clone();
+        }
+
+        if !parent.process_macros.is_empty() {
+            let mut new_process_macros = parent.process_macros.clone();
+            new_process_macros.extend_from_slice(&child.process_macros);
+            child.process_macros = new_process_macros;
+        }
+
+        if !parent.methods.is_empty() {
+            let mut new_methods = parent.methods.clone();
+            new_methods.extend_from_slice(&child.methods);
+            child.methods = new_methods;
+        }
+
+        if !parent.into_future_map.is_empty() {
+            let mut new_into_future_map = parent.into_future_map.clone();
+            new_into_future_map.extend_from_slice(&child.into_future_map);
+            child.into_future_map = new_into_future_map;
+        }
+
+        if !parent.add_await.is_empty() {
+            let mut new_add_await = parent.add_await.clone();
+            new_add_await.extend(&child.add_await);
+            child.add_await = new_add_await;
+        }
+
         if !parent.replace_features.is_empty() {
             child
                 .replace_features
                 .extend(parent.replace_features.clone());
         }
 
+        for (name, pred) in &parent.conditions {
+            child
+                .conditions
+                .entry(name.clone())
+                .or_insert_with(|| pred.clone());
+        }
+
         Ok(())
     }
 
@@ -549,6 +1857,87 @@ impl MacroParameters {
         self.disable
     }
 
+    /// Returns the full condition under which the variant should appear.
+    pub fn cfg_get(&self) -> Option<Meta> {
+        if self.unconditional {
+            return Self::cfg_all(self.item_cfgs.clone());
+        }
+
+        let test_cfg: Option<Meta> = self.test_only.as_ref().map(|feature| match feature {
+            Some(feature) => parse_quote!(any(test, feature = #feature)),
+            None => parse_quote!(test),
+        });
+
+        let preset_cfgs = self
+            .presets_get()
+            .filter_map(|preset| preset.cfg)
+            .map(|cfg| syn::parse_str::<Meta>(cfg).expect("invalid preset condition"));
+
+        let conds: Vec<Meta> = self
+            .item_cfgs
+            .iter()
+            .cloned()
+            .chain(test_cfg)
+            .chain(self.cfg.clone())
+            .chain(preset_cfgs)
+            .collect();
+
+        Self::cfg_all(conds)
+    }
+
+    /// Replaces the named conditions (`cond`) of the variants with the predicates defined by the
+    /// `conditions` parameter. With `strict`, a condition name that is not defined is an error,
+    /// otherwise it is left for the parameters inherited later.
+    pub fn conditions_apply(&mut self, strict: bool) -> syn::Result<()> {
+        for version in &mut self.versions {
+            let params = &mut version.params;
+            let name = match &params.cond {
+                Some(name) => name,
+                None => continue,
+            };
+            let pred = match self.conditions.get(name) {
+                Some(pred) => pred,
+                None if strict => {
+                    let mut names = self.conditions.keys().cloned().collect::<Vec<_>>();
+                    names.sort();
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        format!(
+                            "Unknown condition `{}`, defined conditions: {}",
+                            name,
+                            if names.is_empty() {
+                                "none".to_string()
+                            } else {
+                                names.join(", ")
+                            }
+                        ),
+                    ));
+                }
+                None => continue,
+            };
+            let pred: Meta = syn::parse_str(pred).expect("checked in the builder");
+            params.cfg = Some(match &params.cfg {
+                Some(cfg) => parse_quote!(all(#cfg, #pred)),
+                None => pred,
+            });
+            params.cond = None;
+        }
+        Ok(())
+    }
+
+    fn cfg_all(conds: Vec<Meta>) -> Option<Meta> {
+        match conds.len() {
+            0 => None,
+            1 => conds.into_iter().next(),
+            _ => Some(parse_quote!(all(#(#conds),*))),
+        }
+    }
+
+    /// Makes the variant appear regardless of its condition.
+    pub fn unconditional_set(&mut self) {
+        self.unconditional = true;
+    }
+
     pub fn mode_get(&self) -> Option<ConvertMode> {
         self.mode
     }
@@ -586,30 +1975,692 @@ impl MacroParameters {
             .unwrap_or(DEFAULT_CRATE_NAME)
     }
 
+    /// Returns the `send` parameter, or the send policy of the first preset defining it.
     pub fn send_get(&self) -> Option<bool> {
         self.send
+            .or_else(|| self.presets_get().find_map(|preset| preset.send))
+    }
+
+    /// Returns the `send` parameter only if it is specified explicitly.
+    pub fn send_explicit_get(&self) -> Option<bool> {
+        self.send
+    }
+
+    /// Returns the first preset, which gives the mode and the key of the variant.
+    pub fn preset_get(&self) -> Option<&'static Preset> {
+        self.presets_get().next()
+    }
+
+    /// Returns the presets of the variant, in the order they are specified.
+    pub fn presets_get(&self) -> impl Iterator<Item = &'static Preset> + '_ {
+        self.presets.iter().filter_map(|name| Preset::find(name))
+    }
+
+    pub fn test_attr_get(&self) -> Option<&str> {
+        self.test_attr
+            .as_deref()
+            .or_else(|| self.presets_get().find_map(|preset| preset.test_attr))
+    }
+
+    pub fn main_attr_get(&self) -> Option<&str> {
+        self.main_attr
+            .as_deref()
+            .or_else(|| self.presets_get().find_map(|preset| preset.main_attr))
+    }
+
+    pub fn self_name_get(&self) -> Option<&str> {
+        self.self_name.as_deref()
+    }
+
+    pub fn self_name_set(&mut self, self_name: String) {
+        self.self_name = Some(self_name);
+    }
+
+    pub fn keep_self_set(&mut self) {
+        self.keep_self = true;
+    }
+
+    /// Returns the name of the module the variant is placed in (the last segment of `self_path`).
+    pub fn self_module_get(&self) -> Option<Ident> {
+        let path = syn::parse_str::<syn::Path>(self.self_path.as_deref()?).ok()?;
+        path.segments.last().map(|segment| segment.ident.clone())
+    }
+
+    pub fn primary_get(&self) -> bool {
+        self.primary
+    }
+
+    pub fn dedup_get(&self) -> bool {
+        self.dedup
+    }
+
+    /// Whether the dispatch enum is generated, with its explicit name if any.
+    pub fn dispatch_get(&self) -> Option<Option<&str>> {
+        self.dispatch.as_ref().map(|name| name.as_deref())
+    }
+
+    pub fn alias_get(&self) -> bool {
+        self.alias
+    }
+
+    pub fn doc_cfg_get(&self) -> bool {
+        self.doc_cfg
+    }
+
+    pub fn merge_cfgs_get(&self) -> bool {
+        self.merge_cfgs
+    }
+
+    pub fn constness_get(&self) -> bool {
+        self.constness
+    }
+
+    pub fn process_all_macros_get(&self) -> bool {
+        self.process_all_macros
+    }
+
+    pub fn impl_future_get(&self) -> bool {
+        self.impl_future
+    }
+
+    pub fn send_bounds_get(&self) -> bool {
+        self.send_bounds
+    }
+
+    /// Adds the conditions of the `#[cfg]` attributes of the item to the condition of the variant.
+    pub fn item_cfgs_set(&mut self, cfgs: Vec<Meta>) {
+        self.item_cfgs = cfgs;
+    }
+
+    /// Returns the name the item with the `name` gets in the variant.
+    pub fn self_name_renamed(
+        &self,
+        name: &str,
+        snake_case: bool,
+        convert_mode: ConvertMode,
+    ) -> String {
+        let mut params = self.clone();
+        params.original_self_name_set(name, snake_case);
+        params
+            .idents_renamed(convert_mode)
+            .into_iter()
+            .find(|(original, _, _)| original == name)
+            .map(|(_, target, _)| target)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    pub fn register_take(&mut self) -> Option<String> {
+        self.register.take()
+    }
+
+    /// Leaves only the variants listed in `only(...)`.
+    pub fn only_apply(&mut self) -> syn::Result<()> {
+        let only = match self.only.take() {
+            Some(only) => only,
+            None => return Ok(()),
+        };
+        for key in &only {
+            if !self
+                .versions
+                .iter()
+                .any(|v| v.params.key_get() == Some(key.as_str()))
+            {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("The `only` parameter refers to unknown variant `{}`", key),
+                ));
+            }
+        }
+        self.versions.retain(|version| {
+            only.iter()
+                .any(|key| version.params.key_get() == Some(key.as_str()))
+        });
+        Ok(())
+    }
+
+    pub fn use_registered_take(&mut self) -> Option<String> {
+        self.use_registered.take()
+    }
+
+    pub fn stream_macro_get(&self) -> Option<&str> {
+        self.stream_macro.as_deref()
+    }
+
+    /// Returns the path of the `trait_variant::make` attribute, if it is to be added to the async
+    /// variant of a trait instead of `async_trait` (`send = "trait_variant"`).
+    pub fn trait_variant_get(&self) -> Option<syn::Path> {
+        // checked by the builder
+        self.trait_variant
+            .as_deref()
+            .map(|path| syn::parse_str(path).unwrap())
+    }
+
+    /// Returns the path of the `async_trait` attribute added to the async variants of traits and
+    /// impl blocks.
+    pub fn async_trait_path_get(&self) -> &str {
+        self.async_trait_path
+            .as_deref()
+            .unwrap_or(DEFAULT_ASYNC_TRAIT)
+    }
+
+    /// Returns the path of the attribute making the async variant of a trait dyn-compatible.
+    pub fn dyn_compat_get(&self) -> Option<syn::Path> {
+        self.dyn_compat
+            .as_deref()
+            .map(|dyn_compat| match dyn_compat {
+                DYN_COMPAT_DYNOSAUR => syn::parse_quote!(dynosaur::dynosaur),
+                // checked by the builder
+                path => syn::parse_str(path).unwrap(),
+            })
+    }
+
+    /// Returns the policy of handling the poisoned locks, if the async locks are to be mapped to
+    /// the locks of `std`.
+    pub fn locks_get(&self) -> Option<&str> {
+        self.locks.as_deref()
+    }
+
+    /// Whether the awaited calls of the method are converted to the locking of a lock of `std`
+    /// regardless of their receiver: as listed in `lock_methods`, or all of them without it.
+    pub fn lock_methods_contains(&self, name: &syn::Ident) -> bool {
+        self.lock_methods.is_empty() || self.lock_methods.iter().any(|method| name == method)
+    }
+
+    /// Whether the `Send + Sync` markers are to be added to the trait objects (`Some(true)`) or
+    /// stripped from them (`Some(false)`), `"auto"` adds them in the async variants and strips them
+    /// in the sync ones.
+    pub fn dyn_markers_get(&self, convert_mode: ConvertMode) -> Option<bool> {
+        self.dyn_markers.as_deref().map(|policy| match policy {
+            "add" => true,
+            "strip" => false,
+            _ => convert_mode != ConvertMode::IntoSync,
+        })
+    }
+
+    /// Whether the calls to `yield_now` are to be replaced with `std::thread::yield_now` instead
+    /// of being removed.
+    pub fn yield_now_thread(&self) -> bool {
+        self.yield_now.as_deref() == Some("thread")
+    }
+
+    /// Returns the blocking helper the awaited timeouts are to be mapped to (`Some(None)` if they
+    /// are to be unwrapped to the bare call), or `None` if they are to be left as is.
+    pub fn timeout_get(&self) -> Option<Option<syn::Path>> {
+        self.timeout
+            .as_deref()
+            .map(|policy| match timeout_ok_error(policy) {
+                Some(_) => None,
+                None => syn::parse_str(policy).ok(),
+            })
+    }
+
+    /// Returns the error type of the `Ok` the unwrapped timeouts are wrapped in, if it is given
+    /// as `timeout="ok(Error)"`.
+    pub fn timeout_error_get(&self) -> Option<syn::Type> {
+        self.timeout
+            .as_deref()
+            .and_then(timeout_ok_error)
+            .and_then(|error| syn::parse_str(error?).ok())
+    }
+
+    /// Whether the channels are to be mapped.
+    pub fn channels_get(&self) -> bool {
+        self.channels.is_some()
+    }
+
+    /// Returns the mapping table of the paths: the one of `types`, the ones of the presets and the
+    /// one of the channels (the default one extended with the specified entries), the longest paths
+    /// first.
+    pub fn path_map_get(&self) -> Vec<(Vec<String>, syn::Path)> {
+        let mut table: Vec<(String, String)> = self.types.iter().rev().cloned().collect();
+        table.extend(
+            self.presets_get()
+                .flat_map(|preset| preset.paths)
+                .map(|(from, to)| (from.to_string(), to.to_string())),
+        );
+        if let Some(channels) = &self.channels {
+            table.extend(
+                DEFAULT_CHANNELS
+                    .iter()
+                    .filter(|(from, _)| !channels.iter().any(|(key, _)| key == from))
+                    .map(|(from, to)| (from.to_string(), to.to_string())),
+            );
+            table.extend(channels.iter().cloned());
+        }
+
+        path_table(table)
+    }
+
+    /// Returns the replacement of the called function, as listed in `calls`.
+    pub fn calls_get(&self, func: &syn::Expr) -> Option<syn::Expr> {
+        let path = match func {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None, path, ..
+            }) if !self.calls.is_empty() => path,
+            _ => return None,
+        };
+        let path = path.to_token_stream().to_string().replace(' ', "");
+        let path = path.trim_start_matches("::");
+        let (_, target) = self.calls.iter().rev().find(|(from, _)| from == path)?;
+        match syn::parse_str::<syn::Expr>(target).expect("checked in the builder") {
+            expr @ syn::Expr::Path(_) => Some(expr),
+            expr => Some(syn::parse_quote!((#expr))),
+        }
+    }
+
+    /// Returns the new name of the crate, as listed in `crates`.
+    pub fn crates_get(&self, name: &syn::Ident) -> Option<syn::Ident> {
+        self.crates
+            .iter()
+            .rev()
+            .find(|(krate, _)| name == krate)
+            .map(|(_, target)| Ident::new(target, name.span()))
+    }
+
+    /// Returns the mapping table of the paths of `types`, the longest paths first.
+    pub fn types_map_get(&self) -> Vec<(Vec<String>, syn::Path)> {
+        path_table(self.types.iter().rev().cloned().collect())
+    }
+
+    pub fn spawn_get(&self) -> Option<syn::Path> {
+        self.spawn
+            .as_ref()
+            .map(|path| syn::parse_str(path).expect("checked in the builder"))
+    }
+
+    pub fn suffix_get(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    pub fn receiver_get(&self) -> Option<&str> {
+        self.receiver.as_deref()
     }
 
-    pub fn recursive_asyncness_removal_get(&self) -> bool {
-        self.recursive_asyncness_removal
+    pub fn deprecate_get(&self) -> Option<&Deprecation> {
+        self.deprecate.as_ref()
+    }
+
+    pub fn blocking_ext_get(&self) -> Option<syn::Path> {
+        self.blocking_ext
+            .as_ref()
+            .map(|path| syn::parse_str(path).expect("checked in the builder"))
+    }
+
+    pub fn delegate_get(&self) -> Option<syn::Path> {
+        self.delegate
+            .as_ref()
+            .map(|path| syn::parse_str(path).expect("checked in the builder"))
+    }
+
+    /// Returns the wrapper type and the `block_on` function of the `bridge` parameter.
+    pub fn bridge_get(&self) -> Option<(syn::Path, Option<syn::Path>)> {
+        self.bridge.as_ref().map(|bridge| {
+            let parse = |path: &str| syn::parse_str(path).expect("checked in the builder");
+            (
+                parse(&bridge.wrapper),
+                bridge.block_on.as_deref().map(parse),
+            )
+        })
+    }
+
+    pub fn delegate_target_set(&mut self, name: String, target: String) {
+        self.delegate_targets.insert(name, target);
+    }
+
+    pub fn delegate_target_get<S: AsRef<str>>(&self, name: S) -> Option<&str> {
+        self.delegate_targets.get(name.as_ref()).map(|s| s.as_str())
+    }
+
+    /// Sets the condition of the other variant, which the impl blocks of the `bridge` parameter
+    /// require as well.
+    pub fn bridge_cfg_set(&mut self, cfg: Option<Meta>) {
+        self.bridge_cfg = cfg;
+    }
+
+    pub fn bridge_cfg_get(&self) -> Option<&Meta> {
+        self.bridge_cfg.as_ref()
+    }
+
+    pub fn dyn_from_set(&mut self, name: String, target: String) {
+        self.dyn_from.insert(name, target);
+    }
+
+    /// Names all identifiers except the given traits as in the async variant (in the `dyn`
+    /// variant), unless the names are specified explicitly.
+    pub fn dyn_idents_apply(&mut self, traits: &[String]) {
+        let key = match &self.key {
+            Some(key) => key.clone(),
+            None => return,
+        };
+
+        for (name, target) in &self.dyn_from {
+            if traits.contains(name) {
+                continue;
+            }
+            if let Some(ir) = self.idents.get_mut(name) {
+                ir.idents
+                    .get_or_insert_with(HashMap::new)
+                    .entry(key.clone())
+                    .or_insert_with(|| target.clone());
+            }
+        }
+    }
+
+    pub fn dyn_from_get<S: AsRef<str>>(&self, name: S) -> Option<&str> {
+        self.dyn_from.get(name.as_ref()).map(|s| s.as_str())
+    }
+
+    /// Passes the keys of all declared variants to every variant, so that the `only_if` and
+    /// `remove_if` keys can be checked during the conversion.
+    pub fn variant_keys_apply(&mut self) {
+        let keys = self
+            .versions
+            .iter()
+            .filter_map(|version| version.params.key_get().map(str::to_string))
+            .collect::<Vec<_>>();
+        for version in &mut self.versions {
+            version.params.variant_keys = keys.clone();
+        }
+    }
+
+    pub fn variant_keys_get(&self) -> &[String] {
+        &self.variant_keys
+    }
+
+    pub fn asyncness_removal_get(&self) -> AsyncnessRemoval {
+        self.asyncness_removal.unwrap_or_default()
+    }
+
+    /// Returns original and resulting names for all `idents` (and whether the resulting name was
+    /// specified explicitly), sorted by original name.
+    pub fn idents_renamed(&self, convert_mode: ConvertMode) -> Vec<(String, String, bool)> {
+        let mut renamed = self
+            .idents
+            .iter()
+            .map(|(name, ir)| {
+                let ident = Ident::new(name, Span::call_site());
+                let target = ir.ident_add_suffix(
+                    &ident,
+                    convert_mode,
+                    self.key_get(),
+                    self.primary,
+                    self.suffix_get(),
+                );
+                let explicit = ir.has_explicit_name(convert_mode, self.key_get());
+                (name.clone(), target.to_string(), explicit)
+            })
+            .collect::<Vec<_>>();
+        renamed.sort();
+        renamed
     }
 
     pub fn idents_get<'s, S: AsRef<str>>(&'s self, name: S) -> Option<&'s IdentRecord> {
         self.idents.get(name.as_ref())
     }
 
-    pub fn replace_features_is_empty(&self) -> bool {
-        self.replace_features.is_empty()
-    }
-    pub fn replace_features_get<'s, S: AsRef<str>>(&'s self, name: S) -> Option<&'s str> {
-        self.replace_features.get(name.as_ref()).map(|s| s.as_str())
-    }
+    pub fn replace_features_is_empty(&self) -> bool {
+        self.replace_features.is_empty()
+    }
+    pub fn replace_features_get<'s, S: AsRef<str>>(&'s self, name: S) -> Option<&'s str> {
+        self.replace_features.get(name.as_ref()).map(|s| s.as_str())
+    }
+
+    pub fn drop_attrs_is_empty(&self) -> bool {
+        self.drop_attrs.is_empty()
+    }
+    pub fn drop_attrs_contains(&self, name: &String) -> bool {
+        self.drop_attrs.contains(name)
+    }
+
+    /// Returns the policy of the attribute: `"async"` or `"sync"` if it is to be kept only in the
+    /// variants of this kind, `"keep"` or `None` if it is to be kept everywhere. The policies given
+    /// by `attr_policy` take precedence over the known async attributes (`async_recursion`,
+    /// `async_backtrace::framed`, `async_trait` and `must_use` with a message about futures). An
+    /// attribute is matched by its full path or by its last segment.
+    pub fn attr_policy_get(&self, attr: &syn::Attribute) -> Option<&str> {
+        let path = attr.path.to_token_stream().to_string().replace(' ', "");
+        let path = path.trim_start_matches("::");
+        let last = attr.path.segments.last().map(|s| s.ident.to_string());
+        let matches = |name: &str| name == path || matches!(&last, Some(last) if name == last);
+
+        if let Some((_, policy)) = self
+            .attr_policies
+            .iter()
+            .rev()
+            .find(|(name, _)| matches(name))
+        {
+            return Some(policy);
+        }
+        if ASYNC_ATTRS.iter().any(|name| matches(name)) || matches(self.async_trait_path_get()) {
+            return Some("async");
+        }
+        if path == "must_use" {
+            if let Ok(Meta::NameValue(syn::MetaNameValue {
+                lit: Lit::Str(lit), ..
+            })) = attr.parse_meta()
+            {
+                if lit.value().contains("future") {
+                    return Some("async");
+                }
+            }
+        }
+        None
+    }
+
+    pub fn add_await_is_empty(&self) -> bool {
+        self.add_await.is_empty()
+    }
+
+    pub fn add_await_fns_contains(&self, name: &syn::Ident) -> bool {
+        self.add_await.fns.iter().any(|fn_name| name == fn_name)
+    }
+
+    pub fn add_await_methods_contains(&self, name: &syn::Ident) -> bool {
+        self.add_await.methods.iter().any(|method| name == method)
+    }
+
+    pub fn strip_methods_contains(&self, name: &syn::Ident) -> bool {
+        self.strip_methods.iter().any(|method| name == method)
+    }
+
+    /// Returns the new name of the called method, as listed in `methods`.
+    pub fn methods_get(&self, name: &syn::Ident) -> Option<syn::Ident> {
+        self.methods
+            .iter()
+            .rev()
+            .find(|(method, _)| name == method)
+            .map(|(_, target)| Ident::new(target, name.span()))
+    }
+
+    /// Returns the method to be called instead of awaiting the result of the method `name`, as
+    /// listed in `into_future_map`.
+    pub fn map_into_future_get(&self, name: &syn::Ident) -> Option<syn::Ident> {
+        self.into_future_map
+            .iter()
+            .rev()
+            .find(|(method, _)| name == method)
+            .map(|(_, target)| Ident::new(target, name.span()))
+    }
+
+    pub fn generics_is_empty(&self) -> bool {
+        self.add_generics.is_empty()
+            && self.drop_generics.is_empty()
+            && self.add_bounds.is_empty()
+            && self.drop_bounds.is_empty()
+    }
+
+    /// Removes the bounds listed in `drop_bounds`. A trait bound is matched either by its full path
+    /// or by its last segment.
+    pub fn bounds_drop(&self, bounds: &mut Punctuated<syn::TypeParamBound, syn::token::Add>) {
+        if self.drop_bounds.is_empty() {
+            return;
+        }
+
+        *bounds = std::mem::take(bounds)
+            .into_iter()
+            .filter(|bound| !bound_matches(bound, &self.drop_bounds))
+            .collect();
+    }
+
+    /// Removes the bounds listed in `relax_bounds` from the generic parameters and the `where`
+    /// clause. The `where` predicates left without bounds are removed.
+    pub fn bounds_relax(&self, generics: &mut syn::Generics) {
+        if self.relax_bounds.is_empty() {
+            return;
+        }
+
+        let relax = |bounds: &mut Punctuated<syn::TypeParamBound, syn::token::Add>| {
+            *bounds = std::mem::take(bounds)
+                .into_iter()
+                .filter(|bound| !bound_matches(bound, &self.relax_bounds))
+                .collect();
+        };
+        let relax_lifetimes = |bounds: &mut Punctuated<syn::Lifetime, syn::token::Add>| {
+            *bounds = std::mem::take(bounds)
+                .into_iter()
+                .filter(|lt| !self.relax_bounds.contains(&lt.to_string()))
+                .collect();
+        };
+
+        for param in generics.params.iter_mut() {
+            match param {
+                syn::GenericParam::Type(ty) => {
+                    relax(&mut ty.bounds);
+                    if ty.bounds.is_empty() {
+                        ty.colon_token = None;
+                    }
+                }
+                syn::GenericParam::Lifetime(lt) => {
+                    relax_lifetimes(&mut lt.bounds);
+                    if lt.bounds.is_empty() {
+                        lt.colon_token = None;
+                    }
+                }
+                syn::GenericParam::Const(_) => {}
+            }
+        }
+
+        if let Some(where_clause) = &mut generics.where_clause {
+            where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+                .into_iter()
+                .filter_map(|mut pred| {
+                    let empty = match &mut pred {
+                        syn::WherePredicate::Type(pt) => {
+                            relax(&mut pt.bounds);
+                            pt.bounds.is_empty()
+                        }
+                        syn::WherePredicate::Lifetime(pl) => {
+                            relax_lifetimes(&mut pl.bounds);
+                            pl.bounds.is_empty()
+                        }
+                        syn::WherePredicate::Eq(_) => false,
+                    };
+                    (!empty).then_some(pred)
+                })
+                .collect();
+            if where_clause.predicates.is_empty() {
+                generics.where_clause = None;
+            }
+        }
+    }
+
+    fn lifetime_bounds_drop(&self, bounds: &mut Punctuated<syn::Lifetime, syn::token::Add>) {
+        *bounds = std::mem::take(bounds)
+            .into_iter()
+            .filter(|lt| !self.drop_bounds.contains(&lt.to_string()))
+            .collect();
+    }
+
+    /// Removes the generic parameters listed in `drop_generics` (with their `where` predicates) and
+    /// the bounds listed in `drop_bounds`, then adds the parameters listed in `add_generics` and
+    /// the `where` predicates listed in `add_bounds`.
+    pub fn generics_apply(&self, generics: &mut syn::Generics) {
+        let dropped = |param: &syn::GenericParam| {
+            let name = match param {
+                syn::GenericParam::Type(ty) => ty.ident.to_string(),
+                syn::GenericParam::Lifetime(lt) => lt.lifetime.to_string(),
+                syn::GenericParam::Const(c) => c.ident.to_string(),
+            };
+            self.drop_generics.contains(&name)
+        };
+        generics.params = std::mem::take(&mut generics.params)
+            .into_iter()
+            .filter(|param| !dropped(param))
+            .collect();
+
+        if let Some(where_clause) = &mut generics.where_clause {
+            where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+                .into_iter()
+                .filter(|pred| {
+                    let name = match pred {
+                        syn::WherePredicate::Type(pt) => {
+                            pt.bounded_ty.to_token_stream().to_string()
+                        }
+                        syn::WherePredicate::Lifetime(pl) => pl.lifetime.to_string(),
+                        syn::WherePredicate::Eq(pe) => pe.lhs_ty.to_token_stream().to_string(),
+                    };
+                    !self.drop_generics.contains(&name)
+                })
+                .collect();
+        }
+
+        for param in generics.params.iter_mut() {
+            match param {
+                syn::GenericParam::Type(ty) => {
+                    self.bounds_drop(&mut ty.bounds);
+                    if ty.bounds.is_empty() {
+                        ty.colon_token = None;
+                    }
+                }
+                syn::GenericParam::Lifetime(lt) => {
+                    self.lifetime_bounds_drop(&mut lt.bounds);
+                    if lt.bounds.is_empty() {
+                        lt.colon_token = None;
+                    }
+                }
+                syn::GenericParam::Const(_) => {}
+            }
+        }
+
+        if let Some(where_clause) = &mut generics.where_clause {
+            where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+                .into_iter()
+                .filter_map(|mut pred| {
+                    let empty = match &mut pred {
+                        syn::WherePredicate::Type(pt) => {
+                            self.bounds_drop(&mut pt.bounds);
+                            pt.bounds.is_empty()
+                        }
+                        syn::WherePredicate::Lifetime(pl) => {
+                            self.lifetime_bounds_drop(&mut pl.bounds);
+                            pl.bounds.is_empty()
+                        }
+                        syn::WherePredicate::Eq(_) => false,
+                    };
+                    (!empty).then_some(pred)
+                })
+                .collect();
+            if where_clause.predicates.is_empty() {
+                generics.where_clause = None;
+            }
+        }
 
-    pub fn drop_attrs_is_empty(&self) -> bool {
-        self.drop_attrs.is_empty()
-    }
-    pub fn drop_attrs_contains(&self, name: &String) -> bool {
-        self.drop_attrs.contains(name)
+        for param in &self.add_generics {
+            let param: syn::GenericParam = syn::parse_str(param).expect("checked in the builder");
+            // lifetimes have to precede the other parameters
+            match param {
+                syn::GenericParam::Lifetime(_) => {
+                    let index = generics.lifetimes().count();
+                    generics.params.insert(index, param);
+                }
+                _ => generics.params.push(param),
+            }
+        }
+
+        for pred in &self.add_bounds {
+            let pred: syn::WherePredicate = syn::parse_str(pred).expect("checked in the builder");
+            generics.make_where_clause().predicates.push(pred);
+        }
     }
 
     pub fn is_our_attr(&self, attr: &Attribute) -> Option<String> {
@@ -664,6 +2715,152 @@ impl MacroParameters {
     pub fn standard_macros<'s>(&'s self) -> &'s [&'s str] {
         STANDARD_MACROS
     }
+
+    /// Whether the macro is listed in `process_macros`, by its full path or by its last segment.
+    pub fn process_macros_contains(&self, path: &syn::Path) -> bool {
+        macro_listed(path, &self.process_macros)
+    }
+
+    /// Whether the body of the macro is to be left as is, because of `no_macro_processing`.
+    pub fn macro_processing_disabled(&self, path: &syn::Path) -> bool {
+        match &self.no_macro_processing {
+            Some(names) => names.is_empty() || macro_listed(path, names),
+            None => false,
+        }
+    }
+}
+
+/// Matches a bound against the listed names: a trait bound by its full path or by its last
+/// segment, a lifetime by its name.
+fn bound_matches(bound: &syn::TypeParamBound, names: &[String]) -> bool {
+    match bound {
+        syn::TypeParamBound::Trait(tb) => {
+            let path = tb.path.to_token_stream().to_string().replace(' ', "");
+            let last = tb.path.segments.last().map(|s| s.ident.to_string());
+            names.contains(&path) || matches!(last, Some(last) if names.contains(&last))
+        }
+        syn::TypeParamBound::Lifetime(lt) => names.contains(&lt.to_string()),
+    }
+}
+
+/// Splits the `ok` policy of `timeout`: `Some(None)` for `"ok"`, `Some(Some(error))` for
+/// `"ok(error)"`, `None` for anything else.
+fn timeout_ok_error(policy: &str) -> Option<Option<&str>> {
+    let rest = policy.trim().strip_prefix(TIMEOUT_OK)?.trim();
+    if rest.is_empty() {
+        return Some(None);
+    }
+    let error = rest.strip_prefix('(')?.strip_suffix(')')?.trim();
+    Some(Some(error))
+}
+
+/// Matches a macro against the listed names by its full path or by its last segment.
+fn macro_listed(path: &syn::Path, names: &[String]) -> bool {
+    if names.is_empty() {
+        return false;
+    }
+    let full = path.to_token_stream().to_string().replace(' ', "");
+    let full = full.trim_start_matches("::");
+    let last = path.segments.last().map(|s| s.ident.to_string());
+    names
+        .iter()
+        .any(|name| name == full || matches!(&last, Some(last) if name == last))
+}
+
+fn macro_names(list: &Punctuated<NestedMeta, Comma>) -> syn::Result<Vec<String>> {
+    list.iter()
+        .map(|nm| {
+            let path = match nm {
+                NestedMeta::Meta(Meta::Path(path)) => path.clone(),
+                NestedMeta::Lit(Lit::Str(lit)) => lit.parse::<syn::Path>()?,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected list of macro paths",
+                    ))
+                }
+            };
+            let name = path.to_token_stream().to_string().replace(' ', "");
+            Ok(name.trim_start_matches("::").to_string())
+        })
+        .collect()
+}
+
+/// Parses the `name = "name"` entries, `what` is the kind of the names for the error messages.
+fn ident_pairs(
+    list: &Punctuated<NestedMeta, Comma>,
+    what: &str,
+) -> syn::Result<Vec<(String, String)>> {
+    list.iter()
+        .map(|nm| match nm {
+            NestedMeta::Meta(Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: Lit::Str(lit),
+                ..
+            })) if path.get_ident().is_some() => {
+                syn::parse_str::<Ident>(&lit.value())
+                    .map_err(|_| syn::Error::new_spanned(lit, format!("Expected {} name", what)))?;
+                let name = path.get_ident().unwrap().to_string();
+                Ok((name, lit.value()))
+            }
+            _ => Err(syn::Error::new_spanned(
+                nm.to_token_stream(),
+                format!("Expected `{0} = \"{0}\"`", what),
+            )),
+        })
+        .collect()
+}
+
+/// Builds the mapping table of the paths, the longest paths first (the earlier entries first among
+/// the paths of the same length).
+fn path_table(table: Vec<(String, String)>) -> Vec<(Vec<String>, syn::Path)> {
+    let mut table: Vec<(Vec<String>, syn::Path)> = table
+        .into_iter()
+        .map(|(from, to)| {
+            let from = from.split("::").map(|s| s.to_string()).collect();
+            (from, syn::parse_str(&to).unwrap())
+        })
+        .collect();
+    table.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+    table
+}
+
+fn path_pairs(list: &Punctuated<NestedMeta, Comma>) -> syn::Result<Vec<(String, String)>> {
+    list.iter()
+        .map(|item| match item {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: syn::Lit::Str(lit),
+                ..
+            })) if path.segments.iter().all(|s| s.arguments.is_empty()) => {
+                syn::parse_str::<syn::Path>(&lit.value())
+                    .map_err(|_| syn::Error::new_spanned(lit, "Expected path"))?;
+                let from = path
+                    .segments
+                    .iter()
+                    .map(|s| s.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                Ok((from, lit.value()))
+            }
+            _ => Err(syn::Error::new_spanned(
+                item.to_token_stream(),
+                "Expected `path = \"path\"`",
+            )),
+        })
+        .collect()
+}
+
+fn bound_names(list: &Punctuated<NestedMeta, Comma>) -> syn::Result<Vec<String>> {
+    list.iter()
+        .map(|nm| match nm {
+            NestedMeta::Meta(Meta::Path(path)) => {
+                Ok(path.to_token_stream().to_string().replace(' ', ""))
+            }
+            NestedMeta::Lit(Lit::Str(lit)) => Ok(lit.value().replace(' ', "")),
+            _ => Err(syn::Error::new_spanned(nm, "Expected bound")),
+        })
+        .collect()
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -681,17 +2878,77 @@ impl MacroParametersBuilder {
                 disable: false,
                 key: None,
                 self_name: None,
+                self_path: None,
                 prefix: None,
                 idents: HashMap::new(),
                 keep_self: false,
+                primary: false,
+                doc_hidden: false,
+                test_only: None,
+                dedup: false,
+                dispatch: None,
+                alias: false,
+                doc_cfg: false,
+                register: None,
+                use_registered: None,
                 send: None,
-                recursive_asyncness_removal: true,
+                asyncness_removal: None,
+                stream_macro: None,
+                dyn_compat: None,
+                trait_variant: None,
+                async_trait_path: None,
+                spawn: None,
+                locks: None,
+                lock_methods: vec![],
+                yield_now: None,
+                dyn_markers: None,
+                timeout: None,
+                channels: None,
+                types: vec![],
+                crates: vec![],
+                calls: vec![],
+                receiver: None,
+                presets: vec![],
+                test_attr: None,
+                main_attr: None,
+                suffix: None,
+                blocking_ext: None,
+                delegate: None,
+                bridge: None,
+                deprecate: None,
+                dyn_from: HashMap::new(),
+                delegate_targets: HashMap::new(),
+                bridge_cfg: None,
+                variant_keys: vec![],
                 cfg: None,
+                cond: None,
+                conditions: HashMap::new(),
+                unconditional: false,
+                merge_cfgs: false,
+                constness: false,
+                process_all_macros: false,
+                impl_future: false,
+                send_bounds: false,
+                item_cfgs: vec![],
                 outer_attrs: Punctuated::new(),
                 inner_attrs: Punctuated::new(),
                 drop_attrs: vec![],
+                attr_policies: vec![],
+                strip_methods: vec![],
+                into_future_map: vec![],
+                methods: vec![],
+                process_macros: vec![],
+                no_macro_processing: None,
+                add_await: AddAwait::default(),
+                add_generics: vec![],
+                drop_generics: vec![],
+                add_bounds: vec![],
+                drop_bounds: vec![],
+                relax_bounds: vec![],
                 replace_features: HashMap::new(),
                 versions: vec![],
+                default_variant: None,
+                only: None,
             },
         }
     }
@@ -706,6 +2963,11 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    pub fn mode_into_dyn(&mut self) -> syn::Result<()> {
+        self.params.mode = Some(ConvertMode::IntoDyn);
+        Ok(())
+    }
+
     pub fn key(&mut self, key: String) -> syn::Result<()> {
         self.params.key = Some(key);
         Ok(())
@@ -716,6 +2978,19 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    pub fn self_path(&mut self, self_path: String) -> syn::Result<()> {
+        let path = syn::parse_str::<syn::Path>(&self_path)?;
+        if path
+            .segments
+            .iter()
+            .any(|segment| !segment.arguments.is_empty())
+        {
+            return Err(syn::Error::new_spanned(path, "Expected path to a module"));
+        }
+        self.params.self_path = Some(self_path);
+        Ok(())
+    }
+
     pub fn disable(&mut self) {
         self.params.disable = true;
     }
@@ -724,6 +2999,414 @@ impl MacroParametersBuilder {
         self.params.keep_self = true;
     }
 
+    pub fn primary(&mut self) {
+        self.params.primary = true;
+    }
+
+    pub fn doc_hidden(&mut self) {
+        self.params.doc_hidden = true;
+    }
+
+    pub fn stream_macro(&mut self, path: String) -> syn::Result<()> {
+        self.params.stream_macro = Some(path);
+        Ok(())
+    }
+
+    pub fn dyn_compat(&mut self, path: String) -> syn::Result<()> {
+        if path != DYN_COMPAT_DYNOSAUR && syn::parse_str::<syn::Path>(&path).is_err() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "Expected `\"{}\"` or the path of an attribute in the `dyn_compat` parameter, \
+                    found `{}`",
+                    DYN_COMPAT_DYNOSAUR, path,
+                ),
+            ));
+        }
+        self.params.dyn_compat = Some(path);
+        Ok(())
+    }
+
+    pub fn channels(&mut self, list: Option<&Punctuated<NestedMeta, Comma>>) -> syn::Result<()> {
+        let channels = self.params.channels.get_or_insert_with(Vec::new);
+        if let Some(list) = list {
+            for (from, to) in path_pairs(list)? {
+                channels.retain(|(key, _)| key != &from);
+                channels.push((from, to));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn types(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params.types.extend(path_pairs(list)?);
+        Ok(())
+    }
+
+    pub fn locks(&mut self, policy: String) -> syn::Result<()> {
+        if !LOCK_POLICIES.contains(&policy.as_str()) {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("Expected one of: {}", LOCK_POLICIES.join(", ")),
+            ));
+        }
+        self.params.locks = Some(policy);
+        Ok(())
+    }
+
+    pub fn lock_methods(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in meta {
+            match nm {
+                NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                    let name = path.get_ident().unwrap().to_string();
+                    self.params.lock_methods.push(name);
+                }
+                NestedMeta::Lit(Lit::Str(lit)) if syn::parse_str::<Ident>(&lit.value()).is_ok() => {
+                    self.params.lock_methods.push(lit.value());
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected list of method names",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn yield_now(&mut self, policy: String) -> syn::Result<()> {
+        if !YIELD_POLICIES.contains(&policy.as_str()) {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("Expected one of: {}", YIELD_POLICIES.join(", ")),
+            ));
+        }
+        self.params.yield_now = Some(policy);
+        Ok(())
+    }
+
+    pub fn dyn_markers(&mut self, policy: String) -> syn::Result<()> {
+        if !DYN_MARKERS_POLICIES.contains(&policy.as_str()) {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("Expected one of: {}", DYN_MARKERS_POLICIES.join(", ")),
+            ));
+        }
+        self.params.dyn_markers = Some(policy);
+        Ok(())
+    }
+
+    pub fn timeout(&mut self, policy: String) -> syn::Result<()> {
+        let valid = match timeout_ok_error(&policy) {
+            Some(None) => true,
+            Some(Some(error)) => syn::parse_str::<syn::Type>(error).is_ok(),
+            None => syn::parse_str::<syn::Path>(&policy).is_ok(),
+        };
+        if !valid {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "Expected \"{0}\", \"{0}(ErrorType)\" or the path of a blocking helper",
+                    TIMEOUT_OK
+                ),
+            ));
+        }
+        self.params.timeout = Some(policy);
+        Ok(())
+    }
+
+    pub fn spawn(&mut self, path: String) -> syn::Result<()> {
+        syn::parse_str::<syn::Path>(&path)?;
+        self.params.spawn = Some(path);
+        Ok(())
+    }
+
+    pub fn preset(&mut self, preset: String) -> syn::Result<()> {
+        if Preset::find(&preset).is_none() {
+            let names = PRESETS.iter().map(|preset| preset.name).collect::<Vec<_>>();
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("Unknown preset, expected one of: {}", names.join(", ")),
+            ));
+        }
+        self.params.presets.push(preset);
+        Ok(())
+    }
+
+    pub fn test_attr(&mut self, attr: String) -> syn::Result<()> {
+        make_attr_from_str(&attr, Span::call_site())?;
+        self.params.test_attr = Some(attr);
+        Ok(())
+    }
+
+    pub fn main_attr(&mut self, attr: String) -> syn::Result<()> {
+        make_attr_from_str(&attr, Span::call_site())?;
+        self.params.main_attr = Some(attr);
+        Ok(())
+    }
+
+    pub fn suffix(&mut self, suffix: String) -> syn::Result<()> {
+        self.params.suffix = Some(suffix);
+        Ok(())
+    }
+
+    pub fn default_variant(&mut self, key: String) -> syn::Result<()> {
+        self.params.default_variant = Some(key);
+        Ok(())
+    }
+
+    pub fn blocking_ext(&mut self, block_on: String) -> syn::Result<()> {
+        syn::parse_str::<syn::Path>(&block_on)?;
+        self.params.blocking_ext = Some(block_on);
+        Ok(())
+    }
+
+    pub fn delegate(&mut self, block_on: String) -> syn::Result<()> {
+        syn::parse_str::<syn::Path>(&block_on)?;
+        self.params.delegate = Some(block_on);
+        Ok(())
+    }
+
+    pub fn bridge(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        let mut wrapper = None;
+        let mut block_on = None;
+        for item in list {
+            match item {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) if path.is_ident("wrapper") || path.is_ident("block_on") => {
+                    syn::parse_str::<syn::Path>(&lit.value())
+                        .map_err(|err| syn::Error::new_spanned(lit, err))?;
+                    if path.is_ident("wrapper") {
+                        wrapper = Some(lit.value());
+                    } else {
+                        block_on = Some(lit.value());
+                    }
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        item.to_token_stream(),
+                        "Expected `wrapper = \"Wrapper\"` or `block_on = \"path\"`",
+                    ))
+                }
+            }
+        }
+        let wrapper = wrapper.ok_or_else(|| {
+            syn::Error::new_spanned(list, "The `bridge` parameter requires a `wrapper` type")
+        })?;
+        self.params.bridge = Some(Bridge { wrapper, block_on });
+        Ok(())
+    }
+
+    pub fn receiver(&mut self, receiver: String) -> syn::Result<()> {
+        parse_receiver(&receiver, Span::call_site())?;
+        self.params.receiver = Some(receiver);
+        Ok(())
+    }
+
+    pub fn dyn_from(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for item in list {
+            match item {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) if path.get_ident().is_some() => {
+                    let name = path.get_ident().unwrap().to_string();
+                    self.params.dyn_from.insert(name, lit.value());
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        item.to_token_stream(),
+                        "Expected `Name = \"Target\"`",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn delegate_targets(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for item in list {
+            match item {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) if path.get_ident().is_some() => {
+                    let name = path.get_ident().unwrap().to_string();
+                    self.params.delegate_targets.insert(name, lit.value());
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        item.to_token_stream(),
+                        "Expected `Name = \"Target\"`",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn bridge_cfg(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        match list.first() {
+            Some(NestedMeta::Meta(cfg)) if list.len() == 1 => {
+                self.params.bridge_cfg = Some(cfg.clone());
+                Ok(())
+            }
+            _ => Err(syn::Error::new_spanned(list, "Expected a single condition")),
+        }
+    }
+
+    pub fn test_only(&mut self) {
+        self.params.test_only = Some(None);
+    }
+
+    pub fn test_only_feature(&mut self, feature: String) -> syn::Result<()> {
+        self.params.test_only = Some(Some(feature));
+        Ok(())
+    }
+
+    pub fn alias(&mut self) {
+        self.params.alias = true;
+    }
+
+    pub fn no_cfg(&mut self) {
+        self.params.unconditional = true;
+    }
+
+    pub fn doc_cfg(&mut self) {
+        self.params.doc_cfg = true;
+    }
+
+    pub fn merge_cfgs(&mut self) {
+        self.params.merge_cfgs = true;
+    }
+
+    pub fn constness(&mut self, constness: bool) {
+        self.params.constness = constness;
+    }
+
+    pub fn process_all_macros(&mut self) {
+        self.params.process_all_macros = true;
+    }
+
+    pub fn impl_future(&mut self) {
+        self.params.impl_future = true;
+    }
+
+    pub fn send_bounds(&mut self) {
+        self.params.send_bounds = true;
+    }
+
+    pub fn dedup(&mut self) {
+        self.params.dedup = true;
+    }
+
+    pub fn dispatch(&mut self) {
+        self.params.dispatch = Some(None);
+    }
+
+    pub fn dispatch_name(&mut self, name: String) -> syn::Result<()> {
+        syn::parse_str::<Ident>(&name)?;
+        self.params.dispatch = Some(Some(name));
+        Ok(())
+    }
+
+    pub fn register(&mut self, name: String) -> syn::Result<()> {
+        self.params.register = Some(name);
+        Ok(())
+    }
+
+    pub fn use_registered(&mut self, list: &MetaList) -> syn::Result<()> {
+        match list.nested.iter().collect::<Vec<_>>().as_slice() {
+            [NestedMeta::Lit(Lit::Str(name))] => {
+                self.params.use_registered = Some(name.value());
+                Ok(())
+            }
+            _ => Err(syn::Error::new_spanned(
+                list.to_token_stream(),
+                "Expected name in quotes",
+            )),
+        }
+    }
+
+    pub fn deprecate(&mut self, list: &MetaList) -> syn::Result<()> {
+        let mut deprecate = Deprecation::default();
+        for nm in &list.nested {
+            match nm {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("note") => deprecate.note = Some(lit.value()),
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("since") => deprecate.since = Some(lit.value()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm,
+                        "Expected `note = \"...\"` or `since = \"...\"`",
+                    ))
+                }
+            }
+        }
+        self.params.deprecate = Some(deprecate);
+        Ok(())
+    }
+
+    pub fn variant_keys(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in list {
+            match nm {
+                NestedMeta::Lit(Lit::Str(key)) => self.params.variant_keys.push(key.value()),
+                _ => return Err(syn::Error::new_spanned(nm, "Expected variant key")),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn only(&mut self, list: &MetaList) -> syn::Result<()> {
+        let mut only = vec![];
+        for nm in &list.nested {
+            let key = match nm {
+                NestedMeta::Lit(Lit::Str(key)) => Some(key.value()),
+                NestedMeta::Meta(Meta::Path(path)) => path.get_ident().map(Ident::to_string),
+                _ => None,
+            };
+            only.push(key.ok_or_else(|| syn::Error::new_spanned(nm, "Expected variant key"))?);
+        }
+        self.params.only = Some(only);
+        Ok(())
+    }
+
+    pub fn asyncness_removal(&mut self, list: &MetaList) -> syn::Result<()> {
+        let mut asyncness_removal = AsyncnessRemoval::none();
+        for nm in &list.nested {
+            let flag = match nm {
+                NestedMeta::Meta(Meta::Path(path)) => path
+                    .get_ident()
+                    .and_then(|ident| asyncness_removal.flag_mut(&ident.to_string())),
+                _ => None,
+            };
+            match flag {
+                Some(flag) => *flag = true,
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        format!("Expected one of: {}", AsyncnessRemoval::NAMES.join(", ")),
+                    ))
+                }
+            }
+        }
+        self.params.asyncness_removal = Some(asyncness_removal);
+        Ok(())
+    }
+
     pub fn prefix(&mut self, prefix: String) -> syn::Result<()> {
         self.params.prefix = Some(prefix);
         Ok(())
@@ -839,13 +3522,22 @@ impl MacroParametersBuilder {
     }
 
     pub fn send(&mut self, send: String) -> syn::Result<()> {
+        if send != SEND_TRAIT_VARIANT {
+            self.params.trait_variant = None;
+        }
         self.params.send = Some(match send.as_str() {
             "" | "Send" | "true" => true,
             "?Send" | "false" => false,
+            SEND_TRAIT_VARIANT => {
+                if self.params.trait_variant.is_none() {
+                    self.params.trait_variant = Some(DEFAULT_TRAIT_VARIANT.to_string());
+                }
+                true
+            }
             _ => {
                 return Err(syn::Error::new(
                     Span::call_site(),
-                    "Only accepts `Send` or `?Send`",
+                    "Only accepts `Send`, `?Send` or `trait_variant`",
                 ));
             }
         });
@@ -853,6 +3545,37 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    pub fn async_trait_path(&mut self, path: String) -> syn::Result<()> {
+        if syn::parse_str::<syn::Path>(&path).is_err() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "Expected the path of an attribute in the `async_trait_path` parameter, found \
+                    `{}`",
+                    path,
+                ),
+            ));
+        }
+        self.params.async_trait_path = Some(path);
+        Ok(())
+    }
+
+    /// Sets the path of the `trait_variant::make` attribute, implies `send = "trait_variant"`.
+    pub fn trait_variant(&mut self, path: String) -> syn::Result<()> {
+        if syn::parse_str::<syn::Path>(&path).is_err() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "Expected the path of an attribute in the `trait_variant` parameter, found `{}`",
+                    path,
+                ),
+            ));
+        }
+        self.params.trait_variant = Some(path);
+        self.params.send = Some(true);
+        Ok(())
+    }
+
     pub fn feature(&mut self, meta: &Meta) -> syn::Result<()> {
         self.cfg_meta(meta)
     }
@@ -888,6 +3611,48 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    pub fn cond(&mut self, cond: String) -> syn::Result<()> {
+        self.params.cond = Some(cond);
+        Ok(())
+    }
+
+    pub fn cond_list(&mut self, list: &MetaList) -> syn::Result<()> {
+        let mut iter = list.nested.iter();
+        match (iter.next(), iter.next()) {
+            (Some(NestedMeta::Meta(Meta::Path(path))), None) if path.get_ident().is_some() => {
+                self.cond(path.get_ident().unwrap().to_string())
+            }
+            _ => Err(syn::Error::new_spanned(
+                list.to_token_stream(),
+                "Expected condition name",
+            )),
+        }
+    }
+
+    pub fn conditions(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for item in list {
+            match item {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit),
+                    ..
+                })) if path.get_ident().is_some() => {
+                    syn::parse_str::<Meta>(&lit.value())
+                        .map_err(|_| syn::Error::new_spanned(lit, "Expected `cfg` predicate"))?;
+                    let name = path.get_ident().unwrap().to_string();
+                    self.params.conditions.insert(name, lit.value());
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        item.to_token_stream(),
+                        "Expected `name = \"predicate\"`",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn cfg_meta(&mut self, meta: &Meta) -> syn::Result<()> {
         self.params.cfg = Some(meta.clone());
         Ok(())
@@ -926,6 +3691,57 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    /// Splits the `mode` parameter off the parameters of a variant.
+    fn take_mode(
+        list: &Punctuated<NestedMeta, Comma>,
+    ) -> syn::Result<(Option<ConvertMode>, Punctuated<NestedMeta, Comma>)> {
+        let mut kind = None;
+        let mut args = Punctuated::<NestedMeta, Comma>::new();
+        for nm in list {
+            match nm {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(mode),
+                    ..
+                })) if path.is_ident("mode") => {
+                    kind = Some(ConvertMode::from_str(mode.value()).ok_or_else(|| {
+                        syn::Error::new_spanned(mode, "Expected `sync`, `async` or `dyn`")
+                    })?);
+                }
+                _ => args.push(nm.clone()),
+            }
+        }
+        Ok((kind, args))
+    }
+
+    /// Adds a variant with the conversion mode specified by the `mode` parameter.
+    pub fn variant(&mut self, list: &MetaList) -> syn::Result<()> {
+        let (kind, args) = Self::take_mode(&list.nested)?;
+
+        // the mode and the key default to the ones of the preset
+        let mut inner = MacroParameters::from_args(&args)?;
+        let preset = inner.preset_get();
+        let kind = kind.or(preset.map(|preset| preset.mode)).ok_or_else(|| {
+            syn::Error::new_spanned(list.to_token_stream(), "Expected `mode` parameter")
+        })?;
+        Self::check_preset_mode(&inner, kind, &list.nested)?;
+        if inner.key.is_none() {
+            inner.key = preset.map(|preset| preset.name.to_string());
+        }
+        let key = inner.key.as_deref().ok_or_else(|| {
+            syn::Error::new_spanned(list.to_token_stream(), "Expected `key` parameter")
+        })?;
+        if inner.suffix.is_none() {
+            inner.suffix = Some(to_camel_case(key));
+        }
+
+        self.params.versions.push(MacroParameterVersion {
+            kind,
+            params: inner,
+        });
+        Ok(())
+    }
+
     pub fn inner_attrs(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
         if self.params.inner_attrs.is_empty() {
             self.params.inner_attrs = list.clone();
@@ -935,12 +3751,139 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    pub fn exclusive(&mut self, list: &MetaList) -> syn::Result<()> {
+        let mut feature = None;
+        let mut args = Punctuated::<NestedMeta, Comma>::new();
+        for nm in &list.nested {
+            match nm {
+                NestedMeta::Lit(Lit::Str(lit)) => feature = Some(lit.clone()),
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) if path.is_ident("feature") => feature = Some(lit.clone()),
+                _ => args.push(nm.clone()),
+            }
+        }
+        let feature = feature.ok_or_else(|| {
+            syn::Error::new_spanned(list.to_token_stream(), "Expected `feature` parameter")
+        })?;
+
+        // the sync variant is enabled by the feature, the async one otherwise
+        let mut sync_args = args.clone();
+        sync_args.push(parse_quote!(cfg(feature = #feature)));
+        self.version(ConvertMode::IntoSync, &sync_args)?;
+        let mut async_args = args;
+        async_args.push(parse_quote!(cfg(not(feature = #feature))));
+        self.version(ConvertMode::IntoAsync, &async_args)
+    }
+
+    pub fn matrix(&mut self, list: &MetaList) -> syn::Result<()> {
+        let mut axes = vec![];
+        for nm in &list.nested {
+            match nm {
+                NestedMeta::Meta(Meta::List(axis)) if axis.path.is_ident("axis") => {
+                    if axis.nested.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            axis.to_token_stream(),
+                            "Expected at least one entry",
+                        ));
+                    }
+                    axes.push(
+                        axis.nested
+                            .iter()
+                            .map(MatrixEntry::from_nestedmeta)
+                            .collect::<syn::Result<Vec<_>>>()?,
+                    );
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected `axis(...)`",
+                    ))
+                }
+            }
+        }
+        if axes.is_empty() {
+            return Err(syn::Error::new_spanned(
+                list.to_token_stream(),
+                "Expected at least one `axis(...)`",
+            ));
+        }
+
+        // cartesian product of the axes, the first axis varies slowest
+        let mut combinations: Vec<Vec<&MatrixEntry>> = vec![vec![]];
+        for axis in &axes {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    axis.iter().map(move |entry| {
+                        let mut combination = combination.clone();
+                        combination.push(entry);
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        for combination in combinations {
+            let modes = combination
+                .iter()
+                .filter(|entry| entry.kind.is_some())
+                .count();
+            if modes > 1 {
+                return Err(syn::Error::new_spanned(
+                    list.to_token_stream(),
+                    "Only one entry of each combination can specify the mode",
+                ));
+            }
+            let combined = MatrixEntry::combine(&combination);
+
+            let mut args = Punctuated::<NestedMeta, Comma>::from_iter(combined.args);
+            args.push(make_nestedmeta_namevalue("key", &combined.key));
+            args.push(make_nestedmeta_namevalue("suffix", &combined.suffix));
+            let conds = combined.conds;
+            let cond: Option<NestedMeta> = match conds.len() {
+                0 => None,
+                1 => Some(NestedMeta::Meta(conds[0].clone())),
+                _ => Some(parse_quote!(all(#(#conds),*))),
+            };
+            if let Some(cond) = cond {
+                args.push(make_nestedmeta_list("cfg", Punctuated::from_iter([cond])));
+            }
+
+            let inner = MacroParameters::from_args(&args)?;
+            let kind = combined
+                .kind
+                .or(inner.preset_get().map(|preset| preset.mode))
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        list.to_token_stream(),
+                        format!(
+                            "The mode of the `{}` combination is not specified",
+                            combined.key
+                        ),
+                    )
+                })?;
+            Self::check_preset_mode(&inner, kind, &list.nested)?;
+            self.params.versions.push(MacroParameterVersion {
+                kind,
+                params: inner,
+            });
+        }
+        Ok(())
+    }
+
     pub fn version(
         &mut self,
         kind: ConvertMode,
         list: &Punctuated<NestedMeta, Comma>,
     ) -> syn::Result<()> {
-        let inner = MacroParameters::from_args(list)?;
+        // the `mode` parameter overrides the kind given by the name of the variant
+        let (mode, args) = Self::take_mode(list)?;
+        let kind = mode.unwrap_or(kind);
+        let inner = MacroParameters::from_args(&args)?;
+        Self::check_preset_mode(&inner, kind, list)?;
         self.params.versions.push(MacroParameterVersion {
             kind,
             params: inner,
@@ -948,6 +3891,79 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    fn check_preset_mode(
+        params: &MacroParameters,
+        kind: ConvertMode,
+        list: &Punctuated<NestedMeta, Comma>,
+    ) -> syn::Result<()> {
+        match params
+            .presets_get()
+            .find(|preset| preset.mode.to_str() != kind.to_str())
+        {
+            Some(preset) => Err(syn::Error::new_spanned(
+                list.to_token_stream(),
+                format!(
+                    "The `{}` preset can only be used in `{}` variants",
+                    preset.name,
+                    preset.mode.to_str()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn add_generics(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in list {
+            match nm {
+                NestedMeta::Lit(Lit::Str(lit)) => {
+                    syn::parse_str::<syn::GenericParam>(&lit.value())
+                        .map_err(|_| syn::Error::new_spanned(lit, "Expected generic parameter"))?;
+                    self.params.add_generics.push(lit.value());
+                }
+                _ => return Err(syn::Error::new_spanned(nm, "Expected string literal")),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_bounds(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in list {
+            match nm {
+                NestedMeta::Lit(Lit::Str(lit)) => {
+                    syn::parse_str::<syn::WherePredicate>(&lit.value())
+                        .map_err(|_| syn::Error::new_spanned(lit, "Expected `where` predicate"))?;
+                    self.params.add_bounds.push(lit.value());
+                }
+                _ => return Err(syn::Error::new_spanned(nm, "Expected string literal")),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn drop_bounds(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params.drop_bounds.extend(bound_names(list)?);
+        Ok(())
+    }
+
+    pub fn relax_bounds(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params.relax_bounds.extend(bound_names(list)?);
+        Ok(())
+    }
+
+    pub fn drop_generics(&mut self, list: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in list {
+            let name = match nm {
+                NestedMeta::Meta(Meta::Path(path)) => path.get_ident().map(Ident::to_string),
+                NestedMeta::Lit(Lit::Str(lit)) => Some(lit.value()),
+                _ => None,
+            };
+            let name =
+                name.ok_or_else(|| syn::Error::new_spanned(nm, "Expected generic parameter name"))?;
+            self.params.drop_generics.push(name);
+        }
+        Ok(())
+    }
+
     pub fn drop_attrs(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
         for nm in meta {
             match nm {
@@ -972,6 +3988,149 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    pub fn attr_policy(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in meta {
+            match nm {
+                NestedMeta::Meta(Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) => {
+                    let policy = lit.value();
+                    if !ATTR_POLICIES.contains(&policy.as_str()) {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            format!("Expected one of: {}", ATTR_POLICIES.join(", ")),
+                        ));
+                    }
+                    let name = path.to_token_stream().to_string().replace(' ', "");
+                    self.params.attr_policies.push((name, policy));
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected `attribute = \"policy\"`",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn strip_methods(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in meta {
+            match nm {
+                NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                    let name = path.get_ident().unwrap().to_string();
+                    self.params.strip_methods.push(name);
+                }
+                NestedMeta::Lit(Lit::Str(lit)) if syn::parse_str::<Ident>(&lit.value()).is_ok() => {
+                    self.params.strip_methods.push(lit.value());
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected list of method names",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn process_macros(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params.process_macros.extend(macro_names(meta)?);
+        Ok(())
+    }
+
+    pub fn no_macro_processing(
+        &mut self,
+        meta: Option<&Punctuated<NestedMeta, Comma>>,
+    ) -> syn::Result<()> {
+        let names = self.params.no_macro_processing.get_or_insert_with(Vec::new);
+        if let Some(meta) = meta {
+            names.extend(macro_names(meta)?);
+        }
+        Ok(())
+    }
+
+    pub fn map_into_future(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params
+            .into_future_map
+            .extend(ident_pairs(meta, "method")?);
+        Ok(())
+    }
+
+    pub fn methods(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params.methods.extend(ident_pairs(meta, "method")?);
+        Ok(())
+    }
+
+    pub fn calls(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in meta {
+            match nm {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit),
+                    ..
+                })) => {
+                    lit.parse::<syn::Expr>()?;
+                    let from = path.to_token_stream().to_string().replace(' ', "");
+                    let from = from.trim_start_matches("::").to_string();
+                    self.params.calls.push((from, lit.value()));
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm.to_token_stream(),
+                        "Expected `path = \"expression\"`",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn crates(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        self.params.crates.extend(ident_pairs(meta, "crate")?);
+        Ok(())
+    }
+
+    pub fn add_await(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
+        for nm in meta {
+            let (kind, names) = match nm {
+                NestedMeta::Meta(Meta::List(list)) => match list.path.get_ident() {
+                    Some(kind) => (kind.to_string(), &list.nested),
+                    None => return Err(syn::Error::new_spanned(nm, "Expected `fns` or `methods`")),
+                },
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        nm,
+                        "Expected `fns(..)` or `methods(..)`",
+                    ))
+                }
+            };
+            let target = self
+                .params
+                .add_await
+                .names_mut(&kind)
+                .ok_or_else(|| syn::Error::new_spanned(nm, "Expected `fns` or `methods`"))?;
+            for name in names {
+                match name {
+                    NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                        target.push(path.get_ident().unwrap().to_string());
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            name.to_token_stream(),
+                            "Expected list of idents",
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn replace_feature(&mut self, meta: &Punctuated<NestedMeta, Comma>) -> syn::Result<()> {
         match meta.len() {
             2 => {
@@ -1007,18 +4166,182 @@ impl MacroParametersBuilder {
         Ok(())
     }
 
+    /// Enables the variant with the `key` also when the conditions of all other variants are false.
+    fn apply_default_variant(versions: &mut [MacroParameterVersion], key: &str) -> syn::Result<()> {
+        let index = versions
+            .iter()
+            .position(|version| version.params.key_get() == Some(key))
+            .ok_or_else(|| {
+                syn::Error::new(
+                    Span::call_site(),
+                    format!("The `default_variant` refers to unknown variant `{}`", key),
+                )
+            })?;
+
+        let others = versions
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .filter_map(|(_, version)| version.params.cfg_get())
+            .collect::<Vec<_>>();
+        if others.is_empty() {
+            return Ok(());
+        }
+
+        // a variant without condition is enabled anyway
+        let default = &mut versions[index].params;
+        if let Some(cfg) = &default.cfg {
+            default.cfg = Some(parse_quote!(any(#cfg, not(any(#(#others),*)))));
+        }
+        Ok(())
+    }
+
     pub fn build(mut self) -> syn::Result<MacroParameters> {
         let mut versions = std::mem::replace(&mut self.params.versions, vec![]);
 
+        if versions.iter().filter(|v| v.params.primary).count() > 1 {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "Only one variant can be marked as `primary`",
+            ));
+        }
+
         for version in &mut versions {
             MacroParameters::apply_parent(&mut version.params, &self.params)?;
 
             if version.params.key.is_none() {
                 version.params.key = Some(version.kind.to_str().to_string());
             }
+
+            if version.params.dispatch.is_some() {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "The `dispatch` parameter can only be applied to the whole item, not to variants",
+                ));
+            }
+
+            if version.params.constness && !matches!(version.kind, ConvertMode::IntoSync) {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "The `constness` parameter can only be applied to `sync` variants",
+                ));
+            }
+            // the top-level `constness` applies to the `sync` variants only
+            if let ConvertMode::IntoSync = version.kind {
+                version.params.constness |= self.params.constness;
+            }
+
+            match version.kind {
+                ConvertMode::IntoAsync => {
+                    if version.params.blocking_ext.is_none() {
+                        version.params.blocking_ext = self.params.blocking_ext.clone();
+                    }
+                }
+                ConvertMode::IntoSync | ConvertMode::IntoDyn => {
+                    if version.params.blocking_ext.is_some() {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "The `blocking_ext` parameter can only be applied to `async` variants",
+                        ));
+                    }
+                }
+            }
+
+            match version.kind {
+                ConvertMode::IntoSync => {
+                    if version.params.delegate.is_none() {
+                        version.params.delegate = self.params.delegate.clone();
+                    }
+                }
+                ConvertMode::IntoAsync => {}
+                ConvertMode::IntoDyn => {
+                    if version.params.delegate.is_some() {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "The `delegate` parameter can only be applied to `sync` and `async` \
+                            variants",
+                        ));
+                    }
+                }
+            }
+
+            if version.params.bridge.is_none() {
+                version.params.bridge = self.params.bridge.clone();
+            }
+            match (version.kind, &version.params.bridge) {
+                (ConvertMode::IntoSync, Some(Bridge { block_on: None, .. })) => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        "The `bridge` parameter of `sync` variants requires the `block_on` \
+                        function",
+                    ));
+                }
+                (ConvertMode::IntoDyn, Some(_)) => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        "The `bridge` parameter can only be applied to `sync` and `async` variants",
+                    ));
+                }
+                _ => {}
+            }
+
+            match version.kind {
+                ConvertMode::IntoSync => {
+                    if version.params.relax_bounds.is_empty() {
+                        version.params.relax_bounds = self.params.relax_bounds.clone();
+                    }
+                }
+                ConvertMode::IntoAsync | ConvertMode::IntoDyn => {
+                    if !version.params.relax_bounds.is_empty() {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "The `relax_bounds` parameter can only be applied to `sync` variants",
+                        ));
+                    }
+                }
+            }
+
+            match version.kind {
+                ConvertMode::IntoAsync => {
+                    version.params.impl_future |= self.params.impl_future;
+                    version.params.send_bounds |= self.params.send_bounds;
+                    if version.params.dyn_compat.is_none() {
+                        version.params.dyn_compat = self.params.dyn_compat.clone();
+                    }
+                }
+                ConvertMode::IntoSync | ConvertMode::IntoDyn => {
+                    if version.params.impl_future {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "The `impl_future` parameter can only be applied to `async` variants",
+                        ));
+                    }
+                    if version.params.send_bounds {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "The `send_bounds` parameter can only be applied to `async` variants",
+                        ));
+                    }
+                    if version.params.dyn_compat.is_some() {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "The `dyn_compat` parameter can only be applied to `async` variants",
+                        ));
+                    }
+                }
+            }
         }
 
         self.params.versions = versions;
+        self.params.conditions_apply(false)?;
+
+        // the default variant waits for the conditions inherited later
+        let pending = self.params.versions.iter().any(|v| v.params.cond.is_some());
+        if !pending {
+            if let Some(key) = self.params.default_variant.take() {
+                Self::apply_default_variant(&mut self.params.versions, &key)?;
+            }
+        }
 
         Ok(self.params)
     }
@@ -58,6 +58,59 @@ pub(crate) fn make_attr_ts_from_str<S: AsRef<str>>(s: S, span: Span) -> syn::Res
     Ok(make_attr_from_str(s, span)?.to_token_stream())
 }
 
+/// Converts a `CamelCase` name to `snake_case`.
+pub(crate) fn camel_to_snake_case(s: &str) -> String {
+    let mut res = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                res.push('_');
+            }
+            res.extend(c.to_lowercase());
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+/// Converts a name with `_` or `-` separators (such as a variant key) to `CamelCase`.
+pub(crate) fn to_camel_case(s: &str) -> String {
+    s.split(['_', '-'])
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Parses a method receiver, such as `&mut self` or `self: Arc<Self>`.
+pub(crate) fn parse_receiver<S: AsRef<str>>(s: S, span: Span) -> syn::Result<syn::FnArg> {
+    match syn::parse_str::<syn::FnArg>(s.as_ref()) {
+        Ok(arg @ syn::FnArg::Receiver(_)) => Ok(arg),
+        Ok(syn::FnArg::Typed(pat)) if matches!(&*pat.pat, syn::Pat::Ident(p) if p.ident == "self") => {
+            Ok(syn::FnArg::Typed(pat))
+        }
+        _ => Err(syn::Error::new(
+            span,
+            "Expected method receiver, such as `&self` or `&mut self`",
+        )),
+    }
+}
+
+/// Makes a block that produces a compiler warning with the given message. There is no stable API
+/// for warnings in procedural macros, so the block uses a deprecated item instead.
+pub(crate) fn make_warning_block(message: &str) -> syn::Block {
+    syn::parse_quote!({
+        #[deprecated(note = #message)]
+        struct MaybeAsyncWarning;
+        let _ = MaybeAsyncWarning;
+    })
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 struct VecOfAttrs {
@@ -91,6 +144,83 @@ impl syn::parse::Parse for AttributeArgsInParens {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// The body of `facade!`: `vis? paths (; parameters)?`.
+pub struct Facade {
+    pub vis: syn::Visibility,
+    pub paths: Punctuated<syn::Path, Comma>,
+    pub args: Punctuated<NestedMeta, Comma>,
+}
+
+impl syn::parse::Parse for Facade {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let vis = match input.parse()? {
+            syn::Visibility::Inherited => syn::parse_quote!(pub),
+            vis => vis,
+        };
+
+        let mut paths = Punctuated::new();
+        while !input.is_empty() && !input.peek(Token![;]) {
+            paths.push_value(input.parse()?);
+            if input.is_empty() || input.peek(Token![;]) {
+                break;
+            }
+            paths.push_punct(input.parse()?);
+        }
+
+        let args = if input.parse::<Option<Token![;]>>()?.is_some() {
+            input.parse_terminated(NestedMeta::parse)?
+        } else {
+            Punctuated::new()
+        };
+
+        Ok(Self { vis, paths, args })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The body of `define_preset!`: `vis name(parameters)`.
+pub struct DefinePreset {
+    pub vis: syn::Visibility,
+    pub name: Ident,
+    pub args: AttributeArgsInParens,
+}
+
+impl syn::parse::Parse for DefinePreset {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let preset = Self {
+            vis: input.parse()?,
+            name: input.parse()?,
+            args: input.parse()?,
+        };
+        if !input.is_empty() {
+            input.parse::<Token![;]>()?;
+        }
+        Ok(preset)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The body of `maybe_send_alias!`: `vis? parameters`.
+pub struct MaybeSendAlias {
+    pub vis: syn::Visibility,
+    pub args: Punctuated<NestedMeta, Comma>,
+}
+
+impl syn::parse::Parse for MaybeSendAlias {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let vis = match input.parse()? {
+            syn::Visibility::Inherited => syn::parse_quote!(pub),
+            vis => vis,
+        };
+        let args = input.parse_terminated(NestedMeta::parse)?;
+        Ok(Self { vis, args })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
 pub struct PunctuatedList {
     pub list: Punctuated<Expr, Comma>,
 }
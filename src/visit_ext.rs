@@ -4,8 +4,12 @@ use std::iter::FromIterator;
 #[allow(unused_imports)]
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::quote;
-use syn::visit_mut::{self, VisitMut};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    visit_mut::{self, VisitMut},
+};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
@@ -26,15 +30,33 @@ pub trait VisitMutExt {
     fn process_attribute(&mut self, _node: &mut syn::Attribute) -> syn::Result<()> {
         Ok(())
     }
+    fn process_block(&mut self, _node: &mut syn::Block) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_expr(&mut self, _node: &mut syn::Expr) -> syn::Result<()> {
         Ok(())
     }
+    fn process_expr_async(&mut self, _node: &mut syn::ExprAsync) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_expr_closure(&mut self, _node: &mut syn::ExprClosure) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_fields(&mut self, _node: &mut syn::Fields) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_file(&mut self, _node: &mut syn::File) -> syn::Result<()> {
         Ok(())
     }
+    fn process_impl_item_method(&mut self, _node: &mut syn::ImplItemMethod) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_item(&mut self, _node: &mut syn::Item) -> syn::Result<()> {
         Ok(())
     }
+    fn process_item_enum(&mut self, _node: &mut syn::ItemEnum) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_item_impl(&mut self, _node: &mut syn::ItemImpl) -> syn::Result<()> {
         Ok(())
     }
@@ -47,31 +69,85 @@ pub trait VisitMutExt {
     fn process_macro(&mut self, _node: &mut syn::Macro) -> syn::Result<()> {
         Ok(())
     }
+    fn process_path(&mut self, _node: &mut syn::Path) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_path_segment(&mut self, _node: &mut syn::PathSegment) -> syn::Result<()> {
         Ok(())
     }
+    fn process_stmt(&mut self, _node: &mut syn::Stmt) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_bounds(
+        &mut self,
+        _node: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>,
+    ) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_trait_bound(&mut self, _node: &mut syn::TraitBound) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_trait_item_method(&mut self, _node: &mut syn::TraitItemMethod) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_type(&mut self, _node: &mut syn::Type) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_type_param(&mut self, _node: &mut syn::TypeParam) -> syn::Result<()> {
         Ok(())
     }
+    fn process_generics(&mut self, _node: &mut syn::Generics) -> syn::Result<()> {
+        Ok(())
+    }
+    fn process_expr_method_call(&mut self, _node: &mut syn::ExprMethodCall) -> syn::Result<()> {
+        Ok(())
+    }
+    fn after_process_item_use(&mut self, _node: &mut syn::ItemUse) -> syn::Result<()> {
+        Ok(())
+    }
     fn process_use_tree(&mut self, _node: &mut syn::UseTree) -> syn::Result<()> {
         Ok(())
     }
 
+    fn after_process_expr_async(&mut self, _node: &mut syn::ExprAsync) -> syn::Result<()> {
+        Ok(())
+    }
+    fn after_process_expr_closure(&mut self, _node: &mut syn::ExprClosure) -> syn::Result<()> {
+        Ok(())
+    }
+    fn after_process_impl_item_method(
+        &mut self,
+        _node: &mut syn::ImplItemMethod,
+    ) -> syn::Result<()> {
+        Ok(())
+    }
     fn after_process_item(&mut self, _node: &mut syn::Item) -> syn::Result<()> {
         Ok(())
     }
+    fn after_process_item_impl(&mut self, _node: &mut syn::ItemImpl) -> syn::Result<()> {
+        Ok(())
+    }
+    fn after_process_item_fn(&mut self, _node: &mut syn::ItemFn) -> syn::Result<()> {
+        Ok(())
+    }
+    fn after_process_trait_item_method(
+        &mut self,
+        _node: &mut syn::TraitItemMethod,
+    ) -> syn::Result<()> {
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
 pub struct Visitor<T> {
     pub inner: T,
-    //    err: Option<syn::Error>,
+    err: Option<syn::Error>,
 }
 
 impl<T> Visitor<T> {
     pub fn new(inner: T) -> Self {
-        Self { inner }
+        Self { inner, err: None }
     }
 
     pub fn process(&mut self, item: TokenStream2) -> syn::Result<TokenStream2>
@@ -80,10 +156,148 @@ impl<T> Visitor<T> {
     {
         let mut syntax_tree: syn::File = syn::parse(item.into())?;
         self.visit_file_mut(&mut syntax_tree);
+        self.result()?;
         let ts = quote!(#syntax_tree);
 
         Ok(ts)
     }
+
+    /// Returns the errors collected while visiting, the visiting itself cannot fail.
+    pub fn result(&mut self) -> syn::Result<()> {
+        match self.err.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn error(&mut self, err: syn::Error) {
+        match &mut self.err {
+            Some(e) => e.combine(err),
+            None => self.err = Some(err),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The expressions syn keeps as `Expr::Verbatim`: `let PAT = EXPR else { .. }` (which is a
+/// statement, `Stmt::Semi` with verbatim tokens) and inline `const { .. }` blocks.
+enum VerbatimExpr {
+    LetElse {
+        attrs: Vec<syn::Attribute>,
+        let_token: syn::token::Let,
+        pat: syn::Pat,
+        eq_token: syn::token::Eq,
+        init: Box<syn::Expr>,
+        else_token: syn::token::Else,
+        diverge: syn::Block,
+    },
+    Const {
+        const_token: syn::token::Const,
+        block: syn::Block,
+    },
+}
+
+impl VerbatimExpr {
+    fn visit_mut<V: VisitMut>(&mut self, visitor: &mut V) {
+        match self {
+            VerbatimExpr::LetElse {
+                attrs,
+                pat,
+                init,
+                diverge,
+                ..
+            } => {
+                for attr in attrs {
+                    visitor.visit_attribute_mut(attr);
+                }
+                visitor.visit_pat_mut(pat);
+                visitor.visit_expr_mut(init);
+                visitor.visit_block_mut(diverge);
+            }
+            VerbatimExpr::Const { block, .. } => visitor.visit_block_mut(block),
+        }
+    }
+}
+
+/// Visits the parts of the verbatim expression (see `VerbatimExpr`), returns `false` if the tokens
+/// are not such an expression.
+pub fn visit_verbatim_mut<V: VisitMut>(visitor: &mut V, tokens: &mut TokenStream2) -> bool {
+    match syn::parse2::<VerbatimExpr>(tokens.clone()) {
+        Ok(mut verbatim) => {
+            verbatim.visit_mut(visitor);
+            *tokens = verbatim.into_token_stream();
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+impl Parse for VerbatimExpr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        if input.peek(syn::token::Const) {
+            return Ok(VerbatimExpr::Const {
+                const_token: input.parse()?,
+                block: input.parse()?,
+            });
+        }
+
+        let let_token = input.parse()?;
+        let leading_vert: Option<syn::token::Or> = input.parse()?;
+        let mut pat: syn::Pat = input.parse()?;
+        if leading_vert.is_some() || input.peek(syn::token::Or) && !input.peek(syn::token::OrOr) {
+            let mut cases = Punctuated::new();
+            cases.push_value(pat);
+            while input.peek(syn::token::Or) && !input.peek(syn::token::OrOr) {
+                cases.push_punct(input.parse()?);
+                cases.push_value(input.parse()?);
+            }
+            pat = syn::Pat::Or(syn::PatOr {
+                attrs: vec![],
+                leading_vert,
+                cases,
+            });
+        }
+        if input.peek(syn::token::Colon) {
+            pat = syn::Pat::Type(syn::PatType {
+                attrs: vec![],
+                pat: Box::new(pat),
+                colon_token: input.parse()?,
+                ty: input.parse()?,
+            });
+        }
+        Ok(VerbatimExpr::LetElse {
+            attrs,
+            let_token,
+            pat,
+            eq_token: input.parse()?,
+            init: input.parse()?,
+            else_token: input.parse()?,
+            diverge: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for VerbatimExpr {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            VerbatimExpr::LetElse {
+                attrs,
+                let_token,
+                pat,
+                eq_token,
+                init,
+                else_token,
+                diverge,
+            } => tokens.extend(quote!(
+                #(#attrs)* #let_token #pat #eq_token #init #else_token #diverge
+            )),
+            VerbatimExpr::Const { const_token, block } => {
+                tokens.extend(quote!(#const_token #block))
+            }
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -97,11 +311,15 @@ macro_rules! impl_fn {
     };
     (@func $self:expr, $node:ident, $proc:ident(node $(.$path:ident)? as Some($expr:tt) $(, $mode:expr)?) ) => {
         if let Some(value) = impl_fn!(@arg $node $(.$path)?) {
-            $self.$proc( impl_fn!(@expr value, $expr) $(, $mode)? ).unwrap();
+            if let Err(err) = $self.$proc( impl_fn!(@expr value, $expr) $(, $mode)? ) {
+                $self.error(err);
+            }
         };
     };
     (@func $self:expr, $node:ident, $proc:ident(node $(.$path:ident)? $(, $mode:expr)?) ) => {
-        $self.$proc( impl_fn!(@arg $node $(.$path)?) $(, $mode)? ).unwrap();
+        if let Err(err) = $self.$proc( impl_fn!(@arg $node $(.$path)?) $(, $mode)? ) {
+            $self.error(err);
+        }
     };
     (@funcs $self:expr, $node:ident, { $($proc:ident $params:tt ;)+ } ) => {
         $(
@@ -150,7 +368,7 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_bare_fn_arg_mut,         syn::BareFnArg,         { process_attrs(node.attrs); });
     impl_fn!(visit_bin_op_mut,              syn::BinOp,             );
     impl_fn!(visit_binding_mut,             syn::Binding,           { process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_block_mut,               syn::Block,             );
+    impl_fn!(visit_block_mut,               syn::Block,             { process_block(node); });
     impl_fn!(visit_bound_lifetimes_mut,     syn::BoundLifetimes,    );
     impl_fn!(visit_const_param_mut,         syn::ConstParam,        { process_attrs(node.attrs); });
     impl_fn!(visit_constraint_mut,          syn::Constraint,        );
@@ -159,11 +377,22 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_data_struct_mut,         syn::DataStruct,        );
     impl_fn!(visit_data_union_mut,          syn::DataUnion,         );
     impl_fn!(visit_derive_input_mut,        syn::DeriveInput,       { process_attrs(node.attrs); });
-    impl_fn!(visit_expr_mut,                syn::Expr,              { process_expr(node); });
+    fn visit_expr_mut(&mut self, node: &mut syn::Expr) {
+        if let Err(err) = self.process_expr(node) {
+            self.error(err);
+        }
+        // the constructs syn keeps as tokens are visited part by part
+        if let syn::Expr::Verbatim(tokens) = node {
+            if visit_verbatim_mut(self, tokens) {
+                return;
+            }
+        }
+        visit_mut::visit_expr_mut(self, node);
+    }
     impl_fn!(visit_expr_array_mut,          syn::ExprArray,         { process_attrs(node.attrs); });
     impl_fn!(visit_expr_assign_mut,         syn::ExprAssign,        { process_attrs(node.attrs); });
     impl_fn!(visit_expr_assign_op_mut,      syn::ExprAssignOp,      { process_attrs(node.attrs); });
-    impl_fn!(visit_expr_async_mut,          syn::ExprAsync,         { process_attrs(node.attrs); });
+    impl_fn!(visit_expr_async_mut,          syn::ExprAsync,         { process_attrs(node.attrs); process_expr_async(node); },        { after_process_expr_async(node); });
     impl_fn!(visit_expr_await_mut,          syn::ExprAwait,         { process_attrs(node.attrs); });
     impl_fn!(visit_expr_binary_mut,         syn::ExprBinary,        { process_attrs(node.attrs); });
     impl_fn!(visit_expr_block_mut,          syn::ExprBlock,         { process_attrs(node.attrs); });
@@ -171,7 +400,7 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_expr_break_mut,          syn::ExprBreak,         { process_attrs(node.attrs); });
     impl_fn!(visit_expr_call_mut,           syn::ExprCall,          { process_attrs(node.attrs); });
     impl_fn!(visit_expr_cast_mut,           syn::ExprCast,          { process_attrs(node.attrs); });
-    impl_fn!(visit_expr_closure_mut,        syn::ExprClosure,       { process_attrs(node.attrs); });
+    impl_fn!(visit_expr_closure_mut,        syn::ExprClosure,       { process_attrs(node.attrs); process_expr_closure(node); },      { after_process_expr_closure(node); });
     impl_fn!(visit_expr_continue_mut,       syn::ExprContinue,      { process_attrs(node.attrs); });
     impl_fn!(visit_expr_field_mut,          syn::ExprField,         { process_attrs(node.attrs); });
     impl_fn!(visit_expr_for_loop_mut,       syn::ExprForLoop,       { process_attrs(node.attrs); });
@@ -183,7 +412,7 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_expr_loop_mut,           syn::ExprLoop,          { process_attrs(node.attrs); });
     impl_fn!(visit_expr_macro_mut,          syn::ExprMacro,         { process_attrs(node.attrs); });
     impl_fn!(visit_expr_match_mut,          syn::ExprMatch,         { process_attrs(node.attrs); });
-    impl_fn!(visit_expr_method_call_mut,    syn::ExprMethodCall,    { process_attrs(node.attrs); });
+    impl_fn!(visit_expr_method_call_mut,    syn::ExprMethodCall,    { process_attrs(node.attrs); process_expr_method_call(node); });
     impl_fn!(visit_expr_paren_mut,          syn::ExprParen,         { process_attrs(node.attrs); });
     impl_fn!(visit_expr_path_mut,           syn::ExprPath,          { process_attrs(node.attrs); });
     impl_fn!(visit_expr_range_mut,          syn::ExprRange,         { process_attrs(node.attrs); });
@@ -202,7 +431,7 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_field_mut,               syn::Field,             { process_attrs(node.attrs); });
     impl_fn!(visit_field_pat_mut,           syn::FieldPat,          { process_attrs(node.attrs); });
     impl_fn!(visit_field_value_mut,         syn::FieldValue,        { process_attrs(node.attrs); });
-    impl_fn!(visit_fields_mut,              syn::Fields,            );
+    impl_fn!(visit_fields_mut,              syn::Fields,            { process_fields(node); });
     impl_fn!(visit_fields_named_mut,        syn::FieldsNamed,       );
     impl_fn!(visit_fields_unnamed_mut,      syn::FieldsUnnamed,     );
     impl_fn!(visit_file_mut,                syn::File,              { process_attrs(node.attrs); process_file(node); });
@@ -217,31 +446,31 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
                                             syn::GenericMethodArgument, 
                                                                     );
     impl_fn!(visit_generic_param_mut,       syn::GenericParam,      );
-    impl_fn!(visit_generics_mut,            syn::Generics,          );
+    impl_fn!(visit_generics_mut,            syn::Generics,          { process_generics(node); });
     impl_fn!(visit_ident_mut,               syn::Ident,             );
     impl_fn!(visit_impl_item_mut,           syn::ImplItem,          );
     impl_fn!(visit_impl_item_const_mut,     syn::ImplItemConst,     { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_impl_item_macro_mut,     syn::ImplItemMacro,     { process_attrs(node.attrs); });
-    impl_fn!(visit_impl_item_method_mut,    syn::ImplItemMethod,    { process_attrs(node.attrs); });
+    impl_fn!(visit_impl_item_method_mut,    syn::ImplItemMethod,    { process_attrs(node.attrs); process_impl_item_method(node); }, { after_process_impl_item_method(node); });
     impl_fn!(visit_impl_item_type_mut,      syn::ImplItemType,      { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_index_mut,               syn::Index,             );
     impl_fn!(visit_item_mut,                syn::Item,              { process_item(node); },            { after_process_item(node); });
     impl_fn!(visit_item_const_mut,          syn::ItemConst,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_enum_mut,           syn::ItemEnum,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
+    impl_fn!(visit_item_enum_mut,           syn::ItemEnum,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_item_enum(node); });
     impl_fn!(visit_item_extern_crate_mut,   syn::ItemExternCrate,   { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_ident(node.rename as Some((_, value)), IdentMode::Other); });
-    impl_fn!(visit_item_fn_mut,             syn::ItemFn,            { process_attrs(node.attrs); process_item_fn(node); });
+    impl_fn!(visit_item_fn_mut,             syn::ItemFn,            { process_attrs(node.attrs); process_item_fn(node); },          { after_process_item_fn(node); });
     impl_fn!(visit_item_foreign_mod_mut,    syn::ItemForeignMod,    { process_attrs(node.attrs); });
-    impl_fn!(visit_item_impl_mut,           syn::ItemImpl,          { process_attrs(node.attrs); process_item_impl(node); });
+    impl_fn!(visit_item_impl_mut,           syn::ItemImpl,          { process_attrs(node.attrs); process_item_impl(node); }, { after_process_item_impl(node); });
     impl_fn!(visit_item_macro_mut,          syn::ItemMacro,         { process_attrs(node.attrs); process_ident(node.ident as Some(value), IdentMode::Other); });
     impl_fn!(visit_item_macro2_mut,         syn::ItemMacro2,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_mod_mut,            syn::ItemMod,           { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_static_mut,         syn::ItemStatic,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_struct_mut,         syn::ItemStruct,        { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_trait_mut,          syn::ItemTrait,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_item_trait(node); });
+    impl_fn!(visit_item_trait_mut,          syn::ItemTrait,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_item_trait(node); process_bounds(node.supertraits); });
     impl_fn!(visit_item_trait_alias_mut,    syn::ItemTraitAlias,    { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_type_mut,           syn::ItemType,          { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_item_union_mut,          syn::ItemUnion,         { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_item_use_mut,            syn::ItemUse,           { process_attrs(node.attrs); });
+    impl_fn!(visit_item_use_mut,            syn::ItemUse,           { process_attrs(node.attrs); }, { after_process_item_use(node); });
     impl_fn!(visit_label_mut,               syn::Label,             );
     impl_fn!(visit_lifetime_mut,            syn::Lifetime,          );
     impl_fn!(visit_lifetime_def_mut,        syn::LifetimeDef,       { process_attrs(node.attrs); });
@@ -281,42 +510,42 @@ impl<T> VisitMut for Visitor<T> where Self: VisitMutExt,
     impl_fn!(visit_pat_tuple_struct_mut,    syn::PatTupleStruct,    { process_attrs(node.attrs); });
     impl_fn!(visit_pat_type_mut,            syn::PatType,           { process_attrs(node.attrs); });
     impl_fn!(visit_pat_wild_mut,            syn::PatWild,           { process_attrs(node.attrs); });
-    impl_fn!(visit_path_mut,                syn::Path,              );
+    impl_fn!(visit_path_mut,                syn::Path,              { process_path(node); });
     impl_fn!(visit_path_arguments_mut,      syn::PathArguments,     );
     impl_fn!(visit_path_segment_mut,        syn::PathSegment,       { process_path_segment(node); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_predicate_eq_mut,        syn::PredicateEq,       );
     impl_fn!(visit_predicate_lifetime_mut,  syn::PredicateLifetime, );
-    impl_fn!(visit_predicate_type_mut,      syn::PredicateType,     );
+    impl_fn!(visit_predicate_type_mut,      syn::PredicateType,     { process_bounds(node.bounds); });
     impl_fn!(visit_qself_mut,               syn::QSelf,             );
     impl_fn!(visit_range_limits_mut,        syn::RangeLimits,       );
     impl_fn!(visit_receiver_mut,            syn::Receiver,          { process_attrs(node.attrs); });
     impl_fn!(visit_return_type_mut,         syn::ReturnType,        );
     impl_fn!(visit_signature_mut,           syn::Signature,         { process_ident(node.ident, IdentMode::Other); } );
     impl_fn!(visit_span_mut,                Span,                   );
-    impl_fn!(visit_stmt_mut,                syn::Stmt,              );
-    impl_fn!(visit_trait_bound_mut,         syn::TraitBound,        );
+    impl_fn!(visit_stmt_mut,                syn::Stmt,              { process_stmt(node); });
+    impl_fn!(visit_trait_bound_mut,         syn::TraitBound,        { process_trait_bound(node); });
     impl_fn!(visit_trait_bound_modifier_mut,syn::TraitBoundModifier,);
     impl_fn!(visit_trait_item_mut,          syn::TraitItem,         );
     impl_fn!(visit_trait_item_const_mut,    syn::TraitItemConst,    { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
     impl_fn!(visit_trait_item_macro_mut,    syn::TraitItemMacro,    { process_attrs(node.attrs); });
-    impl_fn!(visit_trait_item_method_mut,   syn::TraitItemMethod,   { process_attrs(node.attrs); });
-    impl_fn!(visit_trait_item_type_mut,     syn::TraitItemType,     { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); });
-    impl_fn!(visit_type_mut,                syn::Type,              );
+    impl_fn!(visit_trait_item_method_mut,   syn::TraitItemMethod,   { process_attrs(node.attrs); process_trait_item_method(node); }, { after_process_trait_item_method(node); });
+    impl_fn!(visit_trait_item_type_mut,     syn::TraitItemType,     { process_attrs(node.attrs); process_ident(node.ident, IdentMode::Other); process_bounds(node.bounds); });
+    impl_fn!(visit_type_mut,                syn::Type,              { process_type(node); });
     impl_fn!(visit_type_array_mut,          syn::TypeArray,         );
     impl_fn!(visit_type_bare_fn_mut,        syn::TypeBareFn,        );
     impl_fn!(visit_type_group_mut,          syn::TypeGroup,         );
-    impl_fn!(visit_type_impl_trait_mut,     syn::TypeImplTrait,     );
+    impl_fn!(visit_type_impl_trait_mut,     syn::TypeImplTrait,     { process_bounds(node.bounds); });
     impl_fn!(visit_type_infer_mut,          syn::TypeInfer,         );
     impl_fn!(visit_type_macro_mut,          syn::TypeMacro,         );
     impl_fn!(visit_type_never_mut,          syn::TypeNever,         );
-    impl_fn!(visit_type_param_mut,          syn::TypeParam,         { process_attrs(node.attrs); process_type_param(node); });
+    impl_fn!(visit_type_param_mut,          syn::TypeParam,         { process_attrs(node.attrs); process_type_param(node); process_bounds(node.bounds); });
     impl_fn!(visit_type_param_bound_mut,    syn::TypeParamBound,    );
     impl_fn!(visit_type_paren_mut,          syn::TypeParen,         );
     impl_fn!(visit_type_path_mut,           syn::TypePath,          );
     impl_fn!(visit_type_ptr_mut,            syn::TypePtr,           );
     impl_fn!(visit_type_reference_mut,      syn::TypeReference,     );
     impl_fn!(visit_type_slice_mut,          syn::TypeSlice,         );
-    impl_fn!(visit_type_trait_object_mut,   syn::TypeTraitObject,   );
+    impl_fn!(visit_type_trait_object_mut,   syn::TypeTraitObject,   { process_bounds(node.bounds); });
     impl_fn!(visit_type_tuple_mut,          syn::TypeTuple,         );
     impl_fn!(visit_un_op_mut,               syn::UnOp,              );
     impl_fn!(visit_use_glob_mut,            syn::UseGlob,           );
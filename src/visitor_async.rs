@@ -1,12 +1,18 @@
 #[allow(unused_imports)]
-use std::{collections::HashMap, iter::FromIterator};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::FromIterator,
+};
 
 #[allow(unused_imports)]
 use proc_macro::TokenStream;
-#[cfg(feature = "doctests")]
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens};
-use syn::{spanned::Spanned, visit_mut::VisitMut};
+use syn::{
+    parse_quote,
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+};
 
 #[cfg(feature = "doctests")]
 use crate::{
@@ -15,180 +21,2348 @@ use crate::{
 };
 use crate::{
     params::{ConvertMode, MacroParameters},
-    utils::{make_attr_from_str, AttributeArgsInParens, PunctuatedList},
-    visit_ext::{IdentMode, VisitMutExt, Visitor},
-    MACRO_NOOP_NAME, MACRO_ONLY_IF_NAME, MACRO_REMOVE_IF_NAME, MACRO_REMOVE_NAME,
+    utils::{make_attr_from_str, parse_receiver, AttributeArgsInParens, PunctuatedList},
+    visit_ext::{visit_verbatim_mut, IdentMode, VisitMutExt, Visitor},
+    MACRO_ASSOC_TYPE_NAME, MACRO_FIELD_TYPE_NAME, MACRO_NOOP_NAME, MACRO_ONLY_ASYNC_NAME,
+    MACRO_ONLY_IF_NAME, MACRO_ONLY_SYNC_NAME, MACRO_RECEIVER_NAME, MACRO_REMOVE_IF_NAME,
+    MACRO_REMOVE_NAME,
 };
 
 pub struct AsyncAwaitVisitor<'p> {
     convert_mode: ConvertMode,
     params: &'p mut MacroParameters,
-    generics: Vec<HashMap<String, syn::PathSegment>>,
+    generics: Vec<HashMap<String, syn::Type>>,
+    assoc_types: HashMap<String, syn::Type>,
+    item_depth: usize,
+    // for each enclosing function, closure or async block: whether its asyncness is removed
+    async_scopes: Vec<bool>,
+    // the path mapping table of the sync variant
+    path_map: Vec<(Vec<String>, syn::Path)>,
+    // the depths of the scopes (see `async_scopes`) where `.await` was added by `add_await`
+    awaits_added: Vec<usize>,
+    // whether the next expression is already awaited
+    awaited: bool,
+    // for each enclosing function: the lifetimes used outside of its generics before conversion
+    lifetimes: Vec<HashSet<String>>,
+    // for each enclosing function: the names of its arguments and variables holding locks
+    lock_bindings: Vec<HashSet<String>>,
+    // for each enclosing function: the variables holding join handles of spawned tasks (whether
+    // spawned by `async_std`)
+    spawn_bindings: Vec<HashMap<String, bool>>,
+    // for each enclosing impl block: whether it implements a trait
+    trait_impls: Vec<bool>,
 }
 
 impl<'p> AsyncAwaitVisitor<'p> {
     pub fn new(params: &'p mut MacroParameters, convert_mode: ConvertMode) -> Self {
+        let path_map = match convert_mode {
+            ConvertMode::IntoSync => params.path_map_get(),
+            _ => params.types_map_get(),
+        };
         Self {
             convert_mode,
             params,
             generics: vec![],
+            assoc_types: HashMap::new(),
+            item_depth: 0,
+            async_scopes: vec![],
+            path_map,
+            awaits_added: vec![],
+            awaited: false,
+            lifetimes: vec![],
+            lock_bindings: vec![],
+            spawn_bindings: vec![],
+            trait_impls: vec![],
         }
     }
 
-    fn generics_get<S: AsRef<str>>(&self, key: S) -> Option<&syn::PathSegment> {
-        for gens in &self.generics {
-            if let Some(ps) = gens.get(key.as_ref()) {
-                return Some(ps);
+    /// Remembers the lifetimes used by a function before its conversion to the sync variant.
+    fn enter_lifetimes(&mut self, sig: &syn::Signature, block: Option<&syn::Block>) {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            self.lifetimes.push(lifetimes_used(sig, block));
+        }
+    }
+
+    /// Removes the lifetime parameters of a function converted to the sync variant, that were used
+    /// before the conversion but not after it (e.g. the ones of `BoxFuture<'a, T>`). Only the
+    /// lifetimes declared by the function are removed, never `'static` or the ones of the impl
+    /// block.
+    fn leave_lifetimes(&mut self, sig: &mut syn::Signature, block: Option<&syn::Block>) {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            let before = self.lifetimes.pop().unwrap_or_default();
+            let after = lifetimes_used(sig, block);
+            let unused = sig
+                .generics
+                .lifetimes()
+                .map(|def| def.lifetime.ident.to_string())
+                .filter(|name| before.contains(name) && !after.contains(name))
+                .collect::<Vec<_>>();
+            if !unused.is_empty() {
+                prune_lifetimes(&mut sig.generics, &unused);
             }
         }
+    }
+
+    /// Remembers the arguments and variables of a function holding locks, if the async locks are
+    /// mapped to the locks of `std`.
+    fn enter_lock_bindings(&mut self, sig: &syn::Signature, block: Option<&syn::Block>) {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            let bindings = match self.params.locks_get() {
+                Some(_) => lock_bindings(sig, block),
+                None => HashSet::new(),
+            };
+            self.lock_bindings.push(bindings);
+        }
+    }
 
-        None
+    fn leave_lock_bindings(&mut self) {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            self.lock_bindings.pop();
+        }
     }
-}
 
-fn search_future_trait_bound(bound: &syn::TypeParamBound) -> Option<syn::PathSegment> {
-    if let syn::TypeParamBound::Trait(trait_bound) = bound {
-        let segment = &trait_bound.path.segments[trait_bound.path.segments.len() - 1];
-        let name = segment.ident.to_string();
-        if name.eq("Future") {
-            // match Future<Output=Type>
-            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                // binding: Output=Type
-                if let syn::GenericArgument::Binding(binding) = &args.args[0] {
-                    if let syn::Type::Path(p) = &binding.ty {
-                        return Some(p.path.segments[0].clone());
-                    }
+    /// Remembers the variables of a function holding join handles, if the spawning of tasks is
+    /// converted.
+    fn enter_spawn_bindings(&mut self, block: Option<&syn::Block>) {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            let bindings = match (self.params.spawn_get(), block) {
+                (Some(_), Some(block)) => spawn_bindings(block),
+                _ => HashMap::new(),
+            };
+            self.spawn_bindings.push(bindings);
+        }
+    }
+
+    fn leave_spawn_bindings(&mut self) {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            self.spawn_bindings.pop();
+        }
+    }
+
+    /// Returns whether the task was spawned by `async_std`, if the awaited expression is a
+    /// variable holding a join handle.
+    fn spawn_binding(&self, base: &syn::Expr) -> Option<bool> {
+        let ident = match base {
+            syn::Expr::Path(path) => path.path.get_ident()?,
+            _ => return None,
+        };
+        self.spawn_bindings.last()?.get(&ident.to_string()).copied()
+    }
+
+    /// Checks whether the awaited call locks a lock: its receiver is an argument or a variable
+    /// holding a lock, or the method is listed in `lock_methods` (any of them without it).
+    fn is_lock_call(&self, base: &syn::Expr) -> bool {
+        let call = match base {
+            syn::Expr::MethodCall(call) => call,
+            _ => return false,
+        };
+        if lock_call_to_std(base, "").is_none() {
+            return false;
+        }
+        if self.params.lock_methods_contains(&call.method) {
+            return true;
+        }
+        let mut receiver = &*call.receiver;
+        loop {
+            match receiver {
+                syn::Expr::Paren(expr) => receiver = &expr.expr,
+                syn::Expr::Reference(expr) => receiver = &expr.expr,
+                syn::Expr::Unary(expr) if matches!(expr.op, syn::UnOp::Deref(_)) => {
+                    receiver = &expr.expr
                 }
+                _ => break,
+            }
+        }
+        match (receiver, self.lock_bindings.last()) {
+            (syn::Expr::Path(path), Some(bindings)) => path
+                .path
+                .get_ident()
+                .is_some_and(|ident| bindings.contains(&ident.to_string())),
+            _ => false,
+        }
+    }
+
+    /// Leaves the scope of a function, closure or async block, returns whether `.await` was added
+    /// in it by the `add_await` parameter, so it has to become async.
+    fn leave_scope(&mut self) -> bool {
+        let added = self.awaits_added.last() == Some(&self.async_scopes.len());
+        if added {
+            self.awaits_added.pop();
+        }
+        self.async_scopes.pop();
+        added
+    }
+
+    /// Appends `.await` to the calls of the functions and methods listed in the `add_await`
+    /// parameter, in the async variant.
+    fn add_await(&mut self, node: &mut syn::Expr) {
+        if std::mem::take(&mut self.awaited) {
+            return;
+        }
+        let add = match node {
+            syn::Expr::Call(call) => match &*call.func {
+                syn::Expr::Path(path) => matches!(
+                    path.path.segments.last(),
+                    Some(seg) if self.params.add_await_fns_contains(&seg.ident)
+                ),
+                _ => false,
+            },
+            syn::Expr::MethodCall(call) => self.params.add_await_methods_contains(&call.method),
+            _ => false,
+        };
+        if add {
+            *node = parse_quote!(#node.await);
+            self.awaited = true;
+            if self.awaits_added.last() != Some(&self.async_scopes.len()) {
+                self.awaits_added.push(self.async_scopes.len());
+            }
+        }
+    }
+
+    /// Whether the asyncness of the innermost enclosing function, closure or async block is
+    /// removed, so `.await` in it should be removed too.
+    fn async_scope_removed(&self) -> bool {
+        self.async_scopes.last().copied().unwrap_or(true)
+    }
+
+    /// Removes the asyncness of a function or method in the sync variant, if allowed by the
+    /// `asyncness_removal` parameter, and enters its scope.
+    fn enter_fn_scope(&mut self, asyncness: &mut Option<syn::token::Async>, member: bool) {
+        let removal = self.params.asyncness_removal_get();
+        let allowed = match (self.item_depth, member) {
+            (0, false) => removal.signature,
+            (0, true) => removal.members,
+            _ => removal.nested,
+        };
+        let removed = match self.convert_mode {
+            ConvertMode::IntoSync => asyncness.is_none() || allowed,
+            ConvertMode::IntoAsync | ConvertMode::IntoDyn => false,
+        };
+        if removed {
+            *asyncness = None;
+        }
+        self.async_scopes.push(removed);
+    }
+
+    /// Drops the generic future parameters of a function converted to the sync variant, their
+    /// uses are replaced with the output types (see `take_future_generics`).
+    fn enter_future_generics(&mut self, generics: &mut syn::Generics) {
+        let outputs = match self.convert_mode {
+            ConvertMode::IntoSync if self.async_scope_removed() => take_future_generics(generics),
+            _ => HashMap::new(),
+        };
+        self.generics.push(outputs);
+    }
+
+    fn leave_future_generics(&mut self) {
+        self.generics.pop();
+    }
+
+    /// The `send` parameter of the `async_trait` attribute of a trait (or of a trait impl block),
+    /// `None` in the async variant if its methods are desugared to the ones returning `impl Future`
+    /// (`impl_future`) or the trait gets another attribute (`dyn_compat`,
+    /// `send = "trait_variant"`).
+    fn async_trait_send(&self) -> Option<bool> {
+        match self.convert_mode {
+            ConvertMode::IntoAsync
+                if self.params.impl_future_get()
+                    || self.params.dyn_compat_get().is_some()
+                    || self.params.trait_variant_get().is_some() =>
+            {
+                None
             }
+            _ => self.params.send_get(),
         }
+    }
+
+    /// Whether the `Send` bounds are added to the futures and their type parameters in this variant
+    /// (the `send_bounds` parameter).
+    fn send_bounds_added(&self) -> bool {
+        matches!(self.convert_mode, ConvertMode::IntoAsync)
+            && self.params.send_bounds_get()
+            && self.params.send_get() != Some(false)
+    }
+
+    /// Whether an async method of a trait (or of a trait impl block) is desugared to the one
+    /// returning `impl Future` in this variant (the `impl_future` parameter).
+    fn impl_future_desugared(&self, sig: &syn::Signature) -> bool {
+        matches!(self.convert_mode, ConvertMode::IntoAsync)
+            && self.params.impl_future_get()
+            && sig.asyncness.is_some()
+    }
+
+    fn generics_get<S: AsRef<str>>(&self, key: S) -> Option<&syn::Type> {
+        self.generics
+            .iter()
+            .rev()
+            .find_map(|gens| gens.get(key.as_ref()))
+    }
+}
+
+/// Whether the macro is `async_stream::stream!`.
+fn is_stream_macro(mac: &syn::Macro) -> bool {
+    let segments = &mac.path.segments;
+    segments.len() == 2 && segments[0].ident == "async_stream" && segments[1].ident == "stream"
+}
+
+/// Whether the macro is `join!` or `try_join!` (of `futures` or `tokio`, called by the path),
+/// returns `true` for the latter.
+fn join_macro_kind(mac: &syn::Macro) -> Option<bool> {
+    let segments = &mac.path.segments;
+    let name = match segments.len() {
+        2 if segments[0].ident == "futures" || segments[0].ident == "tokio" => &segments[1].ident,
+        _ => return None,
     };
+    if name == "join" {
+        Some(false)
+    } else if name == "try_join" {
+        Some(true)
+    } else {
+        None
+    }
+}
 
-    None
+/// Converts `join!(a, b)` into the tuple `(a, b)` and `try_join!(a, b)` into the matches returning
+/// either the tuple of the results or the first error.
+fn join_macro_to_seq(mac: &syn::Macro, is_try: bool) -> syn::Result<syn::Expr> {
+    let args = mac.parse_body_with(
+        syn::punctuated::Punctuated::<syn::Expr, syn::token::Comma>::parse_terminated,
+    )?;
+    if !is_try {
+        let args = args.iter();
+        return Ok(parse_quote!((#(#args,)*)));
+    }
+
+    let values = (0..args.len())
+        .map(|i| syn::Ident::new(&format!("__maybe_async_value{}", i), Span::mixed_site()))
+        .collect::<Vec<_>>();
+    let err = syn::Ident::new("__maybe_async_err", Span::mixed_site());
+    let mut expr: syn::Expr = parse_quote!(::core::result::Result::Ok((#(#values,)*)));
+    for (arg, value) in args.iter().zip(&values).rev() {
+        expr = parse_quote!(match #arg {
+            ::core::result::Result::Ok(#value) => #expr,
+            ::core::result::Result::Err(#err) => ::core::result::Result::Err(#err),
+        });
+    }
+    Ok(expr)
 }
 
-pub fn remove_asyncness_on_trait(item: &mut syn::ItemTrait, convert_mode: ConvertMode) {
-    match convert_mode {
-        ConvertMode::IntoSync => {
-            for inner in &mut item.items {
-                if let syn::TraitItem::Method(ref mut method) = inner {
-                    if method.sig.asyncness.is_some() {
-                        method.sig.asyncness = None;
-                    }
-                }
-            }
+/// Whether the function is an async sleep (`tokio::time::sleep` or `async_std::task::sleep`).
+fn is_async_sleep(func: &syn::Expr) -> bool {
+    let path = match func {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => path,
+        _ => return false,
+    };
+    let path = path.to_token_stream().to_string().replace(' ', "");
+    matches!(
+        path.trim_start_matches("::"),
+        "tokio::time::sleep" | "async_std::task::sleep"
+    )
+}
+
+/// Whether the function yields to the async scheduler (`tokio::task::yield_now`,
+/// `async_std::task::yield_now`, `futures_lite::future::yield_now` or `smol::future::yield_now`).
+fn is_yield_now(func: &syn::Expr) -> bool {
+    let path = match func {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => path,
+        _ => return false,
+    };
+    let path = path.to_token_stream().to_string().replace(' ', "");
+    matches!(
+        path.trim_start_matches("::"),
+        "tokio::task::yield_now"
+            | "async_std::task::yield_now"
+            | "futures_lite::future::yield_now"
+            | "smol::future::yield_now"
+    )
+}
+
+/// Whether the function is an async timeout (`tokio::time::timeout` or
+/// `async_std::future::timeout`).
+fn is_async_timeout(func: &syn::Expr) -> bool {
+    let path = match func {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => path,
+        _ => return false,
+    };
+    let path = path.to_token_stream().to_string().replace(' ', "");
+    matches!(
+        path.trim_start_matches("::"),
+        "tokio::time::timeout" | "async_std::future::timeout"
+    )
+}
+
+/// Whether the statement is a bare `yield_now().await`.
+fn is_yield_now_stmt(stmt: &syn::Stmt) -> bool {
+    match stmt {
+        syn::Stmt::Semi(syn::Expr::Await(expr), _) => match &*expr.base {
+            syn::Expr::Call(call) => call.args.is_empty() && is_yield_now(&call.func),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether the function spawns an async task (`tokio::spawn`, `tokio::task::spawn`,
+/// `async_std::task::spawn` or `task::spawn`).
+fn is_task_spawn(func: &syn::Expr) -> bool {
+    let path = match func {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => path,
+        _ => return false,
+    };
+    let path = path.to_token_stream().to_string().replace(' ', "");
+    matches!(
+        path.trim_start_matches("::"),
+        "tokio::spawn" | "tokio::task::spawn" | "async_std::task::spawn" | "task::spawn"
+    )
+}
+
+fn is_async_std_spawn(func: &syn::Expr) -> bool {
+    matches!(
+        func,
+        syn::Expr::Path(syn::ExprPath { qself: None, path, .. })
+            if path.to_token_stream().to_string().replace(' ', "").trim_start_matches("::")
+                == "async_std::task::spawn"
+    )
+}
+
+/// Returns the async block boxed by `async { .. }.boxed()` (or `.boxed_local()`), the body of the
+/// functions returning `BoxFuture` (`LocalBoxFuture`).
+fn boxed_async_receiver(expr: &syn::Expr) -> Option<&syn::Expr> {
+    match expr {
+        syn::Expr::MethodCall(call)
+            if (call.method == "boxed" || call.method == "boxed_local")
+                && call.args.is_empty()
+                && matches!(&*call.receiver, syn::Expr::Async(_)) =>
+        {
+            Some(&call.receiver)
         }
-        ConvertMode::IntoAsync => {}
+        _ => None,
     }
 }
 
-pub fn remove_asyncness_on_impl(
-    item: &mut syn::ItemImpl,
-    convert_mode: ConvertMode,
-    send: Option<bool>,
-) {
-    match convert_mode {
-        ConvertMode::IntoSync => {
-            for inner in &mut item.items {
-                if let syn::ImplItem::Method(ref mut method) = inner {
-                    if method.sig.asyncness.is_some() {
-                        method.sig.asyncness = None;
-                    }
-                }
+/// Returns the value of the ready future `std::future::ready(x)` (or of `futures`), or the
+/// receiver of `.into_future()`.
+fn ready_future_value(expr: &syn::Expr) -> Option<&syn::Expr> {
+    match expr {
+        syn::Expr::Call(call) if call.args.len() == 1 => {
+            let path = match &*call.func {
+                syn::Expr::Path(syn::ExprPath {
+                    qself: None, path, ..
+                }) => path,
+                _ => return None,
+            };
+            let path = path.to_token_stream().to_string().replace(' ', "");
+            matches!(
+                path.trim_start_matches("::"),
+                "std::future::ready"
+                    | "core::future::ready"
+                    | "futures::future::ready"
+                    | "future::ready"
+            )
+            .then(|| &call.args[0])
+        }
+        syn::Expr::MethodCall(call) if call.method == "into_future" && call.args.is_empty() => {
+            Some(&call.receiver)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the async block pinned by `Box::pin(async { .. })` (or `Box::pin(async move { .. })`).
+fn box_pin_async_arg(call: &syn::ExprCall) -> Option<&syn::Expr> {
+    let path = match &*call.func {
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => path,
+        _ => return None,
+    };
+    let path = path.to_token_stream().to_string().replace(' ', "");
+    if !matches!(
+        path.trim_start_matches("::"),
+        "Box::pin" | "std::boxed::Box::pin" | "alloc::boxed::Box::pin"
+    ) {
+        return None;
+    }
+    match call.args.first() {
+        Some(arg @ syn::Expr::Async(_)) if call.args.len() == 1 => Some(arg),
+        _ => None,
+    }
+}
+
+/// Converts `spawn(future)` into `target(move || future)`, where the body of an async block is
+/// used directly. The arguments (and the receiver) of a call creating the future are evaluated
+/// before spawning, as they are for the future: `spawn(f(x.clone()))` becomes
+/// `{ let arg = x.clone(); target(move || f(arg)) }`.
+fn task_spawn_to_thread(call: &syn::ExprCall, target: &syn::Path) -> Option<syn::Expr> {
+    if call.args.len() != 1 {
+        return None;
+    }
+    let value = |i: usize| syn::Ident::new(&format!("__maybe_async_arg{}", i), Span::mixed_site());
+    Some(match &call.args[0] {
+        syn::Expr::Async(expr) => {
+            let block = &expr.block;
+            parse_quote!(#target(move || #block))
+        }
+        syn::Expr::Call(future) if !future.args.is_empty() => {
+            let func = &future.func;
+            let args = future.args.iter();
+            let values = (0..future.args.len()).map(value).collect::<Vec<_>>();
+            parse_quote!({
+                #(let #values = #args;)*
+                #target(move || #func(#(#values),*))
+            })
+        }
+        syn::Expr::MethodCall(future) => {
+            let receiver = &future.receiver;
+            let method = &future.method;
+            let turbofish = &future.turbofish;
+            let args = future.args.iter();
+            let values = (1..=future.args.len()).map(value).collect::<Vec<_>>();
+            let this = value(0);
+            parse_quote!({
+                let #this = #receiver;
+                #(let #values = #args;)*
+                #target(move || #this.#method #turbofish(#(#values),*))
+            })
+        }
+        arg => parse_quote!(#target(move || #arg)),
+    })
+}
+
+/// Returns the body of the closure passed to `spawn_blocking`, if the expression is
+/// `tokio::task::spawn_blocking(|| expr).await.unwrap()` (or `.expect(..)`, or `?`),
+/// `async_std::task::spawn_blocking(|| expr).await` or `tokio::task::block_in_place(|| expr)`.
+fn spawn_blocking_body(expr: &syn::Expr) -> Option<&syn::Expr> {
+    fn closure_body<'e>(expr: &'e syn::Expr, names: &[&str]) -> Option<&'e syn::Expr> {
+        let call = match expr {
+            syn::Expr::Call(call) if call.args.len() == 1 => call,
+            _ => return None,
+        };
+        let path = match &*call.func {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None, path, ..
+            }) => path,
+            _ => return None,
+        };
+        let path = path.to_token_stream().to_string().replace(' ', "");
+        if !names.contains(&path.trim_start_matches("::")) {
+            return None;
+        }
+        match &call.args[0] {
+            syn::Expr::Closure(closure)
+                if closure.inputs.is_empty() && closure.asyncness.is_none() =>
+            {
+                Some(&closure.body)
             }
+            _ => None,
         }
-        ConvertMode::IntoAsync => {
-            if let Some(send) = send {
-                let attr_str = if send {
-                    "async_trait::async_trait"
-                } else {
-                    "async_trait::async_trait(?Send)"
-                };
-                let attr = make_attr_from_str(attr_str, item.span()).unwrap();
-                item.attrs.push(attr);
+    }
+
+    fn awaited(expr: &syn::Expr) -> Option<&syn::Expr> {
+        match expr {
+            syn::Expr::Await(expr) => Some(&expr.base),
+            _ => None,
+        }
+    }
+
+    const TOKIO: &[&str] = &["tokio::task::spawn_blocking", "task::spawn_blocking"];
+    const ASYNC_STD: &[&str] = &["async_std::task::spawn_blocking"];
+    const IN_PLACE: &[&str] = &["tokio::task::block_in_place", "task::block_in_place"];
+
+    match expr {
+        syn::Expr::MethodCall(call) if call.method == "unwrap" || call.method == "expect" => {
+            closure_body(awaited(&call.receiver)?, TOKIO)
+        }
+        syn::Expr::Try(expr) => closure_body(awaited(&expr.expr)?, TOKIO),
+        syn::Expr::Await(expr) => closure_body(&expr.base, ASYNC_STD),
+        syn::Expr::Call(_) => closure_body(expr, IN_PLACE),
+        _ => None,
+    }
+}
+
+/// Whether the path `first::second::third` is an async lock (`tokio::sync::Mutex`,
+/// `async_std::sync::RwLock`, `futures::lock::Mutex` or one of their guards).
+fn is_async_lock(first: &syn::Ident, second: &syn::Ident, third: &syn::Ident) -> bool {
+    const LOCKS: &[&str] = &[
+        "Mutex",
+        "MutexGuard",
+        "RwLock",
+        "RwLockReadGuard",
+        "RwLockWriteGuard",
+    ];
+
+    match (first.to_string().as_str(), second.to_string().as_str()) {
+        ("tokio", "sync") | ("async_std", "sync") => LOCKS.contains(&third.to_string().as_str()),
+        ("futures", "lock") => third == "Mutex" || third == "MutexGuard",
+        _ => false,
+    }
+}
+
+/// Replaces the path prefix of an async lock with `std::sync`.
+fn lock_path_to_std(first: &mut syn::Ident, second: &mut syn::Ident) {
+    *first = syn::Ident::new("std", first.span());
+    *second = syn::Ident::new("sync", second.span());
+}
+
+/// Converts the awaited `builder.send()` into `builder.send().execute()`, if the method is listed
+/// in `into_future_map`.
+fn into_future_to_call(base: &syn::Expr, params: &MacroParameters) -> Option<syn::Expr> {
+    match base {
+        syn::Expr::MethodCall(call) => {
+            let method = params.map_into_future_get(&call.method)?;
+            Some(parse_quote!(#base.#method()))
+        }
+        _ => None,
+    }
+}
+
+/// Converts `lock.lock()` (as well as `.read()` and `.write()`) of an awaited async lock into
+/// the locking of a lock of `std`, handling the poisoned lock according to the policy.
+fn lock_call_to_std(base: &syn::Expr, policy: &str) -> Option<syn::Expr> {
+    match base {
+        syn::Expr::MethodCall(call)
+            if call.args.is_empty()
+                && (call.method == "lock" || call.method == "read" || call.method == "write") => {}
+        _ => return None,
+    }
+    Some(match policy {
+        "ignore_poison" => parse_quote!(#base.unwrap_or_else(::std::sync::PoisonError::into_inner)),
+        _ => parse_quote!(#base.unwrap()),
+    })
+}
+
+/// Checks whether the tokens of a type or an expression mention a lock, as `&Mutex<u32>` or
+/// `Arc::new(RwLock::new(0))` do.
+fn mentions_lock(tokens: TokenStream2) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == "Mutex" || ident == "RwLock",
+        proc_macro2::TokenTree::Group(group) => mentions_lock(group.stream()),
+        _ => false,
+    })
+}
+
+/// Finds the variables declared with a type or initialized with an expression mentioning a lock
+/// (not in the nested items).
+#[derive(Default)]
+struct LockBindings {
+    names: HashSet<String>,
+}
+
+impl VisitMut for LockBindings {
+    fn visit_local_mut(&mut self, node: &mut syn::Local) {
+        let (pat, ty) = match &node.pat {
+            syn::Pat::Type(pat) => (&*pat.pat, Some(&pat.ty)),
+            pat => (pat, None),
+        };
+        if let syn::Pat::Ident(pat) = pat {
+            let init = node.init.as_ref().map(|(_, expr)| expr);
+            if ty.is_some_and(|ty| mentions_lock(quote!(#ty)))
+                || init.is_some_and(|expr| mentions_lock(quote!(#expr)))
+            {
+                self.names.insert(pat.ident.to_string());
             }
         }
+        visit_mut::visit_local_mut(self, node);
     }
+
+    fn visit_item_mut(&mut self, _node: &mut syn::Item) {}
 }
 
-pub fn remove_asyncness_on_fn(item: &mut syn::ItemFn, convert_mode: ConvertMode) {
-    match convert_mode {
-        ConvertMode::IntoSync => {
-            if item.sig.asyncness.is_some() {
-                item.sig.asyncness = None;
+/// The names of the arguments and variables of a function holding locks.
+fn lock_bindings(sig: &syn::Signature, block: Option<&syn::Block>) -> HashSet<String> {
+    let mut visitor = LockBindings::default();
+    for input in &sig.inputs {
+        if let syn::FnArg::Typed(arg) = input {
+            let ty = &arg.ty;
+            if let syn::Pat::Ident(pat) = &*arg.pat {
+                if mentions_lock(quote!(#ty)) {
+                    visitor.names.insert(pat.ident.to_string());
+                }
             }
         }
-        ConvertMode::IntoAsync => {}
     }
+    if let Some(block) = block {
+        visitor.visit_block_mut(&mut block.clone());
+    }
+    visitor.names
 }
 
-impl<'p> AsyncAwaitVisitor<'p> {
-    fn process_replace_features_meta(&self, meta: &mut syn::Meta) -> syn::Result<bool> {
-        let mut changed = false;
+/// Finds the variables initialized with the join handle of a spawned task (not in the nested
+/// items), such as `let handle = tokio::spawn(..);`.
+#[derive(Default)]
+struct SpawnBindings {
+    names: HashMap<String, bool>,
+}
 
-        match meta {
-            syn::Meta::NameValue(syn::MetaNameValue {
-                path,
-                lit: syn::Lit::Str(s),
-                ..
-            }) => {
-                if let Some(ident) = path.get_ident() {
-                    if ident.to_string() == "feature" {
-                        let prev = s.value();
-                        if let Some(new) = self.params.replace_features_get(&prev) {
-                            *s = syn::LitStr::new(new, s.span());
-                            changed = true;
-                        }
-                    }
+impl VisitMut for SpawnBindings {
+    fn visit_local_mut(&mut self, node: &mut syn::Local) {
+        let pat = match &node.pat {
+            syn::Pat::Type(pat) => &*pat.pat,
+            pat => pat,
+        };
+        if let (syn::Pat::Ident(pat), Some((_, init))) = (pat, &node.init) {
+            match &**init {
+                syn::Expr::Call(call) if is_task_spawn(&call.func) => {
+                    self.names
+                        .insert(pat.ident.to_string(), is_async_std_spawn(&call.func));
                 }
-            }
-            syn::Meta::List(list) => {
-                for nm in &mut list.nested {
-                    if let syn::NestedMeta::Meta(m) = nm {
-                        changed |= self.process_replace_features_meta(m)?;
-                    }
+                // a variable declared again no longer holds the handle
+                _ => {
+                    self.names.remove(&pat.ident.to_string());
                 }
             }
-            _ => {}
         }
-
-        Ok(changed)
+        visit_mut::visit_local_mut(self, node);
     }
 
-    fn process_attribute_if(&mut self, attr: &mut syn::Attribute, not: bool) -> syn::Result<()> {
-        let args =
-            syn::parse_macro_input::parse::<AttributeArgsInParens>(attr.tokens.clone().into())?;
+    fn visit_item_mut(&mut self, _node: &mut syn::Item) {}
+}
 
-        let arg = match &args.args.len() {
-            0 => {
-                return Err(syn::Error::new_spanned(
-                    attr.to_token_stream(),
-                    "Expected ident",
-                ))
+/// The names of the variables of a function holding join handles, with whether the tasks are
+/// spawned by `async_std`.
+fn spawn_bindings(block: &syn::Block) -> HashMap<String, bool> {
+    let mut visitor = SpawnBindings::default();
+    visitor.visit_block_mut(&mut block.clone());
+    visitor.names
+}
+
+/// Replaces the prefix of the path found in the path mapping table with its target, keeping the
+/// generic arguments.
+fn path_map_apply(node: &mut syn::Path, path_map: &[(Vec<String>, syn::Path)]) {
+    let (from, to) = match path_map.iter().find(|(from, _)| {
+        from.len() <= node.segments.len()
+            && from
+                .iter()
+                .zip(&node.segments)
+                .all(|(name, segment)| segment.ident == name)
+    }) {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    let mut segments = to.segments.clone();
+    let mut rest = node.segments.iter().skip(from.len() - 1).cloned();
+    if let (Some(last), Some(matched)) = (segments.last_mut(), rest.next()) {
+        last.arguments = matched.arguments;
+    }
+    segments.extend(rest);
+    node.leading_colon = to.leading_colon;
+    node.segments = segments;
+}
+
+/// Collects the paths imported by the `use` tree with their names (`None` for globs).
+fn flatten_use_tree(
+    tree: &syn::UseTree,
+    prefix: &mut Vec<syn::Ident>,
+    paths: &mut Vec<(Vec<syn::Ident>, Option<syn::Ident>)>,
+) {
+    match tree {
+        syn::UseTree::Path(path) => {
+            prefix.push(path.ident.clone());
+            flatten_use_tree(&path.tree, prefix, paths);
+            prefix.pop();
+        }
+        syn::UseTree::Name(syn::UseName { ident })
+        | syn::UseTree::Rename(syn::UseRename { ident, .. }) => {
+            let mut path = prefix.clone();
+            if ident != "self" {
+                path.push(ident.clone());
             }
-            1 => &args.args[0],
-            _ => {
-                return Err(syn::Error::new_spanned(
-                    args.args[1].to_token_stream(),
-                    "Too many arguments",
-                ))
+            let name = match tree {
+                syn::UseTree::Rename(rename) => Some(rename.rename.clone()),
+                _ => path.last().cloned(),
+            };
+            paths.push((path, name));
+        }
+        syn::UseTree::Glob(_) => paths.push((prefix.clone(), None)),
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                flatten_use_tree(item, prefix, paths);
             }
+        }
+    }
+}
+
+/// Returns the entries of the path mapping table for the paths through the modules imported by the
+/// `use` tree, such as `mpsc::channel` after `use tokio::sync::mpsc`. If all the items of the
+/// module found in the table are mapped to the items of one module, the import is mapped to this
+/// module (`use std::sync::mpsc`) and the paths keep the imported name (`mpsc::sync_channel`),
+/// otherwise the paths are replaced with their full targets.
+fn use_tree_aliases(
+    node: &syn::UseTree,
+    path_map: &[(Vec<String>, syn::Path)],
+) -> Vec<(Vec<String>, syn::Path)> {
+    let mut paths = vec![];
+    flatten_use_tree(node, &mut vec![], &mut paths);
+    let mut aliases = vec![];
+    for (path, name) in paths {
+        let name = match name {
+            Some(name) => name,
+            None => continue,
         };
+        let entries = path_map
+            .iter()
+            .filter(|(from, _)| {
+                from.len() > path.len() && from.iter().zip(&path).all(|(a, b)| b == a)
+            })
+            .collect::<Vec<_>>();
+        // the longest module containing all the targets
+        let mut module = match entries.first() {
+            Some((_, to)) => to.clone(),
+            None => continue,
+        };
+        for (_, to) in &entries {
+            let len = module
+                .segments
+                .iter()
+                .zip(to.segments.iter().take(to.segments.len() - 1))
+                .take_while(|(a, b)| a.ident == b.ident)
+                .count();
+            module.segments = module.segments.iter().take(len).cloned().collect();
+        }
+        for (from, to) in &entries {
+            let alias = std::iter::once(name.to_string())
+                .chain(from[path.len()..].iter().cloned())
+                .collect();
+            let to = match module.segments.len() {
+                0 => to.clone(),
+                len => {
+                    let rest = to.segments.iter().skip(len);
+                    parse_quote!(#name #(::#rest)*)
+                }
+            };
+            aliases.push((alias, to));
+        }
+        if !module.segments.is_empty() {
+            let path = path.iter().map(|ident| ident.to_string()).collect();
+            aliases.push((path, module));
+        }
+    }
+    aliases
+}
 
-        let key = match arg {
-            syn::NestedMeta::Lit(syn::Lit::Str(s)) => s.value(),
-            syn::NestedMeta::Meta(syn::Meta::Path(ref p)) => {
-                if let Some(s) = p.get_ident() {
-                    s.to_string()
+/// Replaces the prefixes of the paths imported by the `use` tree found in the path mapping table
+/// with their targets. The imported items keep their names (`use tokio::sync::mpsc::Sender` becomes
+/// `use std::sync::mpsc::SyncSender as Sender`). If an item of a group is replaced, the tree
+/// becomes a group of all its paths (`use tokio::io::{AsyncReadExt, BufReader}` becomes
+/// `use {std::io::Read as AsyncReadExt, std::io::BufReader}`).
+fn use_tree_map_apply(node: &mut syn::UseTree, path_map: &[(Vec<String>, syn::Path)]) {
+    let mut paths = vec![];
+    flatten_use_tree(node, &mut vec![], &mut paths);
+    let mut replaced = false;
+    for (path, _) in &mut paths {
+        if let Some((from, to)) = path_map.iter().find(|(from, _)| {
+            from.len() <= path.len() && from.iter().zip(path.iter()).all(|(a, b)| b == a)
+        }) {
+            *path = to
+                .segments
+                .iter()
+                .map(|segment| segment.ident.clone())
+                .chain(path.drain(from.len()..))
+                .collect();
+            replaced = true;
+        }
+    }
+    if !replaced {
+        return;
+    }
+
+    let mut trees = paths.into_iter().filter_map(|(mut path, name)| {
+        let tree = match name {
+            Some(name) => {
+                let ident = path.pop()?;
+                if ident == name {
+                    syn::UseTree::Name(syn::UseName { ident })
                 } else {
-                    return Err(syn::Error::new_spanned(
-                        arg.to_token_stream(),
-                        "Wrong ident",
-                    ));
+                    syn::UseTree::Rename(syn::UseRename {
+                        ident,
+                        as_token: Default::default(),
+                        rename: name,
+                    })
                 }
             }
-            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
-                path,
-                lit: syn::Lit::Str(value),
+            None => syn::UseTree::Glob(syn::UseGlob {
+                star_token: Default::default(),
+            }),
+        };
+        Some(path.into_iter().rev().fold(tree, |tree, ident| {
+            syn::UseTree::Path(syn::UsePath {
+                ident,
+                colon2_token: Default::default(),
+                tree: Box::new(tree),
+            })
+        }))
+    });
+    *node = match (trees.next(), trees.next()) {
+        (Some(tree), None) => tree,
+        (first, second) => syn::UseTree::Group(syn::UseGroup {
+            brace_token: Default::default(),
+            items: first.into_iter().chain(second).chain(trees).collect(),
+        }),
+    };
+}
+
+/// Converts the awaited `.recv()` and `.send(x)` calls of an async channel into the calls of a
+/// channel of `std`.
+fn channel_call_to_std(base: &syn::Expr) -> Option<syn::Expr> {
+    match base {
+        syn::Expr::MethodCall(call) if call.method == "recv" && call.args.is_empty() => {
+            Some(parse_quote!(#base.ok()))
+        }
+        syn::Expr::MethodCall(call) if call.method == "send" && call.args.len() == 1 => {
+            Some(base.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Whether the macro is `select!` or `select_biased!` (of `futures` or `tokio`, called by the
+/// path).
+fn is_select_macro(mac: &syn::Macro) -> bool {
+    let segments = &mac.path.segments;
+    let name = match segments.len() {
+        2 if segments[0].ident == "futures" || segments[0].ident == "tokio" => &segments[1].ident,
+        _ => return false,
+    };
+    name == "select" || name == "select_biased"
+}
+
+fn select_macro_error(mac: &syn::Macro) -> syn::Error {
+    syn::Error::new_spanned(
+        mac,
+        "`select!` cannot be converted to sync code, mark the statement containing it with \
+        `only_if(async)` and provide the sync code in a statement marked with `only_if(sync)`",
+    )
+}
+
+/// Returns the attributes of the expression.
+fn expr_attrs_mut(expr: &mut syn::Expr) -> Option<&mut Vec<syn::Attribute>> {
+    macro_rules! attrs {
+        ($($variant:ident)*) => {
+            match expr {
+                $(syn::Expr::$variant(expr) => Some(&mut expr.attrs),)*
+                _ => None,
+            }
+        };
+    }
+
+    attrs!(
+        Array Assign AssignOp Async Await Binary Block Box Break Call Cast Closure Continue Field
+        ForLoop Group If Index Let Lit Loop Macro Match MethodCall Paren Path Range Reference
+        Repeat Return Struct Try TryBlock Tuple Type Unary Unsafe While Yield
+    )
+}
+
+/// Collects the lifetimes mentioned in the tokens.
+fn collect_lifetimes(tokens: TokenStream2, lifetimes: &mut HashSet<String>) {
+    let mut quote = false;
+    for token in tokens {
+        match token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                quote = true;
+                continue;
+            }
+            proc_macro2::TokenTree::Ident(ident) if quote => {
+                lifetimes.insert(ident.to_string());
+            }
+            proc_macro2::TokenTree::Group(group) => collect_lifetimes(group.stream(), lifetimes),
+            _ => {}
+        }
+        quote = false;
+    }
+}
+
+/// The lifetimes used by a function outside of its generics: in the arguments, the return type
+/// and the body.
+fn lifetimes_used(sig: &syn::Signature, block: Option<&syn::Block>) -> HashSet<String> {
+    let mut lifetimes = HashSet::new();
+    let inputs = &sig.inputs;
+    let output = &sig.output;
+    collect_lifetimes(quote!(#inputs #output #block), &mut lifetimes);
+    lifetimes
+}
+
+/// Removes the lifetime parameters with the given names, and the bounds mentioning them.
+fn prune_lifetimes(generics: &mut syn::Generics, names: &[String]) {
+    let pruned = |lifetime: &syn::Lifetime| names.contains(&lifetime.ident.to_string());
+    let prune_bounds =
+        |bounds: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>| {
+            *bounds = std::mem::take(bounds)
+                .into_iter()
+                .filter(|bound| !matches!(bound, syn::TypeParamBound::Lifetime(lt) if pruned(lt)))
+                .collect();
+        };
+
+    generics.params = std::mem::take(&mut generics.params)
+        .into_iter()
+        .filter_map(|mut param| {
+            match &mut param {
+                syn::GenericParam::Lifetime(def) => {
+                    if pruned(&def.lifetime) {
+                        return None;
+                    }
+                    def.bounds = std::mem::take(&mut def.bounds)
+                        .into_iter()
+                        .filter(|lt| !pruned(lt))
+                        .collect();
+                    if def.bounds.is_empty() {
+                        def.colon_token = None;
+                    }
+                }
+                syn::GenericParam::Type(param) => {
+                    prune_bounds(&mut param.bounds);
+                    if param.bounds.is_empty() {
+                        param.colon_token = None;
+                    }
+                }
+                syn::GenericParam::Const(_) => {}
+            }
+            Some(param)
+        })
+        .collect();
+    if generics.params.is_empty() {
+        generics.lt_token = None;
+        generics.gt_token = None;
+    }
+
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+            .into_iter()
+            .filter_map(|mut predicate| {
+                match &mut predicate {
+                    syn::WherePredicate::Lifetime(predicate) => {
+                        if pruned(&predicate.lifetime) {
+                            return None;
+                        }
+                        predicate.bounds = std::mem::take(&mut predicate.bounds)
+                            .into_iter()
+                            .filter(|lt| !pruned(lt))
+                            .collect();
+                        if predicate.bounds.is_empty() {
+                            return None;
+                        }
+                    }
+                    syn::WherePredicate::Type(predicate) => {
+                        prune_bounds(&mut predicate.bounds);
+                        if predicate.bounds.is_empty() {
+                            return None;
+                        }
+                    }
+                    syn::WherePredicate::Eq(_) => {}
+                }
+                Some(predicate)
+            })
+            .collect();
+        if where_clause.predicates.is_empty() {
+            generics.where_clause = None;
+        }
+    }
+}
+
+/// Finds `return` and `?` leaving an async block (not the nested closures, async blocks and items,
+/// and `?` not inside the nested `try` blocks).
+#[derive(Default)]
+struct EarlyExit {
+    found: bool,
+    try_depth: usize,
+}
+
+impl VisitMut for EarlyExit {
+    fn visit_expr_mut(&mut self, node: &mut syn::Expr) {
+        match node {
+            syn::Expr::Return(_) => self.found = true,
+            syn::Expr::Try(_) if self.try_depth == 0 => self.found = true,
+            syn::Expr::TryBlock(_) => {
+                self.try_depth += 1;
+                visit_mut::visit_expr_mut(self, node);
+                self.try_depth -= 1;
+                return;
+            }
+            syn::Expr::Closure(_) | syn::Expr::Async(_) => return,
+            syn::Expr::Verbatim(tokens) => {
+                visit_verbatim_mut(self, tokens);
+                return;
+            }
+            _ => {}
+        }
+        visit_mut::visit_expr_mut(self, node);
+    }
+
+    fn visit_item_mut(&mut self, _node: &mut syn::Item) {}
+}
+
+/// Checks whether the block contains `return` or `?`, which would leave the enclosing function
+/// instead of the block if the async block were replaced with a plain one.
+fn has_early_exit(block: &syn::Block) -> bool {
+    let mut visitor = EarlyExit::default();
+    visitor.visit_block_mut(&mut block.clone());
+    visitor.found
+}
+
+/// Finds `.await` left outside of async functions, closures and blocks, including the tokens of
+/// the macros not processed by the conversion.
+#[derive(Default)]
+struct LeftAwait {
+    span: Option<Span>,
+}
+
+impl VisitMut for LeftAwait {
+    fn visit_expr_mut(&mut self, node: &mut syn::Expr) {
+        if self.span.is_some() {
+            return;
+        }
+        match node {
+            syn::Expr::Await(expr) => self.span = Some(expr.await_token.span),
+            syn::Expr::Async(_) => {}
+            syn::Expr::Closure(expr) if expr.asyncness.is_some() => {}
+            syn::Expr::Verbatim(tokens) => {
+                visit_verbatim_mut(self, tokens);
+            }
+            _ => visit_mut::visit_expr_mut(self, node),
+        }
+    }
+
+    fn visit_item_fn_mut(&mut self, node: &mut syn::ItemFn) {
+        if node.sig.asyncness.is_none() {
+            visit_mut::visit_item_fn_mut(self, node);
+        }
+    }
+
+    fn visit_impl_item_method_mut(&mut self, node: &mut syn::ImplItemMethod) {
+        if node.sig.asyncness.is_none() {
+            visit_mut::visit_impl_item_method_mut(self, node);
+        }
+    }
+
+    fn visit_trait_item_method_mut(&mut self, node: &mut syn::TraitItemMethod) {
+        if node.sig.asyncness.is_none() {
+            visit_mut::visit_trait_item_method_mut(self, node);
+        }
+    }
+
+    fn visit_macro_mut(&mut self, node: &mut syn::Macro) {
+        if self.span.is_none() {
+            self.span = find_await_tokens(node.tokens.clone());
+        }
+    }
+}
+
+/// Finds `.await` in the tokens, skipping the async blocks and closures.
+fn find_await_tokens(ts: TokenStream2) -> Option<Span> {
+    let mut tokens = ts.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            proc_macro2::TokenTree::Ident(ident) if ident == "async" => {
+                // skip everything up to the body of the async block or closure
+                for token in tokens.by_ref() {
+                    if let proc_macro2::TokenTree::Group(group) = token {
+                        if group.delimiter() == proc_macro2::Delimiter::Brace {
+                            break;
+                        }
+                    }
+                }
+            }
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '.' => {
+                if let Some(proc_macro2::TokenTree::Ident(ident)) = tokens.peek() {
+                    if ident == "await" {
+                        return Some(ident.span());
+                    }
+                }
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                if let Some(span) = find_await_tokens(group.stream()) {
+                    return Some(span);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Checks that no `.await` is left in the sync variant, which happens when it is hidden inside a
+/// macro the conversion does not look into.
+pub fn check_left_await(file: &syn::File) -> syn::Result<()> {
+    let mut visitor = LeftAwait::default();
+    visitor.visit_file_mut(&mut file.clone());
+    match visitor.span {
+        Some(span) => Err(syn::Error::new(
+            span,
+            "`.await` is left in the sync variant generated by `maybe`, probably inside a macro \
+            which is not converted; move it out of the macro or mark it with `only_if(async)`",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The `stream!` macro has no lazy counterpart in sync code, so the one to use has to be specified.
+fn stream_macro_error(mac: &syn::Macro) -> syn::Error {
+    syn::Error::new_spanned(
+        mac,
+        "The `stream!` macro cannot be converted in the sync variant generated by `maybe`; specify \
+        the macro generating an iterator with the `stream_macro` parameter or mark it with \
+        `only_if(async)`",
+    )
+}
+
+/// Converts the `while let Some(x) = stream.next().await { .. }` loop into
+/// `for x in stream.by_ref() { .. }`, which borrows the stream the same way.
+fn stream_loop_to_for(expr: &syn::ExprWhile) -> Option<syn::Expr> {
+    let (pat, scrutinee) = match &*expr.cond {
+        syn::Expr::Let(syn::ExprLet { pat, expr, .. }) => (pat, expr),
+        _ => return None,
+    };
+    let pat = match pat {
+        syn::Pat::TupleStruct(pat) if pat.path.is_ident("Some") && pat.pat.elems.len() == 1 => {
+            &pat.pat.elems[0]
+        }
+        _ => return None,
+    };
+    let receiver = match &**scrutinee {
+        syn::Expr::Await(syn::ExprAwait { base, .. }) => match &**base {
+            syn::Expr::MethodCall(call)
+                if call.method == "next" && call.args.is_empty() && call.turbofish.is_none() =>
+            {
+                &call.receiver
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let attrs = &expr.attrs;
+    let label = &expr.label;
+    let body = &expr.body;
+    Some(parse_quote!(#(#attrs)* #label for #pat in #receiver.by_ref() #body))
+}
+
+/// Returns the future passed to an executor, if the expression is `block_on(future)` (as in
+/// `futures::executor::block_on(..)` or `pollster::block_on(..)`) or `runtime.block_on(future)`.
+fn block_on_arg(expr: &syn::Expr) -> Option<&syn::Expr> {
+    let (name, args) = match expr {
+        syn::Expr::Call(syn::ExprCall { func, args, .. }) => match &**func {
+            syn::Expr::Path(syn::ExprPath { path, .. }) => (&path.segments.last()?.ident, args),
+            _ => return None,
+        },
+        syn::Expr::MethodCall(syn::ExprMethodCall { method, args, .. }) => (method, args),
+        _ => return None,
+    };
+
+    if name == "block_on" && args.len() == 1 {
+        args.first()
+    } else {
+        None
+    }
+}
+
+/// Returns `T` of the `Stream<Item = T>` bound.
+fn search_stream_item(path: &syn::Path) -> Option<syn::Type> {
+    let segment = path.segments.last()?;
+    if segment.ident != "Stream" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Binding(binding) if binding.ident == "Item" => {
+                Some(binding.ty.clone())
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the `std::io` analogue of the `AsyncRead`, `AsyncWrite`, `AsyncBufRead` or `AsyncSeek`
+/// bound.
+fn async_io_trait_to_std(path: &syn::Path) -> Option<syn::Path> {
+    let segment = path.segments.last()?;
+    if !segment.arguments.is_empty() {
+        return None;
+    }
+    Some(match segment.ident.to_string().as_str() {
+        "AsyncRead" => parse_quote!(::std::io::Read),
+        "AsyncWrite" => parse_quote!(::std::io::Write),
+        "AsyncBufRead" => parse_quote!(::std::io::BufRead),
+        "AsyncSeek" => parse_quote!(::std::io::Seek),
+        _ => return None,
+    })
+}
+
+/// Converts the bounds `AsyncFn(A) -> R`, `AsyncFnMut(A) -> R` and `AsyncFnOnce(A) -> R` (also
+/// through `std::ops` or `core::ops`) into `Fn(A) -> R`, `FnMut(A) -> R` and `FnOnce(A) -> R`.
+fn async_fn_trait_to_fn(path: &syn::Path) -> Option<syn::Path> {
+    let segment = path.segments.last()?;
+    let ident = match segment.ident.to_string().as_str() {
+        "AsyncFn" => quote::format_ident!("Fn", span = segment.ident.span()),
+        "AsyncFnMut" => quote::format_ident!("FnMut", span = segment.ident.span()),
+        "AsyncFnOnce" => quote::format_ident!("FnOnce", span = segment.ident.span()),
+        _ => return None,
+    };
+    let prefix = path
+        .segments
+        .iter()
+        .take(path.segments.len() - 1)
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>();
+    let prefix = prefix.iter().map(String::as_str).collect::<Vec<_>>();
+    if !matches!(prefix.as_slice(), [] | ["std", "ops"] | ["core", "ops"]) {
+        return None;
+    }
+    Some(
+        syn::PathSegment {
+            ident,
+            arguments: segment.arguments.clone(),
+        }
+        .into(),
+    )
+}
+
+/// Converts `BoxStream<'a, T>` into `Box<dyn Iterator<Item = T> + Send + 'a>` and
+/// `LocalBoxStream<'a, T>` into `Box<dyn Iterator<Item = T> + 'a>`.
+fn box_stream_to_iter(ty: &syn::Type) -> Option<syn::Type> {
+    let path = match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    let send = match segment.ident.to_string().as_str() {
+        "BoxStream" => quote!(+ Send),
+        "LocalBoxStream" => quote!(),
+        _ => return None,
+    };
+    let args = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => &args.args,
+        _ => return None,
+    };
+    let lifetime = args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Lifetime(lifetime) => Some(quote!(+ #lifetime)),
+        _ => None,
+    });
+    let item = args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })?;
+
+    Some(parse_quote!(Box<dyn Iterator<Item = #item> #send #lifetime>))
+}
+
+/// Returns `T` of the future types `impl Future<Output = T> + ..`,
+/// `Pin<Box<dyn Future<Output = T> + ..>>`, `BoxFuture<'a, T>`, `LocalBoxFuture<'a, T>` and
+/// `Ready<T>`. The other bounds (`Send`, lifetimes) have no meaning for `T` and are dropped.
+fn future_output_type(ty: &syn::Type) -> Option<syn::Type> {
+    fn type_args(ty: &syn::Type) -> Option<(String, Vec<&syn::Type>)> {
+        let segment = match ty {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => path.segments.last()?,
+            _ => return None,
+        };
+        let args = match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => return None,
+        };
+        Some((segment.ident.to_string(), args))
+    }
+
+    fn trait_object_output(ty: &syn::Type) -> Option<syn::Type> {
+        match ty {
+            syn::Type::TraitObject(object) => future_bounds_output(&object.bounds),
+            syn::Type::Paren(syn::TypeParen { elem, .. })
+            | syn::Type::Group(syn::TypeGroup { elem, .. }) => trait_object_output(elem),
+            _ => None,
+        }
+    }
+
+    if let syn::Type::ImplTrait(ty) = ty {
+        return future_bounds_output(&ty.bounds);
+    }
+    let (name, args) = type_args(ty)?;
+    match (name.as_str(), args.as_slice()) {
+        ("BoxFuture", [output]) | ("LocalBoxFuture", [output]) | ("Ready", [output]) => {
+            Some((*output).clone())
+        }
+        ("Pin", [boxed]) => match type_args(boxed)? {
+            (name, args) if name == "Box" && args.len() == 1 => trait_object_output(args[0]),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Removes the type parameters bounded by `Future<Output = T>` (in the parameters or in the `where`
+/// clause) along with their `where` predicates, returns their output types by their names. So
+/// `<F: Fn(Request) -> Fut, Fut: Future<Output = Response>>` becomes `<F: Fn(Request) -> Response>`
+/// once the uses of `Fut` are replaced. The other bounds of such parameters (`Send`, `'static`,
+/// but not `Unpin`) are kept on the output type only if it is a type parameter itself:
+/// `Fut: Future<Output = T> + Send` becomes `where T: Send`, they are dropped otherwise.
+fn take_future_generics(generics: &mut syn::Generics) -> HashMap<String, syn::Type> {
+    let mut outputs = HashMap::new();
+    let mut kept = Vec::<syn::WherePredicate>::new();
+    let type_params = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Type(param) => Some(param.ident.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut keep_bounds =
+        |output: &syn::Type,
+         bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>| {
+            // the bounds of a future say nothing about its concrete output type
+            let is_type_param = matches!(
+                output,
+                syn::Type::Path(syn::TypePath { qself: None, path })
+                    if matches!(path.get_ident(), Some(ident) if type_params.contains(ident))
+            );
+            if !is_type_param {
+                return;
+            }
+            let bounds: syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add> = bounds
+                .iter()
+                .filter(|bound| match bound {
+                    syn::TypeParamBound::Trait(bound) => {
+                        search_future_output(&bound.path).is_none() && !bound.path.is_ident("Unpin")
+                    }
+                    syn::TypeParamBound::Lifetime(_) => true,
+                })
+                .cloned()
+                .collect();
+            if !bounds.is_empty() {
+                kept.push(syn::parse_quote!(#output: #bounds));
+            }
+        };
+    for param in &generics.params {
+        if let syn::GenericParam::Type(param) = param {
+            if let Some(output) = future_bounds_output(&param.bounds) {
+                keep_bounds(&output, &param.bounds);
+                outputs.insert(param.ident.to_string(), output);
+            }
+        }
+    }
+    let bounded_param = |ty: &syn::Type| match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path
+            .get_ident()
+            .filter(|ident| {
+                generics.params.iter().any(|param| {
+                    matches!(param, syn::GenericParam::Type(param) if param.ident == **ident)
+                })
+            })
+            .map(|ident| ident.to_string()),
+        _ => None,
+    };
+    if let Some(where_clause) = &generics.where_clause {
+        for predicate in &where_clause.predicates {
+            if let syn::WherePredicate::Type(predicate) = predicate {
+                if let (Some(name), Some(output)) = (
+                    bounded_param(&predicate.bounded_ty),
+                    future_bounds_output(&predicate.bounds),
+                ) {
+                    keep_bounds(&output, &predicate.bounds);
+                    outputs.insert(name, output);
+                }
+            }
+        }
+    }
+    if outputs.is_empty() {
+        return outputs;
+    }
+
+    generics.params = std::mem::take(&mut generics.params)
+        .into_iter()
+        .filter(|param| match param {
+            syn::GenericParam::Type(param) => !outputs.contains_key(&param.ident.to_string()),
+            _ => true,
+        })
+        .collect();
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+            .into_iter()
+            .filter(|predicate| match predicate {
+                syn::WherePredicate::Type(predicate) => match &predicate.bounded_ty {
+                    syn::Type::Path(syn::TypePath { qself: None, path }) => !matches!(
+                        path.get_ident(), Some(ident) if outputs.contains_key(&ident.to_string())
+                    ),
+                    _ => true,
+                },
+                _ => true,
+            })
+            .collect();
+        if where_clause.predicates.is_empty() {
+            generics.where_clause = None;
+        }
+    }
+    if !kept.is_empty() {
+        generics.make_where_clause().predicates.extend(kept);
+    }
+    outputs
+}
+
+/// Returns `T` of the bounds `Future<Output = T> + ..`.
+fn future_bounds_output(
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>,
+) -> Option<syn::Type> {
+    bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(bound) => search_future_output(&bound.path),
+        _ => None,
+    })
+}
+
+/// Returns `T` of the `Future<Output = T>` bound.
+fn search_future_output(path: &syn::Path) -> Option<syn::Type> {
+    let segment = path.segments.last()?;
+    if segment.ident != "Future" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Binding(binding) if binding.ident == "Output" => {
+                Some(binding.ty.clone())
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Whether the attribute is `async_trait` (by its last segment) or the one at the given path.
+fn is_async_trait_attr(attr: &syn::Attribute, async_trait: &str) -> bool {
+    let path = attr.path.to_token_stream().to_string().replace(' ', "");
+    path.trim_start_matches("::") == async_trait.trim_start_matches("::")
+        || matches!(attr.path.segments.last(), Some(last) if last.ident == "async_trait")
+}
+
+pub fn remove_asyncness_on_trait(
+    item: &mut syn::ItemTrait,
+    convert_mode: ConvertMode,
+    send: Option<bool>,
+    async_trait: &str,
+) {
+    match convert_mode {
+        // asyncness of the methods is removed by the visitor, see `asyncness_removal`
+        ConvertMode::IntoSync => {}
+        ConvertMode::IntoAsync => {
+            if let Some(send) = send {
+                let attr_str = if send {
+                    async_trait.to_string()
+                } else {
+                    format!("{}(?Send)", async_trait)
+                };
+                let attr = make_attr_from_str(attr_str, item.span()).unwrap();
+                // the attribute already present is replaced, not duplicated
+                item.attrs
+                    .retain(|attr| !is_async_trait_attr(attr, async_trait));
+                item.attrs.push(attr);
+            }
+        }
+        ConvertMode::IntoDyn => {
+            for inner in &mut item.items {
+                if let syn::TraitItem::Method(ref mut method) = inner {
+                    if method.sig.asyncness.is_some() {
+                        make_dyn_method(method, send != Some(false));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn remove_asyncness_on_impl(
+    item: &mut syn::ItemImpl,
+    convert_mode: ConvertMode,
+    send: Option<bool>,
+    async_trait: &str,
+) {
+    match convert_mode {
+        // asyncness of the methods is removed by the visitor, see `asyncness_removal`
+        ConvertMode::IntoSync | ConvertMode::IntoDyn => {}
+        ConvertMode::IntoAsync => {
+            if let Some(send) = send {
+                let attr_str = if send {
+                    async_trait.to_string()
+                } else {
+                    format!("{}(?Send)", async_trait)
+                };
+                let attr = make_attr_from_str(attr_str, item.span()).unwrap();
+                // the attribute already present is replaced, not duplicated
+                item.attrs
+                    .retain(|attr| !is_async_trait_attr(attr, async_trait));
+                item.attrs.push(attr);
+            }
+        }
+    }
+}
+
+/// Lifetime of the futures returned by the methods of the `dyn` variant of a trait.
+const DYN_LIFETIME: &str = "'maybe_async";
+
+/// Gives all elided lifetimes in a type the same explicit name.
+struct NameElidedLifetimes(syn::Lifetime);
+
+impl VisitMut for NameElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, node: &mut syn::TypeReference) {
+        if node.lifetime.is_none() {
+            node.lifetime = Some(self.0.clone());
+        }
+        visit_mut::visit_type_reference_mut(self, node);
+    }
+
+    fn visit_lifetime_mut(&mut self, node: &mut syn::Lifetime) {
+        if node.ident == "_" {
+            *node = self.0.clone();
+        }
+    }
+
+    // elided lifetimes of fn pointers and `Fn` traits belong to them
+    fn visit_type_bare_fn_mut(&mut self, _node: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _node: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
+/// Turns `async fn f(&self) -> T` into `fn f<'maybe_async>(&'maybe_async self) -> Pin<Box<dyn
+/// Future<Output = T> + Send + 'maybe_async>>`, boxing the default body if any.
+fn make_dyn_method(method: &mut syn::TraitItemMethod, send: bool) {
+    let lifetime = syn::Lifetime::new(DYN_LIFETIME, Span::call_site());
+    let sig = &mut method.sig;
+    sig.asyncness = None;
+
+    for input in &mut sig.inputs {
+        match input {
+            syn::FnArg::Receiver(receiver) => {
+                if let Some((_, ref mut receiver_lifetime @ None)) = receiver.reference {
+                    *receiver_lifetime = Some(lifetime.clone());
+                }
+            }
+            syn::FnArg::Typed(arg) => {
+                NameElidedLifetimes(lifetime.clone()).visit_type_mut(&mut arg.ty)
+            }
+        }
+    }
+
+    let output = match &sig.output {
+        syn::ReturnType::Default => parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => {
+            let mut ty = (**ty).clone();
+            NameElidedLifetimes(lifetime.clone()).visit_type_mut(&mut ty);
+            ty
+        }
+    };
+    let send_bound = if send {
+        quote!(+ ::std::marker::Send)
+    } else {
+        quote!()
+    };
+    sig.output = parse_quote!(
+        -> ::std::pin::Pin<::std::boxed::Box<
+            dyn ::std::future::Future<Output = #output> #send_bound + #lifetime
+        >>
+    );
+
+    // everything the future borrows has to outlive it
+    let outlives = sig
+        .generics
+        .lifetimes()
+        .map(|param| {
+            let param = &param.lifetime;
+            parse_quote!(#param: #lifetime)
+        })
+        .collect::<Vec<syn::WherePredicate>>();
+    let where_clause = sig.generics.make_where_clause();
+    where_clause.predicates.extend(outlives);
+    where_clause.predicates.push(parse_quote!(Self: #lifetime));
+    sig.generics.params.insert(0, parse_quote!(#lifetime));
+
+    if let Some(block) = &mut method.default {
+        // the default body borrows `self` in a `Send` future
+        if send {
+            add_receiver_send_bound(sig);
+        }
+
+        let body = &*block;
+        *block = parse_quote!({ ::std::boxed::Box::pin(async move #body) });
+    }
+}
+
+/// Adds the bound on `Self` needed by a `Send` future borrowing `self` to the default method of a
+/// trait: `Sync` for `&self`, `Send` for `&mut self` and `self`.
+fn add_receiver_send_bound(sig: &mut syn::Signature) {
+    let bound = match sig.receiver() {
+        Some(syn::FnArg::Receiver(syn::Receiver {
+            reference: Some(_),
+            mutability: None,
+            ..
+        })) => quote!(::std::marker::Sync),
+        Some(_) => quote!(::std::marker::Send),
+        None => return,
+    };
+    let where_clause = sig.generics.make_where_clause();
+    where_clause.predicates.push(parse_quote!(Self: #bound));
+}
+
+/// Turns the signature `async fn f(&self) -> R` of a trait method into `fn f(&self) -> impl
+/// Future<Output = R> + Send`, the future captures all the lifetimes of the method in traits and
+/// their impl blocks.
+fn make_impl_future_sig(sig: &mut syn::Signature, send: bool) {
+    sig.asyncness = None;
+    let output = match &sig.output {
+        syn::ReturnType::Default => parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+    let send_bound = if send {
+        quote!(+ ::std::marker::Send)
+    } else {
+        quote!()
+    };
+    sig.output = parse_quote!(-> impl ::std::future::Future<Output = #output> #send_bound);
+}
+
+/// Whether the function returns a future or a stream: is async, or returns `impl Future<..>` or
+/// `impl Stream<..>`.
+fn returns_future(sig: &syn::Signature) -> bool {
+    sig.asyncness.is_some()
+        || matches!(&sig.output, syn::ReturnType::Type(_, ty) if is_impl_future(ty))
+}
+
+fn is_impl_future(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::ImplTrait(ty) => ty.bounds.iter().any(|bound| {
+            matches!(bound, syn::TypeParamBound::Trait(bound) if matches!(
+                bound.path.segments.last(),
+                Some(last) if last.ident == "Future" || last.ident == "Stream"
+            ))
+        }),
+        _ => false,
+    }
+}
+
+/// Whether the bounds contain the trait with the given name (the last segment of its path).
+fn has_trait_bound(
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>,
+    name: &str,
+) -> bool {
+    bounds.iter().any(|bound| {
+        matches!(bound, syn::TypeParamBound::Trait(bound) if matches!(
+            bound.path.segments.last(), Some(last) if last.ident == name
+        ))
+    })
+}
+
+/// Adds the `Send + Sync` markers to a trait object, or strips them (`dyn_markers`). The futures
+/// and streams (`dyn Future<..> + Send`) are left as is.
+fn apply_dyn_markers(ty: &mut syn::TypeTraitObject, add: bool) {
+    if has_trait_bound(&ty.bounds, "Future") || has_trait_bound(&ty.bounds, "Stream") {
+        return;
+    }
+    if add {
+        if !has_trait_bound(&ty.bounds, "Send") {
+            ty.bounds.push(parse_quote!(::std::marker::Send));
+        }
+        if !has_trait_bound(&ty.bounds, "Sync") {
+            ty.bounds.push(parse_quote!(::std::marker::Sync));
+        }
+    } else {
+        let bounds: syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add> = ty
+            .bounds
+            .iter()
+            .filter(|bound| {
+                !matches!(bound, syn::TypeParamBound::Trait(bound) if matches!(
+                    bound.path.segments.last(),
+                    Some(last) if last.ident == "Send" || last.ident == "Sync"
+                ))
+            })
+            .cloned()
+            .collect();
+        // `dyn Send` has nothing else to keep
+        if bounds
+            .iter()
+            .any(|bound| matches!(bound, syn::TypeParamBound::Trait(_)))
+        {
+            ty.bounds = bounds;
+        }
+    }
+}
+
+/// Adds `Send + Sync` to the type parameters captured by futures (`send_bounds`).
+fn add_send_sync_bounds(generics: &mut syn::Generics) {
+    for param in &mut generics.params {
+        if let syn::GenericParam::Type(param) = param {
+            if !has_trait_bound(&param.bounds, "Send") {
+                param.bounds.push(parse_quote!(::std::marker::Send));
+            }
+            if !has_trait_bound(&param.bounds, "Sync") {
+                param.bounds.push(parse_quote!(::std::marker::Sync));
+            }
+            param.colon_token.get_or_insert_with(Default::default);
+        }
+    }
+}
+
+/// Adds `+ Send` to the `impl Future<..>` (`impl Stream<..>`) return type of a function, and
+/// `Send + Sync` to its type parameters (`send_bounds`).
+fn add_send_bounds(sig: &mut syn::Signature) {
+    if let syn::ReturnType::Type(_, ty) = &mut sig.output {
+        if is_impl_future(ty) {
+            if let syn::Type::ImplTrait(ty) = &mut **ty {
+                if !has_trait_bound(&ty.bounds, "Send") {
+                    ty.bounds.push(parse_quote!(::std::marker::Send));
+                }
+            }
+        }
+    }
+    add_send_sync_bounds(&mut sig.generics);
+}
+
+/// Turns `async fn f(x: &T) -> R { body }` into `fn f<'maybe_async>(x: &'maybe_async T) ->
+/// impl Future<Output = R> + Send + 'maybe_async { async move { body } }`.
+pub fn make_send_fn(item: &mut syn::ItemFn) {
+    let lifetime = syn::Lifetime::new(DYN_LIFETIME, Span::call_site());
+    let sig = &mut item.sig;
+    sig.asyncness = None;
+
+    for input in &mut sig.inputs {
+        if let syn::FnArg::Typed(arg) = input {
+            NameElidedLifetimes(lifetime.clone()).visit_type_mut(&mut arg.ty)
+        }
+    }
+
+    let output = match &sig.output {
+        syn::ReturnType::Default => parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => {
+            let mut ty = (**ty).clone();
+            NameElidedLifetimes(lifetime.clone()).visit_type_mut(&mut ty);
+            ty
+        }
+    };
+    sig.output = parse_quote!(
+        -> impl ::std::future::Future<Output = #output> + ::std::marker::Send + #lifetime
+    );
+
+    // everything the future captures has to outlive it
+    let outlives = sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Lifetime(param) => {
+                let param = &param.lifetime;
+                Some(parse_quote!(#param: #lifetime))
+            }
+            syn::GenericParam::Type(param) => {
+                let param = &param.ident;
+                Some(parse_quote!(#param: #lifetime))
+            }
+            syn::GenericParam::Const(_) => None,
+        })
+        .collect::<Vec<syn::WherePredicate>>();
+    if !outlives.is_empty() {
+        let where_clause = sig.generics.make_where_clause();
+        where_clause.predicates.extend(outlives);
+    }
+    sig.generics.params.insert(0, parse_quote!(#lifetime));
+
+    let body = &*item.block;
+    *item.block = parse_quote!({ async move #body });
+}
+
+/// Makes the blanket implementation of the `dyn` variant of a trait for all implementors of the
+/// trait `from` (its async variant).
+pub fn make_dyn_blanket_impl(
+    item: &syn::ItemTrait,
+    from: &str,
+    send: Option<bool>,
+) -> syn::Result<syn::ItemImpl> {
+    let from = syn::Ident::new(from, item.ident.span());
+    let target = quote!(__MaybeAsyncSelf);
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+
+    let bounds = if send != Some(false) {
+        quote!(+ ::std::marker::Send + ::std::marker::Sync)
+    } else {
+        quote!()
+    };
+    let mut generics = item.generics.clone();
+    generics
+        .params
+        .push(parse_quote!(#target: #from #ty_generics + ?::std::marker::Sized #bounds));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let mut items: Vec<syn::ImplItem> = vec![];
+    for inner in &item.items {
+        match inner {
+            syn::TraitItem::Method(method) => {
+                let attrs = method.attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+                let mut sig = method.sig.clone();
+                let args = forward_args(&mut sig);
+
+                let name = &sig.ident;
+                let call = quote!(<#target as #from #ty_generics>::#name(#(#args),*));
+                let boxed = sig
+                    .generics
+                    .lifetimes()
+                    .any(|param| param.lifetime.to_string() == DYN_LIFETIME);
+                let body = if boxed {
+                    quote!(::std::boxed::Box::pin(#call))
+                } else {
+                    call
+                };
+                items.push(parse_quote!(#(#attrs)* #sig { #body }));
+            }
+            syn::TraitItem::Type(ty) => {
+                let name = &ty.ident;
+                let params = &ty.generics.params;
+                let (_, assoc_generics, _) = ty.generics.split_for_impl();
+                items.push(parse_quote!(
+                    type #name<#params> = <#target as #from #ty_generics>::#name #assoc_generics;
+                ));
+            }
+            syn::TraitItem::Const(konst) => {
+                let name = &konst.ident;
+                let ty = &konst.ty;
+                items.push(parse_quote!(
+                    const #name: #ty = <#target as #from #ty_generics>::#name;
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let unsafety = &item.unsafety;
+    let ident = &item.ident;
+    Ok(parse_quote!(
+        #unsafety impl #impl_generics #ident #ty_generics for #target #where_clause {
+            #(#items)*
+        }
+    ))
+}
+
+/// Renames the arguments of a method to forward them to another method, and returns the arguments
+/// to pass.
+fn forward_args(sig: &mut syn::Signature) -> Vec<TokenStream2> {
+    let mut args = vec![];
+    for (index, input) in sig.inputs.iter_mut().enumerate() {
+        match input {
+            syn::FnArg::Receiver(_) => args.push(quote!(self)),
+            syn::FnArg::Typed(arg) => match &*arg.pat {
+                syn::Pat::Ident(pat) if pat.ident == "self" => args.push(quote!(self)),
+                _ => {
+                    let name = quote::format_ident!("__arg{}", index);
+                    arg.pat = parse_quote!(#name);
+                    args.push(quote!(#name));
+                }
+            },
+        }
+    }
+    args
+}
+
+/// Replaces the body of a function with a call of `target` (the function of the other variant),
+/// through the `delegate` function: `block_on` in the `sync` variant, `spawn_blocking` (or
+/// `unblock`) in the `async` one.
+pub fn make_delegate_fn(
+    item: &mut syn::ItemFn,
+    target: &syn::Ident,
+    delegate: &syn::Path,
+    convert_mode: ConvertMode,
+) {
+    let args = forward_args(&mut item.sig);
+    *item.block = match convert_mode {
+        ConvertMode::IntoAsync => {
+            item.sig.asyncness = Some(Default::default());
+            let call = quote!(#delegate(move || #target(#(#args),*)).await);
+            // the join handle of tokio resolves to a `Result`, with the panic of the task as an
+            // error
+            if matches!(delegate.segments.first(), Some(seg) if seg.ident == "tokio") {
+                parse_quote!({
+                    #call.unwrap_or_else(|err| ::std::panic::resume_unwind(err.into_panic()))
+                })
+            } else {
+                parse_quote!({ #call })
+            }
+        }
+        ConvertMode::IntoSync | ConvertMode::IntoDyn => {
+            parse_quote!({ #delegate(#target(#(#args),*)) })
+        }
+    };
+}
+
+/// Makes the declarations and the definitions of blocking counterparts (with the `_blocking`
+/// suffix) of async methods, which call the async methods of `callee` through `block_on`.
+fn make_blocking_methods<'a>(
+    methods: impl Iterator<Item = (&'a [syn::Attribute], &'a syn::Signature)>,
+    callee: &TokenStream2,
+    block_on: &syn::Path,
+) -> (Vec<syn::TraitItem>, Vec<syn::ImplItem>) {
+    let mut decls = vec![];
+    let mut defs = vec![];
+    for (attrs, sig) in methods {
+        if sig.asyncness.is_none() {
+            continue;
+        }
+
+        let attrs = attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("cfg"))
+            .collect::<Vec<_>>();
+        let name = &sig.ident;
+        let mut sig = sig.clone();
+        sig.asyncness = None;
+        sig.ident = quote::format_ident!("{}_blocking", name);
+        let args = forward_args(&mut sig);
+
+        decls.push(parse_quote!(#(#attrs)* #sig;));
+        defs.push(parse_quote!(
+            #(#attrs)* #sig {
+                #block_on(#callee::#name(#(#args),*))
+            }
+        ));
+    }
+    (decls, defs)
+}
+
+/// Makes the `<Name>BlockingExt` trait with blocking counterparts of the async methods of a trait,
+/// implemented for all implementors of the trait. Returns `None` if there are no async methods.
+pub fn make_blocking_ext_trait(
+    item: &syn::ItemTrait,
+    name: &str,
+    block_on: &syn::Path,
+) -> Option<(syn::ItemTrait, syn::ItemImpl)> {
+    let ext = quote::format_ident!("{}BlockingExt", name, span = item.ident.span());
+    let ident = &item.ident;
+    let target = quote!(__MaybeAsyncSelf);
+    let (trait_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let methods = item.items.iter().filter_map(|inner| match inner {
+        syn::TraitItem::Method(method) => Some((method.attrs.as_slice(), &method.sig)),
+        _ => None,
+    });
+    let callee = quote!(<#target as #ident #ty_generics>);
+    let (decls, defs) = make_blocking_methods(methods, &callee, block_on);
+    if decls.is_empty() {
+        return None;
+    }
+
+    let mut generics = item.generics.clone();
+    generics
+        .params
+        .push(parse_quote!(#target: #ident #ty_generics + ?::std::marker::Sized));
+    let (impl_generics, _, _) = generics.split_for_impl();
+
+    let vis = &item.vis;
+    Some((
+        parse_quote!(
+            #vis trait #ext #trait_generics: #ident #ty_generics #where_clause {
+                #(#decls)*
+            }
+        ),
+        parse_quote!(
+            impl #impl_generics #ext #ty_generics for #target #where_clause {
+                #(#defs)*
+            }
+        ),
+    ))
+}
+
+/// Implements a trait for the `wrapper` type over the implementors of its variant of the other kind
+/// (the `bridge` parameter). In the sync variant, `impl<T: ClientAsync> ClientSync for Wrapper<T>`
+/// blocks on the futures of the async methods with `block_on`; in the async variant,
+/// `impl<T: ClientSync> ClientAsync for Wrapper<T>` calls the sync methods in the async ones. The
+/// wrapped value is the first field of the wrapper.
+pub fn make_bridge_impl(
+    item: &syn::ItemTrait,
+    other: &syn::Ident,
+    wrapper: &syn::Path,
+    block_on: Option<&syn::Path>,
+    async_methods: &[String],
+    async_trait: &str,
+) -> syn::ItemImpl {
+    let ident = &item.ident;
+    let target = quote!(__MaybeInner);
+    let (_, ty_generics, where_clause) = item.generics.split_for_impl();
+    let callee = quote!(<#target as #other #ty_generics>);
+
+    let mut items: Vec<syn::ImplItem> = vec![];
+    for inner in &item.items {
+        match inner {
+            syn::TraitItem::Method(method) => {
+                let attrs = method.attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+                let mut sig = method.sig.clone();
+                let mut args = forward_args(&mut sig);
+                if let Some(syn::FnArg::Receiver(receiver)) = sig.inputs.first() {
+                    args[0] = match (&receiver.reference, &receiver.mutability) {
+                        (Some(_), Some(_)) => quote!(&mut self.0),
+                        (Some(_), None) => quote!(&self.0),
+                        (None, _) => quote!(self.0),
+                    };
+                }
+
+                let name = &sig.ident;
+                let call = quote!(#callee::#name(#(#args),*));
+                let is_async = async_methods.contains(&name.to_string());
+                let body = match block_on {
+                    Some(block_on) if is_async => quote!(#block_on(#call)),
+                    // desugared to the one returning `impl Future` (`impl_future`)
+                    None if is_async && sig.asyncness.is_none() => quote!(async move { #call }),
+                    _ => call,
+                };
+                items.push(parse_quote!(#(#attrs)* #sig { #body }));
+            }
+            syn::TraitItem::Type(ty) if ty.generics.params.is_empty() => {
+                let name = &ty.ident;
+                items.push(parse_quote!(type #name = #callee::#name;));
+            }
+            syn::TraitItem::Const(constant) => {
+                let name = &constant.ident;
+                let ty = &constant.ty;
+                items.push(parse_quote!(const #name: #ty = #callee::#name;));
+            }
+            _ => {}
+        }
+    }
+
+    // the impl block needs the `async_trait` attribute of the trait as well
+    let attrs: Vec<_> = item
+        .attrs
+        .iter()
+        .filter(|attr| is_async_trait_attr(attr, async_trait))
+        .collect();
+    // the futures holding the references to the wrapped value are `Send` only if it is
+    // `Send + Sync`
+    let send = block_on.is_none()
+        && (attrs.iter().any(|attr| attr.tokens.is_empty())
+            || items.iter().any(|item| {
+                matches!(item, syn::ImplItem::Method(method) if matches!(
+                    &method.sig.output,
+                    syn::ReturnType::Type(_, ty) if is_impl_future(ty) && matches!(
+                        &**ty, syn::Type::ImplTrait(ty) if has_trait_bound(&ty.bounds, "Send")
+                    )
+                ))
+            }));
+
+    let mut generics = item.generics.clone();
+    generics.params.push(if send {
+        parse_quote!(#target: #other #ty_generics + Send + Sync)
+    } else {
+        parse_quote!(#target: #other #ty_generics)
+    });
+    let (impl_generics, _, _) = generics.split_for_impl();
+    parse_quote!(
+        #(#attrs)*
+        impl #impl_generics #ident #ty_generics for #wrapper<#target> #where_clause {
+            #(#items)*
+        }
+    )
+}
+
+/// Makes the `<Name>BlockingExt` trait with blocking counterparts of the async methods of an
+/// inherent impl block, implemented for the type. Returns `None` if there are no async methods.
+pub fn make_blocking_ext_impl(
+    item: &syn::ItemImpl,
+    name: &str,
+    block_on: &syn::Path,
+) -> Option<(syn::ItemTrait, syn::ItemImpl)> {
+    let ext = quote::format_ident!("{}BlockingExt", name);
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let methods = item.items.iter().filter_map(|inner| match inner {
+        syn::ImplItem::Method(method) => Some((method.attrs.as_slice(), &method.sig)),
+        _ => None,
+    });
+    let (decls, defs) = make_blocking_methods(methods, &quote!(Self), block_on);
+    if decls.is_empty() {
+        return None;
+    }
+
+    // the trait is as visible as the first async method
+    let vis = item
+        .items
+        .iter()
+        .find_map(|inner| match inner {
+            syn::ImplItem::Method(method) if method.sig.asyncness.is_some() => {
+                Some(method.vis.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or(syn::Visibility::Inherited);
+    let self_ty = &item.self_ty;
+    Some((
+        parse_quote!(
+            #vis trait #ext #impl_generics #where_clause {
+                #(#decls)*
+            }
+        ),
+        parse_quote!(
+            impl #impl_generics #ext #ty_generics for #self_ty #where_clause {
+                #(#defs)*
+            }
+        ),
+    ))
+}
+
+impl<'p> AsyncAwaitVisitor<'p> {
+    fn process_replace_features_meta(&self, meta: &mut syn::Meta) -> syn::Result<bool> {
+        let mut changed = false;
+
+        match meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(s),
+                ..
+            }) => {
+                if let Some(ident) = path.get_ident() {
+                    if ident.to_string() == "feature" {
+                        let prev = s.value();
+                        if let Some(new) = self.params.replace_features_get(&prev) {
+                            *s = syn::LitStr::new(new, s.span());
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            syn::Meta::List(list) => {
+                for nm in &mut list.nested {
+                    if let syn::NestedMeta::Meta(m) = nm {
+                        changed |= self.process_replace_features_meta(m)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(changed)
+    }
+
+    /// For the `only_if`, `remove_if`, `only_sync` and `only_async` attributes, returns whether the
+    /// marked content stays in the current variant.
+    fn attribute_condition(&self, attr: &syn::Attribute) -> syn::Result<Option<bool>> {
+        let (key, not) = match self.params.is_our_attr(attr).as_deref() {
+            Some(MACRO_ONLY_IF_NAME) => (self.attribute_checked_key(attr)?, false),
+            Some(MACRO_REMOVE_IF_NAME) => (self.attribute_checked_key(attr)?, true),
+            Some(MACRO_ONLY_SYNC_NAME) => (Self::attribute_no_args(attr, "sync")?, false),
+            Some(MACRO_ONLY_ASYNC_NAME) => (Self::attribute_no_args(attr, "async")?, false),
+            _ => return Ok(None),
+        };
+
+        let success = if let Some(current_key) = self.params.key_get() {
+            (current_key == &key) ^ not
+        } else {
+            false
+        };
+
+        Ok(Some(success))
+    }
+
+    /// Returns the key of the `only_if` or `remove_if` attribute, checking that some declared
+    /// variant has it.
+    fn attribute_checked_key(&self, attr: &syn::Attribute) -> syn::Result<String> {
+        let key = Self::attribute_if_key(attr)?;
+        let keys = self.params.variant_keys_get();
+        if !keys.is_empty() && !keys.contains(&key) {
+            return Err(syn::Error::new_spanned(
+                attr.tokens.to_token_stream(),
+                format!(
+                    "Unknown variant key `{}`, expected one of: {}",
+                    key,
+                    keys.join(", ")
+                ),
+            ));
+        }
+
+        Ok(key)
+    }
+
+    fn attribute_no_args(attr: &syn::Attribute, key: &str) -> syn::Result<String> {
+        if !attr.tokens.is_empty() {
+            return Err(syn::Error::new_spanned(
+                attr.tokens.to_token_stream(),
+                "Expected no arguments",
+            ));
+        }
+
+        Ok(key.to_string())
+    }
+
+    /// Removes the conditional attributes, returns whether the marked content stays in the current
+    /// variant. Used where attribute macros are not allowed.
+    fn process_conditional_attrs(&self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<bool> {
+        let mut keep = true;
+        for attr in std::mem::take(attrs) {
+            match self.attribute_condition(&attr)? {
+                Some(success) => keep &= success,
+                None => attrs.push(attr),
+            }
+        }
+
+        Ok(keep)
+    }
+
+    fn retain_conditional<T>(
+        &self,
+        list: &mut syn::punctuated::Punctuated<T, syn::token::Comma>,
+        attrs: fn(&mut T) -> &mut Vec<syn::Attribute>,
+    ) -> syn::Result<()> {
+        let mut retained = syn::punctuated::Punctuated::new();
+        for mut item in std::mem::take(list) {
+            if self.process_conditional_attrs(attrs(&mut item))? {
+                retained.push(item);
+            }
+        }
+        *list = retained;
+
+        Ok(())
+    }
+
+    fn attribute_if_key(attr: &syn::Attribute) -> syn::Result<String> {
+        let args =
+            syn::parse_macro_input::parse::<AttributeArgsInParens>(attr.tokens.clone().into())?;
+
+        let arg = match &args.args.len() {
+            0 => {
+                return Err(syn::Error::new_spanned(
+                    attr.to_token_stream(),
+                    "Expected ident",
+                ))
+            }
+            1 => &args.args[0],
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    args.args[1].to_token_stream(),
+                    "Too many arguments",
+                ))
+            }
+        };
+
+        let key = match arg {
+            syn::NestedMeta::Lit(syn::Lit::Str(s)) => s.value(),
+            syn::NestedMeta::Meta(syn::Meta::Path(ref p)) => {
+                if let Some(s) = p.get_ident() {
+                    s.to_string()
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        arg.to_token_stream(),
+                        "Wrong ident",
+                    ));
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(value),
                 ..
             })) if path.is_ident("key") => value.value(),
             _ => {
@@ -199,359 +2373,1024 @@ impl<'p> AsyncAwaitVisitor<'p> {
             }
         };
 
-        let success = if let Some(current_key) = self.params.key_get() {
-            (current_key == &key) ^ not
+        Ok(key)
+    }
+
+    fn process_attribute_if(&mut self, attr: &mut syn::Attribute, success: bool) {
+        let new_name = if success {
+            MACRO_NOOP_NAME
+        } else {
+            MACRO_REMOVE_NAME
+        };
+        attr.path = self.params.make_self_path(new_name);
+    }
+
+    #[cfg(feature = "doctests")]
+    fn process_doc_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
+        let mut acc: Vec<syn::Attribute> = vec![];
+        let mut acc_temp: Vec<syn::Attribute> = vec![];
+        let mut lines: Vec<String> = vec![];
+        let mut inside_doc = false;
+
+        fn process_docs(
+            acc: &mut Vec<syn::Attribute>,
+            acc_temp: &mut Vec<syn::Attribute>,
+            lines: &mut Vec<String>,
+            params: &MacroParameters,
+        ) {
+            assert!(!lines.is_empty());
+            let mut first = true;
+            let doc: String = lines
+                .iter()
+                .map(|s| {
+                    if first {
+                        first = false;
+                        s.clone()
+                    } else {
+                        let mut ss = String::from("\n");
+                        ss.push_str(s.as_str());
+                        ss
+                    }
+                })
+                .collect();
+
+            let processor = |key: &str, code: &str| -> Option<Option<String>> {
+                if let Some(param_key) = params.key_get() {
+                    if param_key == key {
+                        Some(Some(code.to_string()))
+                    } else {
+                        Some(None)
+                    }
+                } else {
+                    None
+                }
+            };
+
+            if let Some(doc) = process_doctests(doc.as_str(), processor) {
+                let mut acc_temp_drain = acc_temp.drain(..);
+                for line in doc.lines() {
+                    let tokens = quote!(= #line);
+                    let attr = if let Some(mut attr) = acc_temp_drain.next() {
+                        attr.tokens = tokens;
+                        attr
+                    } else {
+                        let sp = Span::call_site();
+                        syn::Attribute {
+                            pound_token: syn::Token![#]([sp]),
+                            style: syn::AttrStyle::Outer,
+                            bracket_token: syn::token::Bracket(sp),
+                            path: make_path("doc"),
+                            tokens,
+                        }
+                    };
+                    acc.push(attr);
+                }
+            } else {
+                for attr in acc_temp.drain(..) {
+                    acc.push(attr);
+                }
+            }
+        }
+
+        for attr in attrs.drain(..) {
+            // only `#[doc = "..."]` attributes are doc comments, not `#[doc(hidden)]` and so on
+            let is_doc_comment =
+                attr.path.is_ident("doc") && syn::parse2::<EqStr>(attr.tokens.clone()).is_ok();
+
+            match (inside_doc, is_doc_comment) {
+                (false, false) => {
+                    acc.push(attr);
+                }
+                (false, true) => {
+                    let es = syn::parse2::<EqStr>(attr.tokens.clone())?;
+                    let doc = es.str.value();
+
+                    lines.push(doc);
+                    acc_temp.push(attr);
+                    inside_doc = true;
+                }
+                (true, false) => {
+                    process_docs(&mut acc, &mut acc_temp, &mut lines, &self.params);
+
+                    acc_temp.clear();
+                    lines.clear();
+                    inside_doc = false;
+
+                    acc.push(attr);
+                }
+                (true, true) => {
+                    let es = syn::parse2::<EqStr>(attr.tokens.clone())?;
+                    let doc = es.str.value();
+
+                    lines.push(doc);
+                    acc_temp.push(attr);
+                }
+            }
+        }
+
+        if inside_doc {
+            process_docs(&mut acc, &mut acc_temp, &mut lines, &self.params);
+        }
+
+        let _ = std::mem::replace(attrs, acc);
+
+        Ok(())
+    }
+
+    fn process_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
+        #[cfg(feature = "doctests")]
+        self.process_doc_attrs(attrs)?;
+
+        for attr in attrs.iter_mut() {
+            // Other attributes stay unchanged. Unknown attributes will be rejected by compiler
+            // later.
+            if let Some(success) = self.attribute_condition(attr)? {
+                self.process_attribute_if(attr, success);
+            }
+        }
+
+        // #[async_recursion] is dropped in the sync variants
+        attrs.retain(|attr| match self.params.attr_policy_get(attr) {
+            Some("async") => self.convert_mode == ConvertMode::IntoAsync,
+            Some("sync") => self.convert_mode == ConvertMode::IntoSync,
+            _ => true,
+        });
+
+        if !self.params.drop_attrs_is_empty() {
+            attrs.retain(|attr| {
+                if let Some(ident) = attr.path.get_ident() {
+                    let ident = ident.to_string();
+                    !self.params.drop_attrs_contains(&ident)
+                } else {
+                    true
+                }
+            });
+        }
+
+        if !self.params.replace_features_is_empty() {
+            for attr in attrs {
+                if let Some(ident) = attr.path.get_ident() {
+                    if ident.to_string() == "cfg" {
+                        if let Ok(mut meta) = attr.parse_meta() {
+                            if self.process_replace_features_meta(&mut meta)? {
+                                if let syn::Meta::List(syn::MetaList { nested, .. }) = meta {
+                                    attr.tokens = quote!((#nested));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_expr(&mut self, node: &mut syn::Expr) -> syn::Result<()> {
+        // fut.boxed() -> fut
+        while let syn::Expr::MethodCall(call) = node {
+            if !self.params.strip_methods_contains(&call.method) {
+                break;
+            }
+            *node = (*call.receiver).clone();
+        }
+
+        // tokio::time::sleep(d) -> std::thread::sleep(d)
+        if let syn::Expr::Call(call) = node {
+            if let Some(func) = self.params.calls_get(&call.func) {
+                *call.func = func;
+            }
+        }
+
+        match self.convert_mode {
+            ConvertMode::IntoSync => {
+                // async -> sync, remove async_impl blocks
+                match node {
+                    _ if self.async_scope_removed() && spawn_blocking_body(node).is_some() => {
+                        // the blocking code runs in place in the sync variant
+                        *node = spawn_blocking_body(node).unwrap().clone();
+                        self.process_expr(node)?;
+                    }
+
+                    syn::Expr::Await(expr)
+                        if self.async_scope_removed()
+                            && self.params.locks_get().is_some()
+                            && self.is_lock_call(&expr.base) =>
+                    {
+                        let policy = self.params.locks_get().unwrap();
+                        *node = lock_call_to_std(&expr.base, policy).unwrap();
+                    }
+
+                    syn::Expr::Await(expr)
+                        if self.async_scope_removed()
+                            && self.params.channels_get()
+                            && channel_call_to_std(&expr.base).is_some() =>
+                    {
+                        *node = channel_call_to_std(&expr.base).unwrap();
+                    }
+
+                    syn::Expr::Await(expr)
+                        if self.async_scope_removed()
+                            && into_future_to_call(&expr.base, self.params).is_some() =>
+                    {
+                        *node = into_future_to_call(&expr.base, self.params).unwrap();
+                    }
+
+                    syn::Expr::Await(expr)
+                        if self.async_scope_removed()
+                            && self.params.spawn_get().is_some()
+                            && matches!(&*expr.base, syn::Expr::Call(call) if is_task_spawn(&call.func)) =>
+                    {
+                        // the awaited join handle of a task is joined, `async_std` gives the output
+                        // of the task itself
+                        let mut spawn = (*expr.base).clone();
+                        let is_async_std = matches!(
+                            &spawn, syn::Expr::Call(call) if is_async_std_spawn(&call.func)
+                        );
+                        self.process_expr(&mut spawn)?;
+                        if let syn::Expr::Block(_) = spawn {
+                            // `{ .. }.join()` would be a statement followed by `.join()`
+                            spawn = parse_quote!((#spawn));
+                        }
+                        *node = if is_async_std {
+                            parse_quote!(#spawn.join().unwrap())
+                        } else {
+                            parse_quote!(#spawn.join())
+                        };
+                    }
+
+                    syn::Expr::Await(expr)
+                        if self.async_scope_removed()
+                            && self.spawn_binding(&expr.base).is_some() =>
+                    {
+                        // the same for a variable holding the join handle
+                        let handle = &expr.base;
+                        *node = if self.spawn_binding(handle).unwrap() {
+                            parse_quote!(#handle.join().unwrap())
+                        } else {
+                            parse_quote!(#handle.join())
+                        };
+                    }
+
+                    syn::Expr::Await(expr) if self.async_scope_removed() => {
+                        *node = (*expr.base).clone();
+                        // the awaited expression itself may need to be converted, e.g. the sleep
+                        self.process_expr(node)?;
+                    }
+
+                    syn::Expr::Async(expr)
+                        if self.params.asyncness_removal_get().closures
+                            && self.async_scope_removed() =>
+                    {
+                        let inner = &expr.block;
+                        let sync_expr = if has_early_exit(inner) {
+                            // `return` and `?` have to leave only the block
+                            let attrs = &expr.attrs;
+                            let capture = &expr.capture;
+                            parse_quote!(#(#attrs)* (#capture || #inner)())
+                        } else if let ([syn::Stmt::Expr(stmt)], true) =
+                            (inner.stmts.as_slice(), expr.attrs.is_empty())
+                        {
+                            // remove useless braces when there is only one expression
+                            stmt.clone()
+                        } else {
+                            syn::Expr::Block(syn::ExprBlock {
+                                attrs: expr.attrs.clone(),
+                                block: inner.clone(),
+                                label: None,
+                            })
+                        };
+                        *node = sync_expr;
+                        // the statement taken out of the block may need to be converted itself,
+                        // e.g. `block_on(async { fut.await })`
+                        self.process_expr(node)?;
+                    }
+
+                    syn::Expr::Call(expr)
+                        if self.params.asyncness_removal_get().closures
+                            && self.async_scope_removed()
+                            && box_pin_async_arg(expr).is_some() =>
+                    {
+                        // the async block is converted to a block expression
+                        *node = box_pin_async_arg(expr).unwrap().clone();
+                        self.process_expr(node)?;
+                    }
+
+                    _ if self.params.asyncness_removal_get().closures
+                        && self.async_scope_removed()
+                        && ready_future_value(node).is_some() =>
+                    {
+                        *node = ready_future_value(node).unwrap().clone();
+                        self.process_expr(node)?;
+                    }
+
+                    _ if self.params.asyncness_removal_get().closures
+                        && self.async_scope_removed()
+                        && boxed_async_receiver(node).is_some() =>
+                    {
+                        *node = boxed_async_receiver(node).unwrap().clone();
+                        self.process_expr(node)?;
+                    }
+
+                    _ if self.params.asyncness_removal_get().closures
+                        && self.async_scope_removed()
+                        && block_on_arg(node).is_some() =>
+                    {
+                        // the future itself is converted to sync code, so no executor is needed
+                        *node = block_on_arg(node).unwrap().clone();
+                        self.process_expr(node)?;
+                    }
+
+                    syn::Expr::While(expr)
+                        if self.async_scope_removed() && stream_loop_to_for(expr).is_some() =>
+                    {
+                        *node = stream_loop_to_for(expr).unwrap();
+                    }
+
+                    syn::Expr::Macro(expr)
+                        if self.async_scope_removed() && join_macro_kind(&expr.mac).is_some() =>
+                    {
+                        let is_try = join_macro_kind(&expr.mac).unwrap();
+                        *node = join_macro_to_seq(&expr.mac, is_try)?;
+                    }
+
+                    syn::Expr::Call(expr)
+                        if self.async_scope_removed() && is_async_sleep(&expr.func) =>
+                    {
+                        expr.func = parse_quote!(::std::thread::sleep);
+                    }
+
+                    syn::Expr::Call(expr)
+                        if self.async_scope_removed()
+                            && self.params.timeout_get().is_some()
+                            && is_async_timeout(&expr.func)
+                            && expr.args.len() == 2 =>
+                    {
+                        let duration = &expr.args[0];
+                        let call = &expr.args[1];
+                        *node = match self.params.timeout_get().unwrap() {
+                            Some(helper) => parse_quote!(#helper(#duration, || #call)),
+                            None => match self.params.timeout_error_get() {
+                                Some(error) => {
+                                    parse_quote!(::core::result::Result::<_, #error>::Ok(#call))
+                                }
+                                None => parse_quote!(::core::result::Result::Ok(#call)),
+                            },
+                        };
+                    }
+
+                    syn::Expr::Call(expr)
+                        if self.async_scope_removed() && is_yield_now(&expr.func) =>
+                    {
+                        if self.params.yield_now_thread() {
+                            expr.func = parse_quote!(::std::thread::yield_now);
+                        } else {
+                            *node = parse_quote!(());
+                        }
+                    }
+
+                    syn::Expr::Call(expr)
+                        if self.async_scope_removed()
+                            && self.params.spawn_get().is_some()
+                            && is_task_spawn(&expr.func) =>
+                    {
+                        let target = self.params.spawn_get().unwrap();
+                        if let Some(thread) = task_spawn_to_thread(expr, &target) {
+                            *node = thread;
+                        }
+                    }
+
+                    syn::Expr::Macro(expr)
+                        if self.async_scope_removed() && is_select_macro(&expr.mac) =>
+                    {
+                        return Err(select_macro_error(&expr.mac));
+                    }
+
+                    syn::Expr::Macro(expr) if is_stream_macro(&expr.mac) => {
+                        match self.params.stream_macro_get() {
+                            Some(path) => expr.mac.path = syn::parse_str(path)?,
+                            None => return Err(stream_macro_error(&expr.mac)),
+                        }
+                    }
+
+                    _ => {}
+                }
+            }
+            ConvertMode::IntoAsync => {
+                // fetch() -> fetch().await
+                if !self.params.add_await_is_empty() {
+                    if let syn::Expr::Await(_) = node {
+                        self.awaited = true;
+                    } else {
+                        self.add_await(node);
+                    }
+                }
+            }
+            ConvertMode::IntoDyn => {}
+        };
+
+        Ok(())
+    }
+
+    fn process_block(&mut self, node: &mut syn::Block) -> syn::Result<()> {
+        // statements cannot be removed by attribute macros, so they are removed here
+        let mut stmts = vec![];
+        for mut stmt in std::mem::take(&mut node.stmts) {
+            let attrs = match &mut stmt {
+                syn::Stmt::Local(local) => Some(&mut local.attrs),
+                syn::Stmt::Expr(expr) | syn::Stmt::Semi(expr, _) => expr_attrs_mut(expr),
+                // braced macros in statement position are parsed as items
+                syn::Stmt::Item(syn::Item::Macro(item)) => Some(&mut item.attrs),
+                syn::Stmt::Item(_) => None,
+            };
+            let keep = match attrs {
+                Some(attrs) => self.process_conditional_attrs(attrs)?,
+                None => true,
+            };
+            // yield_now().await; -> (nothing)
+            let yielded = self.convert_mode == ConvertMode::IntoSync
+                && self.async_scope_removed()
+                && !self.params.yield_now_thread()
+                && is_yield_now_stmt(&stmt);
+            if keep && !yielded {
+                stmts.push(stmt);
+            }
+        }
+        node.stmts = stmts;
+
+        Ok(())
+    }
+
+    fn process_stmt(&mut self, node: &mut syn::Stmt) -> syn::Result<()> {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            // braced macros in statement position are parsed as items
+            if let syn::Stmt::Item(syn::Item::Macro(item)) = node {
+                if item.ident.is_none() && is_select_macro(&item.mac) && self.async_scope_removed()
+                {
+                    return Err(select_macro_error(&item.mac));
+                }
+                if item.ident.is_none() && is_stream_macro(&item.mac) {
+                    match self.params.stream_macro_get() {
+                        Some(path) => item.mac.path = syn::parse_str(path)?,
+                        None => return Err(stream_macro_error(&item.mac)),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_expr_async(&mut self, _node: &mut syn::ExprAsync) -> syn::Result<()> {
+        // async blocks reaching here are kept as is
+        self.async_scopes.push(false);
+        Ok(())
+    }
+
+    fn after_process_expr_async(&mut self, _node: &mut syn::ExprAsync) -> syn::Result<()> {
+        self.leave_scope();
+        Ok(())
+    }
+
+    fn process_expr_closure(&mut self, node: &mut syn::ExprClosure) -> syn::Result<()> {
+        let removed = if node.asyncness.is_none() {
+            self.async_scope_removed()
+        } else if let ConvertMode::IntoSync = self.convert_mode {
+            self.params.asyncness_removal_get().closures && self.async_scope_removed()
         } else {
             false
         };
+        if removed {
+            node.asyncness = None;
+        }
+        // `|x| async move { .. }` -> `move |x| { .. }`, the captures are moved into the closure
+        if let (ConvertMode::IntoSync, syn::Expr::Async(body)) = (self.convert_mode, &*node.body) {
+            if self.params.asyncness_removal_get().closures && removed && node.capture.is_none() {
+                node.capture = body.capture;
+            }
+        }
+        self.async_scopes.push(removed);
+        Ok(())
+    }
 
-        let new_name = if success {
-            MACRO_NOOP_NAME
-        } else {
-            MACRO_REMOVE_NAME
-        };
-        attr.path = self.params.make_self_path(new_name);
+    fn after_process_expr_closure(&mut self, node: &mut syn::ExprClosure) -> syn::Result<()> {
+        if self.leave_scope() {
+            node.asyncness = Some(Default::default());
+        }
+        Ok(())
+    }
 
+    fn process_item(&mut self, node: &mut syn::Item) -> syn::Result<()> {
+        self.item_depth += 1;
+        // use tokio::sync::mpsc; mpsc::channel(8) -> std::sync::mpsc::sync_channel(8)
+        if let syn::Item::Use(item) = node {
+            let aliases = use_tree_aliases(&item.tree, &self.path_map);
+            if !aliases.is_empty() {
+                self.path_map.extend(aliases);
+                self.path_map
+                    .sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+            }
+        }
         Ok(())
     }
 
-    #[cfg(feature = "doctests")]
-    fn process_doc_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
-        let mut acc: Vec<syn::Attribute> = vec![];
-        let mut acc_temp: Vec<syn::Attribute> = vec![];
-        let mut lines: Vec<String> = vec![];
-        let mut inside_doc = false;
+    fn after_process_item(&mut self, _node: &mut syn::Item) -> syn::Result<()> {
+        self.item_depth -= 1;
+        Ok(())
+    }
 
-        fn process_docs(
-            acc: &mut Vec<syn::Attribute>,
-            acc_temp: &mut Vec<syn::Attribute>,
-            lines: &mut Vec<String>,
-            params: &MacroParameters,
-        ) {
-            assert!(!lines.is_empty());
-            let mut first = true;
-            let doc: String = lines
-                .iter()
-                .map(|s| {
-                    if first {
-                        first = false;
-                        s.clone()
-                    } else {
-                        let mut ss = String::from("\n");
-                        ss.push_str(s.as_str());
-                        ss
-                    }
-                })
-                .collect();
+    /// Removes the attribute with the given name (such as `assoc_type`) and returns it along with
+    /// its value for the current variant, taken from the `key = "value"` pairs of the attribute.
+    fn take_variant_attr(
+        &self,
+        attrs: &mut Vec<syn::Attribute>,
+        name: &str,
+        expected: &str,
+    ) -> syn::Result<Option<(syn::Attribute, Option<syn::LitStr>)>> {
+        let pos = attrs
+            .iter()
+            .position(|attr| self.params.is_our_attr(attr).as_deref() == Some(name));
+        let attr = match pos {
+            Some(pos) => attrs.remove(pos),
+            None => return Ok(None),
+        };
 
-            let processor = |key: &str, code: &str| -> Option<Option<String>> {
-                if let Some(param_key) = params.key_get() {
-                    if param_key == key {
-                        Some(Some(code.to_string()))
-                    } else {
-                        Some(None)
+        let mut value = None;
+        let args = syn::parse2::<AttributeArgsInParens>(attr.tokens.clone())?;
+        for arg in &args.args {
+            let (key, s) = match arg {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(s),
+                    ..
+                })) => match path.get_ident() {
+                    Some(key) => (key.to_string(), s),
+                    None => {
+                        return Err(syn::Error::new_spanned(
+                            path.to_token_stream(),
+                            "Expected variant key",
+                        ))
                     }
-                } else {
-                    None
+                },
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        arg.to_token_stream(),
+                        format!("Expected `key = \"{}\"` pair", expected),
+                    ))
                 }
             };
 
-            if let Some(doc) = process_doctests(doc.as_str(), processor) {
-                let mut acc_temp_drain = acc_temp.drain(..);
-                for line in doc.lines() {
-                    let tokens = quote!(= #line);
-                    let attr = if let Some(mut attr) = acc_temp_drain.next() {
-                        attr.tokens = tokens;
-                        attr
-                    } else {
-                        let sp = Span::call_site();
-                        syn::Attribute {
-                            pound_token: syn::Token![#]([sp]),
-                            style: syn::AttrStyle::Outer,
-                            bracket_token: syn::token::Bracket(sp),
-                            path: make_path("doc"),
-                            tokens,
-                        }
-                    };
-                    acc.push(attr);
+            if value.is_none() && self.params.key_get() == Some(key.as_str()) {
+                value = Some(s.clone());
+            }
+        }
+
+        Ok(Some((attr, value)))
+    }
+
+    /// Processes the `assoc_type` attribute of an associated type. Returns `false` if the type has
+    /// a definition for the current variant and should be removed.
+    fn process_assoc_type(
+        &mut self,
+        attrs: &mut Vec<syn::Attribute>,
+        ident: &syn::Ident,
+    ) -> syn::Result<bool> {
+        match self.take_variant_attr(attrs, MACRO_ASSOC_TYPE_NAME, "Type")? {
+            Some((_, Some(ty))) => {
+                self.assoc_types.insert(ident.to_string(), ty.parse()?);
+                Ok(false)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// Drops the associated future type (`type Fut: Future<Output = T>` of a trait, `type Fut =
+    /// BoxFuture<'a, T>` of an impl block) in the sync variant, `Self::Fut` is replaced with `T`.
+    /// Returns whether the associated type is kept.
+    fn process_future_assoc_type(&mut self, ident: &syn::Ident, output: Option<syn::Type>) -> bool {
+        match output {
+            Some(output)
+                if matches!(self.convert_mode, ConvertMode::IntoSync)
+                    && self.params.asyncness_removal_get().closures =>
+            {
+                self.assoc_types.insert(ident.to_string(), output);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Replaces the receiver of a method according to its `receiver` attribute or (for the methods
+    /// of the converted trait or impl block taking `&self` or `&mut self`) the `receiver` parameter
+    /// of the variant.
+    fn process_receiver(
+        &mut self,
+        attrs: &mut Vec<syn::Attribute>,
+        sig: &mut syn::Signature,
+    ) -> syn::Result<()> {
+        // the methods consuming `self` keep their receivers
+        let by_reference = matches!(
+            sig.inputs.first(),
+            Some(syn::FnArg::Receiver(receiver)) if receiver.reference.is_some()
+        );
+        let mut receiver = match self.params.receiver_get() {
+            Some(receiver) if self.item_depth == 0 && by_reference => {
+                Some(parse_receiver(receiver, Span::call_site())?)
+            }
+            _ => None,
+        };
+
+        if let Some((attr, value)) =
+            self.take_variant_attr(attrs, MACRO_RECEIVER_NAME, "receiver")?
+        {
+            if sig.receiver().is_none() {
+                return Err(syn::Error::new_spanned(
+                    attr.to_token_stream(),
+                    "The `receiver` attribute cannot be applied to a method without receiver",
+                ));
+            }
+            if let Some(value) = value {
+                receiver = Some(parse_receiver(value.value(), value.span())?);
+            }
+        }
+
+        if let Some(receiver) = receiver {
+            if sig.receiver().is_some() {
+                sig.inputs[0] = receiver;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Processes the `field_type` attribute of a struct or enum field.
+    fn process_field_type(&mut self, field: &mut syn::Field) -> syn::Result<()> {
+        if let Some((_, Some(ty))) =
+            self.take_variant_attr(&mut field.attrs, MACRO_FIELD_TYPE_NAME, "Type")?
+        {
+            field.ty = ty.parse()?;
+        }
+
+        Ok(())
+    }
+
+    fn process_fields(&mut self, node: &mut syn::Fields) -> syn::Result<()> {
+        match node {
+            syn::Fields::Named(fields) => {
+                self.retain_conditional(&mut fields.named, |field| &mut field.attrs)?
+            }
+            syn::Fields::Unnamed(fields) => {
+                self.retain_conditional(&mut fields.unnamed, |field| &mut field.attrs)?
+            }
+            syn::Fields::Unit => {}
+        }
+
+        for field in node.iter_mut() {
+            self.process_field_type(field)?;
+        }
+
+        Ok(())
+    }
+
+    fn process_item_enum(&mut self, node: &mut syn::ItemEnum) -> syn::Result<()> {
+        self.retain_conditional(&mut node.variants, |variant| &mut variant.attrs)
+    }
+
+    fn process_item_impl(&mut self, node: &mut syn::ItemImpl) -> syn::Result<()> {
+        let mut items = vec![];
+        for mut item in std::mem::take(&mut node.items) {
+            let keep = match &mut item {
+                syn::ImplItem::Type(ty) => {
+                    self.process_assoc_type(&mut ty.attrs, &ty.ident)?
+                        && self.process_future_assoc_type(&ty.ident, future_output_type(&ty.ty))
                 }
-            } else {
-                for attr in acc_temp.drain(..) {
-                    acc.push(attr);
+                syn::ImplItem::Method(method) => {
+                    self.process_receiver(&mut method.attrs, &mut method.sig)?;
+                    true
                 }
+                _ => true,
+            };
+            if keep {
+                items.push(item);
             }
         }
+        node.items = items;
+
+        self.trait_impls.push(node.trait_.is_some());
+        let has_futures = node.items.iter().any(|item| match item {
+            syn::ImplItem::Method(method) => returns_future(&method.sig),
+            _ => false,
+        });
+        if self.send_bounds_added() && has_futures {
+            add_send_sync_bounds(&mut node.generics);
+        }
+        let send = match node.trait_ {
+            Some(_) => self.async_trait_send(),
+            None => self.params.send_get(),
+        };
+        remove_asyncness_on_impl(
+            node,
+            self.convert_mode,
+            send,
+            self.params.async_trait_path_get(),
+        );
 
-        for attr in attrs.drain(..) {
-            match (inside_doc, attr.path.is_ident("doc")) {
-                (false, false) => {
-                    acc.push(attr);
-                }
-                (false, true) => {
-                    let es = syn::parse2::<EqStr>(attr.tokens.clone())?;
-                    let doc = es.str.value();
+        Ok(())
+    }
 
-                    lines.push(doc);
-                    acc_temp.push(attr);
-                    inside_doc = true;
-                }
-                (true, false) => {
-                    process_docs(&mut acc, &mut acc_temp, &mut lines, &self.params);
+    fn process_impl_item_method(&mut self, node: &mut syn::ImplItemMethod) -> syn::Result<()> {
+        if let ConvertMode::IntoAsync = self.convert_mode {
+            if self.params.add_await_methods_contains(&node.sig.ident) {
+                node.sig.asyncness = Some(Default::default());
+            }
+        }
+        self.enter_fn_scope(&mut node.sig.asyncness, true);
+        self.enter_lifetimes(&node.sig, Some(&node.block));
+        self.enter_lock_bindings(&node.sig, Some(&node.block));
+        self.enter_spawn_bindings(Some(&node.block));
+        self.enter_future_generics(&mut node.sig.generics);
+        Ok(())
+    }
 
-                    acc_temp.clear();
-                    lines.clear();
-                    inside_doc = false;
+    fn after_process_impl_item_method(
+        &mut self,
+        node: &mut syn::ImplItemMethod,
+    ) -> syn::Result<()> {
+        self.leave_future_generics();
+        self.leave_lifetimes(&mut node.sig, Some(&node.block));
+        self.leave_lock_bindings();
+        self.leave_spawn_bindings();
+        if self.leave_scope() {
+            node.sig.asyncness = Some(Default::default());
+        }
+        if self.send_bounds_added() && returns_future(&node.sig) {
+            add_send_bounds(&mut node.sig);
+        }
+        if self.impl_future_desugared(&node.sig) && self.trait_impls.last() == Some(&true) {
+            make_impl_future_sig(&mut node.sig, self.params.send_get() != Some(false));
+            let body = &node.block;
+            node.block = parse_quote!({ async move #body });
+        }
+        Ok(())
+    }
 
-                    acc.push(attr);
-                }
-                (true, true) => {
-                    let es = syn::parse2::<EqStr>(attr.tokens.clone())?;
-                    let doc = es.str.value();
+    fn after_process_item_impl(&mut self, _node: &mut syn::ItemImpl) -> syn::Result<()> {
+        self.trait_impls.pop();
+        Ok(())
+    }
 
-                    lines.push(doc);
-                    acc_temp.push(attr);
+    fn process_item_trait(&mut self, node: &mut syn::ItemTrait) -> syn::Result<()> {
+        let mut items = vec![];
+        for mut item in std::mem::take(&mut node.items) {
+            let keep = match &mut item {
+                syn::TraitItem::Type(ty) => {
+                    self.process_assoc_type(&mut ty.attrs, &ty.ident)?
+                        && self
+                            .process_future_assoc_type(&ty.ident, future_bounds_output(&ty.bounds))
+                }
+                syn::TraitItem::Method(method) => {
+                    self.process_receiver(&mut method.attrs, &mut method.sig)?;
+                    true
                 }
+                _ => true,
+            };
+            if keep {
+                items.push(item);
             }
         }
+        node.items = items;
+
+        let has_futures = node.items.iter().any(|item| match item {
+            syn::TraitItem::Method(method) => returns_future(&method.sig),
+            _ => false,
+        });
+        if self.send_bounds_added() && has_futures {
+            add_send_sync_bounds(&mut node.generics);
+        }
 
-        if inside_doc {
-            process_docs(&mut acc, &mut acc_temp, &mut lines, &self.params);
+        if let ConvertMode::IntoAsync = self.convert_mode {
+            if let Some(path) = self.params.dyn_compat_get() {
+                let dyn_ident = quote::format_ident!("Dyn{}", node.ident);
+                node.attrs.push(parse_quote!(#[#path(#dyn_ident)]));
+            }
+            if let Some(path) = self.params.trait_variant_get() {
+                node.attrs.push(parse_quote!(#[#path(Send)]));
+            }
         }
 
-        let _ = std::mem::replace(attrs, acc);
+        remove_asyncness_on_trait(
+            node,
+            self.convert_mode,
+            self.async_trait_send(),
+            self.params.async_trait_path_get(),
+        );
 
         Ok(())
     }
 
-    fn process_attrs(&mut self, attrs: &mut Vec<syn::Attribute>) -> syn::Result<()> {
-        #[cfg(feature = "doctests")]
-        self.process_doc_attrs(attrs)?;
-
-        for attr in attrs.iter_mut() {
-            if let Some(name) = self.params.is_our_attr(attr) {
-                match name.as_str() {
-                    MACRO_ONLY_IF_NAME => self.process_attribute_if(attr, false)?,
-                    MACRO_REMOVE_IF_NAME => self.process_attribute_if(attr, true)?,
-                    _ => {
-                        // Attribute stays unchanged. Unknown attributes will be
-                        // rejected by compiter later.
-                    }
-                }
+    fn process_trait_item_method(&mut self, node: &mut syn::TraitItemMethod) -> syn::Result<()> {
+        if let ConvertMode::IntoAsync = self.convert_mode {
+            if self.params.add_await_methods_contains(&node.sig.ident) {
+                node.sig.asyncness = Some(Default::default());
             }
         }
+        self.enter_fn_scope(&mut node.sig.asyncness, true);
+        self.enter_lifetimes(&node.sig, node.default.as_ref());
+        self.enter_lock_bindings(&node.sig, node.default.as_ref());
+        self.enter_spawn_bindings(node.default.as_ref());
+        self.enter_future_generics(&mut node.sig.generics);
+        Ok(())
+    }
 
-        if !self.params.drop_attrs_is_empty() {
-            attrs.retain(|attr| {
-                if let Some(ident) = attr.path.get_ident() {
-                    let ident = ident.to_string();
-                    !self.params.drop_attrs_contains(&ident)
-                } else {
-                    true
-                }
-            });
+    fn after_process_trait_item_method(
+        &mut self,
+        node: &mut syn::TraitItemMethod,
+    ) -> syn::Result<()> {
+        self.leave_future_generics();
+        self.leave_lifetimes(&mut node.sig, node.default.as_ref());
+        self.leave_lock_bindings();
+        self.leave_spawn_bindings();
+        if self.leave_scope() {
+            node.sig.asyncness = Some(Default::default());
         }
-
-        if !self.params.replace_features_is_empty() {
-            for attr in attrs {
-                if let Some(ident) = attr.path.get_ident() {
-                    if ident.to_string() == "cfg" {
-                        if let Ok(mut meta) = attr.parse_meta() {
-                            if self.process_replace_features_meta(&mut meta)? {
-                                if let syn::Meta::List(syn::MetaList { nested, .. }) = meta {
-                                    attr.tokens = quote!((#nested));
-                                }
-                            }
-                        }
-                    }
+        if self.send_bounds_added() && returns_future(&node.sig) {
+            add_send_bounds(&mut node.sig);
+        }
+        if self.impl_future_desugared(&node.sig) {
+            let send = self.params.send_get() != Some(false);
+            make_impl_future_sig(&mut node.sig, send);
+            if let Some(body) = &node.default {
+                node.default = Some(parse_quote!({ async move #body }));
+                // the default body borrows `self` in a `Send` future
+                if send {
+                    add_receiver_send_bound(&mut node.sig);
                 }
             }
         }
-
         Ok(())
     }
 
-    fn process_expr(&mut self, node: &mut syn::Expr) -> syn::Result<()> {
-        match self.convert_mode {
-            ConvertMode::IntoSync => {
-                // async -> sync, remove async_impl blocks
-                match node {
-                    syn::Expr::Await(expr) => *node = (*expr.base).clone(),
+    fn process_type(&mut self, node: &mut syn::Type) -> syn::Result<()> {
+        // dyn Client -> dyn Client + Send + Sync (or back)
+        if let syn::Type::TraitObject(ty) = node {
+            if let Some(add) = self.params.dyn_markers_get(self.convert_mode) {
+                apply_dyn_markers(ty, add);
+            }
+        }
 
-                    syn::Expr::Async(expr) => {
-                        let inner = &expr.block;
-                        let sync_expr = if inner.stmts.len() == 1 {
-                            // remove useless braces when there is only one statement
-                            let stmt = &inner.stmts.get(0).unwrap();
-                            // convert statement to Expr
-                            syn::parse_quote!(#stmt)
-                        } else {
-                            syn::Expr::Block(syn::ExprBlock {
-                                attrs: expr.attrs.clone(),
-                                block: inner.clone(),
-                                label: None,
-                            })
-                        };
-                        *node = sync_expr;
-                    }
+        // Fut -> T of the dropped `Fut: Future<Output = T>` parameter
+        if let syn::Type::Path(syn::TypePath { qself: None, path }) = node {
+            if let Some(ty) = path
+                .get_ident()
+                .and_then(|ident| self.generics_get(ident.to_string()))
+            {
+                *node = ty.clone();
+            }
+        }
 
-                    _ => {}
+        // replace `Self::Type` with the definition of the associated type
+        if let syn::Type::Path(syn::TypePath { qself: None, path }) = node {
+            if path.leading_colon.is_none()
+                && path.segments.len() == 2
+                && path.segments[0].ident == "Self"
+            {
+                if let Some(ty) = self.assoc_types.get(&path.segments[1].ident.to_string()) {
+                    *node = ty.clone();
                 }
             }
-            ConvertMode::IntoAsync => {
-                // stay async, just remove sync_impl blocks
-                match node {
-                    _ => {}
+        }
+
+        if let ConvertMode::IntoSync = self.convert_mode {
+            if let Some(ty) = box_stream_to_iter(node) {
+                *node = ty;
+            } else if self.params.asyncness_removal_get().closures {
+                // the future becomes its output, as the async block returned is unwrapped (see
+                // `box_pin_async_arg`)
+                if let Some(ty) = future_output_type(node) {
+                    *node = ty;
                 }
             }
-        };
+        }
 
         Ok(())
     }
 
-    fn process_item(&mut self, node: &mut syn::Item) -> syn::Result<()> {
-        match self.convert_mode {
-            ConvertMode::IntoSync => {
-                // find generic parameter of Future and replace it with its Output type
-                if let syn::Item::Fn(item_fn) = node {
-                    let mut gens: HashMap<String, syn::PathSegment> = HashMap::new();
-
-                    // generic params: <T:Future<Output=()>, F>
-                    for param in &item_fn.sig.generics.params {
-                        // generic param: T:Future<Output=()>
-                        if let syn::GenericParam::Type(type_param) = param {
-                            let generic_type_name = &type_param.ident;
-
-                            // bound: Future<Output=()>
-                            for bound in &type_param.bounds {
-                                if let Some(ps) = search_future_trait_bound(bound) {
-                                    gens.insert(generic_type_name.to_string(), ps);
-                                }
-                            }
-                        }
-                    }
-
-                    if let Some(where_clause) = &item_fn.sig.generics.where_clause {
-                        for predicate in &where_clause.predicates {
-                            if let syn::WherePredicate::Type(predicate_type) = predicate {
-                                let generic_type_name =
-                                    if let syn::Type::Path(p) = &predicate_type.bounded_ty {
-                                        &p.path.segments[0].ident
-                                    } else {
-                                        panic!("Please submit an issue");
-                                    };
-
-                                for bound in &predicate_type.bounds {
-                                    if let Some(ps) = search_future_trait_bound(bound) {
-                                        gens.insert(generic_type_name.to_string(), ps);
-                                    }
-                                }
-                            }
-                        }
+    fn process_bounds(
+        &mut self,
+        node: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>,
+    ) -> syn::Result<()> {
+        // AsyncRead + Unpin -> ::std::io::Read
+        if let ConvertMode::IntoSync = self.convert_mode {
+            let mut converted = false;
+            for bound in node.iter_mut() {
+                if let syn::TypeParamBound::Trait(bound) = bound {
+                    if let Some(path) = async_io_trait_to_std(&bound.path) {
+                        bound.path = path;
+                        converted = true;
                     }
-
-                    self.generics.push(gens);
-                }
-
-                if let syn::Item::Fn(item_fn) = node {
-                    // remove generic type from generics <T, F>
-                    let args = item_fn
-                        .sig
-                        .generics
-                        .params
-                        .iter()
-                        .filter_map(|param| {
-                            if let syn::GenericParam::Type(type_param) = &param {
-                                if let Some(_) = self.generics_get(type_param.ident.to_string()) {
-                                    return None;
-                                }
-                            };
-                            Some(param)
-                        })
-                        .collect::<Vec<_>>();
-
-                    item_fn.sig.generics.params = syn::punctuated::Punctuated::from_iter(
-                        args.into_iter().map(|p| p.clone()).collect::<Vec<_>>(),
-                    );
-
-                    // remove generic type from where clause
-                    if let Some(where_clause) = &mut item_fn.sig.generics.where_clause {
-                        let new_where_clause = where_clause
-                            .predicates
-                            .iter()
-                            .filter_map(|predicate| {
-                                if let syn::WherePredicate::Type(predicate_type) = predicate {
-                                    if let syn::Type::Path(p) = &predicate_type.bounded_ty {
-                                        if let Some(_) =
-                                            self.generics_get(p.path.segments[0].ident.to_string())
-                                        {
-                                            return None;
-                                        }
-                                    }
-                                };
-                                Some(predicate)
-                            })
-                            .collect::<Vec<_>>();
-
-                        where_clause.predicates = syn::punctuated::Punctuated::from_iter(
-                            new_where_clause
-                                .into_iter()
-                                .map(|c| c.clone())
-                                .collect::<Vec<_>>(),
-                        );
-                    };
                 }
             }
-            ConvertMode::IntoAsync => {}
-        };
+            if converted {
+                *node = std::mem::take(node)
+                    .into_iter()
+                    .filter(|bound| match bound {
+                        syn::TypeParamBound::Trait(bound) => !bound.path.is_ident("Unpin"),
+                        _ => true,
+                    })
+                    .collect();
+            }
+        }
 
         Ok(())
     }
 
-    fn after_process_item(&mut self, node: &mut syn::Item) -> syn::Result<()> {
-        match self.convert_mode {
-            ConvertMode::IntoSync => {
-                // find generic parameter of Future and replace it with its Output type
-                if let syn::Item::Fn(_item_fn) = node {
-                    self.generics.pop();
-                }
+    fn process_trait_bound(&mut self, node: &mut syn::TraitBound) -> syn::Result<()> {
+        // Stream<Item = T> -> Iterator<Item = T>
+        if let ConvertMode::IntoSync = self.convert_mode {
+            if let Some(item) = search_stream_item(&node.path) {
+                node.path = parse_quote!(Iterator<Item = #item>);
             }
-            _ => {}
         }
-        Ok(())
-    }
 
-    fn process_item_impl(&mut self, node: &mut syn::ItemImpl) -> syn::Result<()> {
-        if self.params.recursive_asyncness_removal_get() {
-            remove_asyncness_on_impl(node, self.convert_mode, self.params.send_get());
-        };
+        // AsyncFnMut(A) -> R -> FnMut(A) -> R
+        if let ConvertMode::IntoSync = self.convert_mode {
+            if self.params.asyncness_removal_get().closures {
+                if let Some(path) = async_fn_trait_to_fn(&node.path) {
+                    node.path = path;
+                }
+            }
+        }
 
         Ok(())
     }
 
-    fn process_item_trait(&mut self, node: &mut syn::ItemTrait) -> syn::Result<()> {
-        if self.params.recursive_asyncness_removal_get() {
-            remove_asyncness_on_trait(node, self.convert_mode);
-        };
+    fn process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
+        if let Some(test_attr) = self.params.test_attr_get() {
+            for attr in &mut node.attrs {
+                if attr.path.is_ident("test") && attr.tokens.is_empty() {
+                    *attr = make_attr_from_str(test_attr, attr.span())?;
+                }
+            }
+        }
 
+        if let Some(main_attr) = self.params.main_attr_get() {
+            if self.item_depth == 0 && node.sig.ident == "main" {
+                node.attrs
+                    .push(make_attr_from_str(main_attr, node.sig.ident.span())?);
+            }
+        }
+
+        if let ConvertMode::IntoAsync = self.convert_mode {
+            if self.params.add_await_fns_contains(&node.sig.ident) {
+                node.sig.asyncness = Some(Default::default());
+            }
+        }
+        self.enter_fn_scope(&mut node.sig.asyncness, false);
+        self.enter_lifetimes(&node.sig, Some(&node.block));
+        self.enter_lock_bindings(&node.sig, Some(&node.block));
+        self.enter_spawn_bindings(Some(&node.block));
+        self.enter_future_generics(&mut node.sig.generics);
         Ok(())
     }
 
-    fn process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
-        if self.params.recursive_asyncness_removal_get() {
-            remove_asyncness_on_fn(node, self.convert_mode);
-        };
-
+    fn after_process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
+        self.leave_future_generics();
+        self.leave_lifetimes(&mut node.sig, Some(&node.block));
+        self.leave_lock_bindings();
+        self.leave_spawn_bindings();
+        if self.leave_scope() {
+            node.sig.asyncness = Some(Default::default());
+        }
+        if self.send_bounds_added() && returns_future(&node.sig) {
+            add_send_bounds(&mut node.sig);
+        }
         Ok(())
     }
 
-    fn process_path_segment(&mut self, node: &mut syn::PathSegment) -> syn::Result<()> {
-        let ident = &mut node.ident;
-        let ident_s = ident.to_string();
-
-        // replace generic type with target type
-        if let Some(ps) = self.generics_get(&ident_s) {
-            *node = ps.clone();
-            return Ok(());
+    fn process_path(&mut self, node: &mut syn::Path) -> syn::Result<()> {
+        // tokio::sync::Mutex -> std::sync::Mutex
+        if let ConvertMode::IntoSync = self.convert_mode {
+            if self.params.locks_get().is_some() {
+                let mut segments = node.segments.iter_mut();
+                if let (Some(first), Some(second), Some(third)) =
+                    (segments.next(), segments.next(), segments.next())
+                {
+                    if is_async_lock(&first.ident, &second.ident, &third.ident) {
+                        lock_path_to_std(&mut first.ident, &mut second.ident);
+                    }
+                }
+            }
+        }
+        // tokio::sync::mpsc::Sender -> std::sync::mpsc::SyncSender
+        path_map_apply(node, &self.path_map);
+
+        // tokio_postgres::Client -> postgres::Client
+        if node.segments.len() > 1 || node.leading_colon.is_some() {
+            if let Some(first) = node.segments.first_mut() {
+                if let Some(krate) = self.params.crates_get(&first.ident) {
+                    first.ident = krate;
+                }
+            }
         }
 
         Ok(())
@@ -563,7 +3402,13 @@ impl<'p> AsyncAwaitVisitor<'p> {
         };
 
         if let Some(ir) = self.params.idents_get(ident.to_string()) {
-            *ident = ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get());
+            *ident = ir.ident_add_suffix(
+                ident,
+                self.convert_mode,
+                self.params.key_get(),
+                self.params.primary_get(),
+                self.params.suffix_get(),
+            );
             return Ok(());
         }
 
@@ -574,19 +3419,104 @@ impl<'p> AsyncAwaitVisitor<'p> {
         let ident = &mut node.ident;
 
         if let Some(ir) = self.params.idents_get(&ident.to_string()) {
-            *ident = ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get());
+            *ident = ir.ident_add_suffix(
+                ident,
+                self.convert_mode,
+                self.params.key_get(),
+                self.params.primary_get(),
+                self.params.suffix_get(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn process_generics(&mut self, node: &mut syn::Generics) -> syn::Result<()> {
+        // T: Send + 'static -> T
+        if let ConvertMode::IntoSync = self.convert_mode {
+            self.params.bounds_relax(node);
+        }
+
+        Ok(())
+    }
+
+    fn after_process_item_use(&mut self, node: &mut syn::ItemUse) -> syn::Result<()> {
+        // use tokio_postgres::Client -> use postgres::Client
+        match &mut node.tree {
+            syn::UseTree::Path(path) => {
+                if let Some(krate) = self.params.crates_get(&path.ident) {
+                    path.ident = krate;
+                }
+            }
+            // use tokio_postgres; -> use postgres as tokio_postgres;
+            syn::UseTree::Name(name) => {
+                if let Some(krate) = self.params.crates_get(&name.ident) {
+                    let rename = name.ident.clone();
+                    node.tree = parse_quote!(#krate as #rename);
+                }
+            }
+            syn::UseTree::Rename(rename) => {
+                if let Some(krate) = self.params.crates_get(&rename.ident) {
+                    rename.ident = krate;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn process_expr_method_call(&mut self, node: &mut syn::ExprMethodCall) -> syn::Result<()> {
+        // x.read_async() -> x.read()
+        if let Some(method) = self.params.methods_get(&node.method) {
+            node.method = method;
         }
 
         Ok(())
     }
 
     fn process_use_tree(&mut self, node: &mut syn::UseTree) -> syn::Result<()> {
+        if let ConvertMode::IntoSync = self.convert_mode {
+            if self.params.locks_get().is_some() {
+                if let syn::UseTree::Path(first) = node {
+                    if let syn::UseTree::Path(second) = &mut *first.tree {
+                        let is_lock = |tree: &syn::UseTree| match tree {
+                            syn::UseTree::Name(name) => {
+                                is_async_lock(&first.ident, &second.ident, &name.ident)
+                            }
+                            syn::UseTree::Rename(rename) => {
+                                is_async_lock(&first.ident, &second.ident, &rename.ident)
+                            }
+                            _ => false,
+                        };
+                        // `use tokio::sync::{Mutex, RwLock}` is mapped only if all the items are
+                        // locks
+                        let matched = match &*second.tree {
+                            syn::UseTree::Group(group) => {
+                                !group.items.is_empty() && group.items.iter().all(is_lock)
+                            }
+                            tree => is_lock(tree),
+                        };
+                        if matched {
+                            lock_path_to_std(&mut first.ident, &mut second.ident);
+                        }
+                    }
+                }
+            }
+        }
+        use_tree_map_apply(node, &self.path_map);
+
         match node {
             syn::UseTree::Path(syn::UsePath { ident, .. }) => {
                 if let Some(ir) = self.params.idents_get(&ident.to_string()) {
                     if !ir.use_mode {
-                        *ident =
-                            ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get());
+                        *ident = ir.ident_add_suffix(
+                            ident,
+                            self.convert_mode,
+                            self.params.key_get(),
+                            self.params.primary_get(),
+                            self.params.suffix_get(),
+                        );
                     }
                 }
             }
@@ -602,11 +3532,18 @@ impl<'p> AsyncAwaitVisitor<'p> {
                                 ident,
                                 self.convert_mode,
                                 self.params.key_get(),
+                                self.params.primary_get(),
+                                self.params.suffix_get(),
                             ),
                         });
                     } else {
-                        *ident =
-                            ir.ident_add_suffix(ident, self.convert_mode, self.params.key_get());
+                        *ident = ir.ident_add_suffix(
+                            ident,
+                            self.convert_mode,
+                            self.params.key_get(),
+                            self.params.primary_get(),
+                            self.params.suffix_get(),
+                        );
                     }
                 }
             }
@@ -627,9 +3564,27 @@ impl<'p> VisitMutExt for Visitor<AsyncAwaitVisitor<'p>> {
     fn process_expr(&mut self, node: &mut syn::Expr) -> syn::Result<()> {
         self.inner.process_expr(node)
     }
+    fn process_expr_async(&mut self, node: &mut syn::ExprAsync) -> syn::Result<()> {
+        self.inner.process_expr_async(node)
+    }
+    fn process_expr_closure(&mut self, node: &mut syn::ExprClosure) -> syn::Result<()> {
+        self.inner.process_expr_closure(node)
+    }
+    fn process_block(&mut self, node: &mut syn::Block) -> syn::Result<()> {
+        self.inner.process_block(node)
+    }
+    fn process_stmt(&mut self, node: &mut syn::Stmt) -> syn::Result<()> {
+        self.inner.process_stmt(node)
+    }
     fn process_item(&mut self, node: &mut syn::Item) -> syn::Result<()> {
         self.inner.process_item(node)
     }
+    fn process_fields(&mut self, node: &mut syn::Fields) -> syn::Result<()> {
+        self.inner.process_fields(node)
+    }
+    fn process_item_enum(&mut self, node: &mut syn::ItemEnum) -> syn::Result<()> {
+        self.inner.process_item_enum(node)
+    }
     fn process_item_impl(&mut self, node: &mut syn::ItemImpl) -> syn::Result<()> {
         self.inner.process_item_impl(node)
     }
@@ -639,38 +3594,174 @@ impl<'p> VisitMutExt for Visitor<AsyncAwaitVisitor<'p>> {
     fn process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
         self.inner.process_item_fn(node)
     }
+    fn process_impl_item_method(&mut self, node: &mut syn::ImplItemMethod) -> syn::Result<()> {
+        self.inner.process_impl_item_method(node)
+    }
+    fn process_trait_item_method(&mut self, node: &mut syn::TraitItemMethod) -> syn::Result<()> {
+        self.inner.process_trait_item_method(node)
+    }
+    fn after_process_expr_async(&mut self, node: &mut syn::ExprAsync) -> syn::Result<()> {
+        self.inner.after_process_expr_async(node)
+    }
+    fn after_process_expr_closure(&mut self, node: &mut syn::ExprClosure) -> syn::Result<()> {
+        self.inner.after_process_expr_closure(node)
+    }
+    fn after_process_impl_item_method(
+        &mut self,
+        node: &mut syn::ImplItemMethod,
+    ) -> syn::Result<()> {
+        self.inner.after_process_impl_item_method(node)
+    }
+    fn after_process_item_impl(&mut self, node: &mut syn::ItemImpl) -> syn::Result<()> {
+        self.inner.after_process_item_impl(node)
+    }
     fn after_process_item(&mut self, node: &mut syn::Item) -> syn::Result<()> {
         self.inner.after_process_item(node)
     }
+    fn after_process_item_fn(&mut self, node: &mut syn::ItemFn) -> syn::Result<()> {
+        self.inner.after_process_item_fn(node)
+    }
+    fn after_process_trait_item_method(
+        &mut self,
+        node: &mut syn::TraitItemMethod,
+    ) -> syn::Result<()> {
+        self.inner.after_process_trait_item_method(node)
+    }
 
     fn process_macro(&mut self, node: &mut syn::Macro) -> syn::Result<()> {
-        if let Some(ident) = node.path.get_ident() {
-            if self
+        let disabled = self.inner.params.macro_processing_disabled(&node.path);
+
+        let standard = match node.path.get_ident() {
+            Some(ident) => self
                 .inner
                 .params
                 .standard_macros()
-                .contains(&ident.to_string().as_str())
-            {
-                let mut args = syn::parse2::<PunctuatedList>(node.tokens.clone())?;
+                .contains(&ident.to_string().as_str()),
+            // the `join!` macros left in the async variant
+            None => join_macro_kind(node).is_some(),
+        };
+        if standard && !disabled {
+            let mut args = syn::parse2::<PunctuatedList>(node.tokens.clone())?;
 
-                for arg in &mut args.list {
-                    self.visit_expr_mut(arg);
+            for arg in &mut args.list {
+                self.visit_expr_mut(arg);
+            }
+
+            node.tokens = args.list.into_token_stream();
+            return Ok(());
+        }
+
+        if let Some(stream_macro) = self.inner.params.stream_macro_get() {
+            if node.path.to_token_stream().to_string()
+                == syn::parse_str::<syn::Path>(stream_macro)?
+                    .to_token_stream()
+                    .to_string()
+            {
+                let mut stmts = node.parse_body_with(syn::Block::parse_within)?;
+                for stmt in &mut stmts {
+                    self.visit_stmt_mut(stmt);
                 }
+                node.tokens = quote!(#(#stmts)*);
+                return Ok(());
+            }
+        }
 
-                node.tokens = args.list.into_token_stream();
+        if !disabled
+            && (self.inner.params.process_all_macros_get()
+                || self.inner.params.process_macros_contains(&node.path))
+        {
+            // the arguments are converted as expressions if possible, as raw tokens otherwise
+            match syn::parse2::<PunctuatedList>(node.tokens.clone()) {
+                Ok(mut args) => {
+                    for arg in &mut args.list {
+                        self.visit_expr_mut(arg);
+                    }
+                    node.tokens = args.list.into_token_stream();
+                }
+                Err(_) => node.tokens = self.process_macro_tokens(node.tokens.clone())?,
             }
-        };
+        }
+
         Ok(())
     }
-    fn process_path_segment(&mut self, node: &mut syn::PathSegment) -> syn::Result<()> {
-        self.inner.process_path_segment(node)
+    fn process_path(&mut self, node: &mut syn::Path) -> syn::Result<()> {
+        self.inner.process_path(node)
+    }
+    fn process_bounds(
+        &mut self,
+        node: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Add>,
+    ) -> syn::Result<()> {
+        self.inner.process_bounds(node)
+    }
+    fn process_trait_bound(&mut self, node: &mut syn::TraitBound) -> syn::Result<()> {
+        self.inner.process_trait_bound(node)
+    }
+    fn process_type(&mut self, node: &mut syn::Type) -> syn::Result<()> {
+        self.inner.process_type(node)
     }
     fn process_type_param(&mut self, node: &mut syn::TypeParam) -> syn::Result<()> {
         self.inner.process_type_param(node)
     }
+    fn process_generics(&mut self, node: &mut syn::Generics) -> syn::Result<()> {
+        self.inner.process_generics(node)
+    }
+    fn process_expr_method_call(&mut self, node: &mut syn::ExprMethodCall) -> syn::Result<()> {
+        self.inner.process_expr_method_call(node)
+    }
+    fn after_process_item_use(&mut self, node: &mut syn::ItemUse) -> syn::Result<()> {
+        self.inner.after_process_item_use(node)
+    }
     fn process_use_tree(&mut self, node: &mut syn::UseTree) -> syn::Result<()> {
         self.inner.process_use_tree(node)
     }
 }
 
 impl<'p> AsyncAwaitVisitor<'p> {}
+
+impl<'p> Visitor<AsyncAwaitVisitor<'p>> {
+    /// Converts the tokens of a macro which cannot be parsed as expressions: renames the listed
+    /// identifiers and, in the sync variant, removes `.await` and `async`.
+    fn process_macro_tokens(&mut self, ts: TokenStream2) -> syn::Result<TokenStream2> {
+        let sync = self.inner.convert_mode == ConvertMode::IntoSync;
+        let mut result = vec![];
+        let mut tokens = ts.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                proc_macro2::TokenTree::Punct(punct) if sync && punct.as_char() == '.' => {
+                    match tokens.peek() {
+                        Some(proc_macro2::TokenTree::Ident(ident)) if ident == "await" => {
+                            tokens.next();
+                        }
+                        _ => result.push(proc_macro2::TokenTree::Punct(punct)),
+                    }
+                }
+                proc_macro2::TokenTree::Ident(ident) if sync && ident == "async" => {
+                    // async move { .. } -> { .. }
+                    let mut rest = tokens.clone();
+                    if let (Some(proc_macro2::TokenTree::Ident(ident)), Some(next)) =
+                        (rest.next(), rest.next())
+                    {
+                        if ident == "move"
+                            && matches!(&next, proc_macro2::TokenTree::Group(group)
+                                if group.delimiter() == proc_macro2::Delimiter::Brace)
+                        {
+                            tokens.next();
+                        }
+                    }
+                }
+                proc_macro2::TokenTree::Ident(mut ident) => {
+                    self.inner.process_ident(&mut ident, IdentMode::Other)?;
+                    result.push(proc_macro2::TokenTree::Ident(ident));
+                }
+                proc_macro2::TokenTree::Group(group) => {
+                    let stream = self.process_macro_tokens(group.stream())?;
+                    let mut new_group = proc_macro2::Group::new(group.delimiter(), stream);
+                    new_group.set_span(group.span());
+                    result.push(proc_macro2::TokenTree::Group(new_group));
+                }
+                token => result.push(token),
+            }
+        }
+        Ok(result.into_iter().collect())
+    }
+}
@@ -7,7 +7,8 @@ use proc_macro2::{Delimiter, Group, TokenStream as TokenStream2, TokenTree};
 use quote::quote;
 
 use crate::{
-    params::MacroParameters,
+    params::{ConvertMode, MacroParameters},
+    utils::make_warning_block,
     visit_ext::{VisitMutExt, Visitor},
     DEFAULT_CRATE_NAME, MACRO_DEFAULT_NAME, MACRO_MAYBE_NAME,
 };
@@ -23,17 +24,109 @@ impl ContentVisitor {
         }
     }
 
-    fn process_attribute_maybe(&mut self, node: &mut syn::Attribute) -> syn::Result<()> {
-        let mut params = MacroParameters::from_tokens_in_parens(node.tokens.clone().into())?;
+    /// Returns the parameters of the `maybe` attribute with the defaults applied.
+    fn maybe_params(&self, node: &syn::Attribute) -> syn::Result<MacroParameters> {
+        let mut params = if node.tokens.is_empty() {
+            MacroParameters::new()
+        } else {
+            MacroParameters::from_tokens_in_parens(node.tokens.clone().into())?
+        };
 
         MacroParameters::apply_parent(&mut params, &self.params)?;
 
-        let tokens = params.to_tokens(None);
+        // items without own variants get the variants of the defaults
+        if params.versions.is_empty() {
+            params.versions = self.params.versions.clone();
+        }
+
+        Ok(params)
+    }
+
+    fn process_attribute_maybe(&mut self, node: &mut syn::Attribute) -> syn::Result<()> {
+        let tokens = self.maybe_params(node)?.to_tokens(None);
         node.tokens = quote!((#tokens));
 
         Ok(())
     }
 
+    /// Returns the `send` parameters of the async variants of the item (by variant key), if it has
+    /// the `maybe` attribute.
+    fn async_sends(&self, attrs: &[syn::Attribute]) -> Option<Vec<(String, Option<bool>)>> {
+        let attr = attrs
+            .iter()
+            .find(|attr| self.params.is_our_attr(attr).as_deref() == Some(MACRO_MAYBE_NAME))?;
+        // the variants are built again to get the inherited parameters
+        let tokens = self.maybe_params(attr).ok()?.to_tokens(None);
+        let params = MacroParameters::from_tokens(tokens.into()).ok()?;
+        let sends = params
+            .versions
+            .iter()
+            .filter(|version| matches!(version.kind, ConvertMode::IntoAsync))
+            .map(|version| {
+                let key = version.params.key_get().unwrap_or_default().to_string();
+                // `send = "trait_variant"` needs no attribute on the impl blocks, as no `send` at all
+                let send = match version.params.trait_variant_get() {
+                    Some(_) => None,
+                    None => version.params.send_get(),
+                };
+                (key, send)
+            })
+            .collect();
+        Some(sends)
+    }
+
+    /// Warns about the impl blocks whose `send` differs from the one of their trait (which results
+    /// in `async_trait` errors in generated code). Only the traits in the same `content!` are known.
+    fn check_impl_sends(&self, node: &mut syn::File) {
+        let mut traits = vec![];
+        for item in &node.items {
+            if let syn::Item::Trait(item) = item {
+                if let Some(sends) = self.async_sends(&item.attrs) {
+                    traits.push((item.ident.to_string(), sends));
+                }
+            }
+        }
+
+        let mut warnings = vec![];
+        for item in &node.items {
+            let item = match item {
+                syn::Item::Impl(item) => item,
+                _ => continue,
+            };
+            let name = match &item.trait_ {
+                Some((_, path, _)) => match path.segments.last() {
+                    Some(last) => last.ident.to_string(),
+                    None => continue,
+                },
+                None => continue,
+            };
+            let trait_sends = match traits.iter().find(|(n, _)| *n == name) {
+                Some((_, sends)) => sends,
+                None => continue,
+            };
+            for (key, send) in self.async_sends(&item.attrs).unwrap_or_default() {
+                let trait_send = match trait_sends.iter().find(|(k, _)| *k == key) {
+                    Some((_, trait_send)) => *trait_send,
+                    None => continue,
+                };
+                if trait_send != send {
+                    warnings.push(make_warning_block(&format!(
+                        "Trait `{}` is declared with {} in the `{}` variant, but its impl block \
+                        has {}",
+                        name,
+                        describe_send(trait_send),
+                        key,
+                        describe_send(send),
+                    )));
+                }
+            }
+        }
+
+        for warning in warnings {
+            node.items.push(syn::parse_quote!(const _: () = #warning;));
+        }
+    }
+
     fn process_file(&mut self, node: &mut syn::File) -> syn::Result<()> {
         node.attrs.retain(|attr| {
             if let Some(prefix) = is_default_attr(attr) {
@@ -48,6 +141,7 @@ impl ContentVisitor {
                 true
             }
         });
+        self.check_impl_sends(node);
 
         Ok(())
     }
@@ -201,6 +295,184 @@ impl VisitMutExt for Visitor<ContentVisitor> {
     }
 }
 
+/// Moves the entries of grouped imports that have attributes (e.g.
+/// `use a::{B, #[only_if(async)] C};`) to separate `use` items (`use a::{B}; #[only_if(async)] use
+/// a::C;`), since attributes are not allowed inside use trees. Groups left empty are removed.
+pub fn split_use_trees(ts: TokenStream2) -> TokenStream2 {
+    let mut result: Vec<TokenTree> = vec![];
+    let mut iter = ts.into_iter();
+
+    while let Some(tt) = iter.next() {
+        match tt {
+            TokenTree::Ident(ident) if ident == "use" => {
+                let mut tree = vec![];
+                let mut semi = None;
+                for tt in &mut iter {
+                    match &tt {
+                        TokenTree::Punct(p) if p.as_char() == ';' => {
+                            semi = Some(tt);
+                            break;
+                        }
+                        _ => tree.push(tt),
+                    }
+                }
+
+                let semi = match semi {
+                    Some(semi) if has_attrs_in_groups(&tree) => semi,
+                    semi => {
+                        result.push(TokenTree::Ident(ident));
+                        result.extend(tree);
+                        result.extend(semi);
+                        continue;
+                    }
+                };
+
+                // attributes and visibility of the `use` item are repeated on every split item
+                let start = use_item_start(&result);
+                let prefix = result.split_off(start);
+
+                let (kept, split) = split_use_tree(tree);
+                if let Some(kept) = kept {
+                    result.extend(prefix.clone());
+                    result.push(TokenTree::Ident(ident.clone()));
+                    result.extend(kept);
+                    result.push(semi.clone());
+                }
+                for (attrs, tree) in split {
+                    result.extend(prefix[..attrs_len(&prefix)].iter().cloned());
+                    result.extend(attrs);
+                    result.extend(prefix[attrs_len(&prefix)..].iter().cloned());
+                    result.push(TokenTree::Ident(ident.clone()));
+                    result.extend(tree);
+                    result.push(semi.clone());
+                }
+            }
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), split_use_trees(g.stream()));
+                group.set_span(g.span());
+                result.push(TokenTree::Group(group));
+            }
+            tt => result.push(tt),
+        }
+    }
+
+    TokenStream2::from_iter(result)
+}
+
+fn is_attr_start(tokens: &[TokenTree]) -> bool {
+    match tokens {
+        [TokenTree::Punct(p), TokenTree::Group(g), ..] => {
+            p.as_char() == '#' && g.delimiter() == Delimiter::Bracket
+        }
+        _ => false,
+    }
+}
+
+fn has_attrs_in_groups(tokens: &[TokenTree]) -> bool {
+    tokens.iter().any(|tt| match tt {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => {
+            let inner = g.stream().into_iter().collect::<Vec<_>>();
+            (0..inner.len()).any(|i| is_attr_start(&inner[i..])) || has_attrs_in_groups(&inner)
+        }
+        _ => false,
+    })
+}
+
+/// Returns the position of the first outer attribute or visibility token preceding `use`.
+fn use_item_start(tokens: &[TokenTree]) -> usize {
+    let mut start = tokens.len();
+
+    // visibility: `pub` or `pub(...)`
+    match &tokens[..start] {
+        [.., TokenTree::Ident(ident), TokenTree::Group(g)]
+            if ident == "pub" && g.delimiter() == Delimiter::Parenthesis =>
+        {
+            start -= 2
+        }
+        [.., TokenTree::Ident(ident)] if ident == "pub" => start -= 1,
+        _ => {}
+    }
+
+    while start >= 2 && is_attr_start(&tokens[start - 2..]) {
+        start -= 2;
+    }
+
+    start
+}
+
+/// Returns the length of the attributes at the start of the tokens.
+fn attrs_len(tokens: &[TokenTree]) -> usize {
+    let mut len = 0;
+    while is_attr_start(&tokens[len..]) {
+        len += 2;
+    }
+    len
+}
+
+/// Attributes and the complete use tree of an import entry.
+type AttributedUseTree = (Vec<TokenTree>, Vec<TokenTree>);
+
+/// Splits a use tree into the tree without entries having attributes (`None` if nothing is left)
+/// and the list of those entries (as complete trees) with their attributes.
+fn split_use_tree(tree: Vec<TokenTree>) -> (Option<Vec<TokenTree>>, Vec<AttributedUseTree>) {
+    let (path, group) = match tree.split_last() {
+        Some((TokenTree::Group(g), path)) if g.delimiter() == Delimiter::Brace => {
+            (path.to_vec(), g.clone())
+        }
+        _ => return (Some(tree), vec![]),
+    };
+
+    let mut kept = TokenStream2::new();
+    let mut split = vec![];
+
+    let entries = group.stream().into_iter().collect::<Vec<_>>();
+    for entry in entries.split(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == ',')) {
+        if entry.is_empty() {
+            continue;
+        }
+        let len = attrs_len(entry);
+        let (attrs, entry) = entry.split_at(len);
+
+        let (entry_kept, entry_split) = split_use_tree(entry.to_vec());
+        if let Some(entry_kept) = entry_kept {
+            if attrs.is_empty() {
+                if !kept.is_empty() {
+                    kept.extend(quote!(,));
+                }
+                kept.extend(entry_kept);
+            } else {
+                split.push((attrs.to_vec(), [path.clone(), entry_kept].concat()));
+            }
+        }
+        for (entry_attrs, entry_tree) in entry_split {
+            split.push((
+                [attrs.to_vec(), entry_attrs].concat(),
+                [path.clone(), entry_tree].concat(),
+            ));
+        }
+    }
+
+    let kept = if kept.is_empty() {
+        None
+    } else {
+        let mut kept_group = Group::new(Delimiter::Brace, kept);
+        kept_group.set_span(group.span());
+        let mut tree = path;
+        tree.push(TokenTree::Group(kept_group));
+        Some(tree)
+    };
+
+    (kept, split)
+}
+
+fn describe_send(send: Option<bool>) -> &'static str {
+    match send {
+        Some(true) => "`send = \"Send\"`",
+        Some(false) => "`send = \"?Send\"`",
+        None => "no `send`",
+    }
+}
+
 fn is_default_attr(attr: &syn::Attribute) -> Option<String> {
     if let syn::AttrStyle::Inner(_) = attr.style {
         if attr.path.leading_colon.is_none() && attr.path.segments.len() == 2 {
@@ -0,0 +1,69 @@
+use std::future::Future;
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+pub struct Blocking<T>(T);
+
+pub struct Nonblocking<T>(T);
+
+#[maybe_async_cfg2::maybe(
+    idents(Client),
+    sync(all(), bridge(wrapper = "Blocking", block_on = "crate::block_on")),
+    async(all(), bridge(wrapper = "Nonblocking"))
+)]
+trait Client {
+    const SCHEME: &'static str;
+
+    async fn request(&self, uri: &str) -> String;
+
+    fn name(&self) -> String;
+}
+
+struct SyncClient;
+
+impl ClientSync for SyncClient {
+    const SCHEME: &'static str = "sync";
+
+    fn request(&self, uri: &str) -> String {
+        format!("sync {}", uri)
+    }
+
+    fn name(&self) -> String {
+        "sync".to_string()
+    }
+}
+
+struct AsyncClient;
+
+impl ClientAsync for AsyncClient {
+    const SCHEME: &'static str = "async";
+
+    async fn request(&self, uri: &str) -> String {
+        format!("async {}", uri)
+    }
+
+    fn name(&self) -> String {
+        "async".to_string()
+    }
+}
+
+#[test]
+fn sync_trait_over_async_implementor() {
+    let client = Blocking(AsyncClient);
+    assert_eq!(ClientSync::request(&client, "/a"), "async /a");
+    assert_eq!(ClientSync::name(&client), "async");
+    assert_eq!(<Blocking<AsyncClient> as ClientSync>::SCHEME, "async");
+}
+
+#[tokio::test]
+async fn async_trait_over_sync_implementor() {
+    let client = Nonblocking(SyncClient);
+    assert_eq!(ClientAsync::request(&client, "/b").await, "sync /b");
+    assert_eq!(ClientAsync::name(&client), "sync");
+    assert_eq!(<Nonblocking<SyncClient> as ClientAsync>::SCHEME, "sync");
+}
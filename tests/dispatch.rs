@@ -0,0 +1,53 @@
+#[maybe_async_cfg2::maybe(sync(all()), async(all()))]
+pub struct Client {
+    base: u32,
+}
+
+#[maybe_async_cfg2::maybe(dispatch, idents(Client), sync(all()), async(all()))]
+impl Client {
+    pub async fn get(&self, key: u32) -> u32 {
+        self.base + key
+    }
+
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+}
+
+#[tokio::test]
+async fn either_forwards_to_both_variants() {
+    let clients = [
+        ClientEither::Sync(ClientSync { base: 10 }),
+        ClientEither::Async(ClientAsync { base: 20 }),
+    ];
+    assert_eq!(clients[0].get(1).await, 11);
+    assert_eq!(clients[1].get(1).await, 21);
+    assert_eq!(clients[1].base(), 20);
+}
+
+#[maybe_async_cfg2::maybe(dispatch, sync(all()), async(all()))]
+trait Store {
+    async fn load(&self, key: u32) -> Option<u32>;
+}
+
+struct Doubling;
+
+impl StoreSync for Doubling {
+    fn load(&self, key: u32) -> Option<u32> {
+        Some(key * 2)
+    }
+}
+
+impl StoreAsync for Doubling {
+    async fn load(&self, key: u32) -> Option<u32> {
+        Some(key * 3)
+    }
+}
+
+#[tokio::test]
+async fn either_of_trait_implementors() {
+    let sync: StoreEither<Doubling, Doubling> = StoreEither::Sync(Doubling);
+    let r#async: StoreEither<Doubling, Doubling> = StoreEither::Async(Doubling);
+    assert_eq!(sync.load(5).await, Some(10));
+    assert_eq!(r#async.load(5).await, Some(15));
+}
@@ -0,0 +1,34 @@
+#[maybe_async_cfg2::maybe(sync(all()), async(all()), dyn(all(), send = "?Send"))]
+trait Plugin {
+    async fn run(&self, input: &str) -> usize;
+
+    async fn run_twice(&self, input: &str) -> usize {
+        self.run(input).await * 2
+    }
+}
+
+struct Len;
+
+impl PluginSync for Len {
+    fn run(&self, input: &str) -> usize {
+        input.len()
+    }
+}
+
+impl PluginAsync for Len {
+    async fn run(&self, input: &str) -> usize {
+        input.len()
+    }
+}
+
+#[test]
+fn sync_variant() {
+    assert_eq!(PluginSync::run_twice(&Len, "abc"), 6);
+}
+
+#[tokio::test]
+async fn dyn_variant_through_blanket_impl() {
+    let plugins: Vec<Box<dyn PluginDyn>> = vec![Box::new(Len)];
+    assert_eq!(plugins[0].run("abcd").await, 4);
+    assert_eq!(plugins[0].run_twice("abcd").await, 8);
+}
@@ -0,0 +1,8 @@
+#[test]
+fn force_variant() {
+    // read by the macro while trybuild compiles the cases
+    std::env::set_var("MAYBE_ASYNC_FORCE_VARIANT", "sync");
+    let t = trybuild::TestCases::new();
+    t.pass("tests/force_variant/sync_without_cfg.rs");
+    t.compile_fail("tests/force_variant/unknown_key.rs");
+}
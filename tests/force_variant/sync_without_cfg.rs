@@ -0,0 +1,9 @@
+// the features are not enabled, only the forced variant is emitted and without its condition
+#[maybe_async_cfg2::maybe(sync(feature = "use_sync"), async(feature = "use_async"))]
+async fn answer() -> u32 {
+    async { 42 }.await
+}
+
+fn main() {
+    assert_eq!(answer_sync(), 42);
+}
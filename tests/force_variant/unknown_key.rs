@@ -0,0 +1,9 @@
+#[maybe_async_cfg2::maybe(
+    variant(key = "blocking", mode = "sync", feature = "use_blocking"),
+    variant(key = "nonblocking", mode = "async", feature = "use_nonblocking"),
+)]
+async fn answer() -> u32 {
+    42
+}
+
+fn main() {}
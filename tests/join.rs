@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+
+#[maybe_async_cfg2::maybe(sync(all()), async(all()))]
+async fn record(log: &Mutex<Vec<u32>>, n: u32) -> u32 {
+    log.lock().unwrap().push(n);
+    n * 10
+}
+
+#[maybe_async_cfg2::maybe(sync(all()), async(all()))]
+async fn check(n: u32) -> Result<u32, String> {
+    if n < 10 {
+        Ok(n)
+    } else {
+        Err(format!("too large: {}", n))
+    }
+}
+
+#[maybe_async_cfg2::maybe(idents(record(fn)), sync(all()), async(all()))]
+async fn both(log: &Mutex<Vec<u32>>) -> (u32, u32) {
+    tokio::join!(record(log, 1), record(log, 2))
+}
+
+#[maybe_async_cfg2::maybe(idents(check(fn)), sync(all()), async(all()))]
+async fn all_small(a: u32, b: u32) -> Result<(u32, u32), String> {
+    tokio::try_join!(check(a), check(b))
+}
+
+#[test]
+fn sync_join_is_sequential() {
+    let log = Mutex::new(Vec::new());
+    assert_eq!(both_sync(&log), (10, 20));
+    assert_eq!(*log.lock().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn sync_try_join_stops_at_the_first_error() {
+    assert_eq!(all_small_sync(2, 4), Ok((2, 4)));
+    assert_eq!(all_small_sync(30, 50), Err("too large: 30".to_string()));
+}
+
+#[tokio::test]
+async fn async_join_is_kept() {
+    let log = Mutex::new(Vec::new());
+    assert_eq!(both_async(&log).await, (10, 20));
+    assert_eq!(all_small_async(2, 4).await, Ok((2, 4)));
+}
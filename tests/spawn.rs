@@ -0,0 +1,33 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+#[maybe_async_cfg2::maybe(sync(all()), async(all()))]
+async fn add(counter: Arc<AtomicU32>, n: u32) -> u32 {
+    counter.fetch_add(n, Ordering::SeqCst) + n
+}
+
+#[maybe_async_cfg2::maybe(idents(add(fn)), sync(all(), spawn), async(all()))]
+async fn spawn_both(counter: Arc<AtomicU32>) -> u32 {
+    tokio::spawn(add(counter.clone(), 10)).await.unwrap();
+    let first = tokio::spawn(add(counter.clone(), 1));
+    let second = tokio::spawn(async move { add(counter, 2).await })
+        .await
+        .unwrap();
+    first.await.unwrap() + second
+}
+
+#[test]
+fn sync_variant_joins_threads() {
+    let counter = Arc::new(AtomicU32::new(0));
+    let total = spawn_both_sync(counter.clone());
+    assert_eq!(counter.load(Ordering::SeqCst), 13);
+    assert!(total == 24 || total == 25);
+}
+
+#[tokio::test]
+async fn async_variant_joins_tasks() {
+    let counter = Arc::new(AtomicU32::new(0));
+    let total = spawn_both_async(counter.clone()).await;
+    assert_eq!(counter.load(Ordering::SeqCst), 13);
+    assert!(total == 24 || total == 25);
+}
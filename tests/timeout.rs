@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use tokio::time::error::Elapsed;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Timeout,
+    Failed(String),
+}
+
+impl From<Elapsed> for Error {
+    fn from(_: Elapsed) -> Self {
+        Error::Timeout
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Failed(message)
+    }
+}
+
+pub fn with_timeout<T>(limit: Duration, call: impl FnOnce() -> T) -> Result<T, Error> {
+    let start = std::time::Instant::now();
+    let value = call();
+    match start.elapsed() <= limit {
+        true => Ok(value),
+        false => Err(Error::Timeout),
+    }
+}
+
+#[maybe_async_cfg2::maybe(sync(all()), async(all()))]
+async fn slow(delay: u64) -> u32 {
+    tokio::time::sleep(Duration::from_millis(delay)).await;
+    42
+}
+
+#[maybe_async_cfg2::maybe(idents(slow(fn)), sync(all(), timeout), async(all()))]
+async fn inferred(delay: u64) -> Result<u32, Elapsed> {
+    let value = tokio::time::timeout(Duration::from_millis(50), slow(delay)).await?;
+    Ok(value + 1)
+}
+
+#[maybe_async_cfg2::maybe(
+    idents(slow(fn)),
+    sync(all(), timeout = "ok(crate::Error)"),
+    async(all())
+)]
+async fn explicit(delay: u64) -> Result<u32, Error> {
+    let value = tokio::time::timeout(Duration::from_millis(50), slow(delay)).await?;
+    Ok(value)
+}
+
+#[maybe_async_cfg2::maybe(
+    idents(slow(fn)),
+    sync(all(), timeout = "crate::with_timeout"),
+    async(all())
+)]
+async fn helper(delay: u64) -> Result<u32, Error> {
+    Ok(tokio::time::timeout(Duration::from_millis(50), slow(delay)).await?)
+}
+
+#[test]
+fn sync_timeouts_are_dropped_or_mapped() {
+    assert_eq!(inferred_sync(100), Ok(43));
+    assert_eq!(explicit_sync(100), Ok(42));
+    assert_eq!(helper_sync(0), Ok(42));
+    assert_eq!(helper_sync(100), Err(Error::Timeout));
+}
+
+#[tokio::test]
+async fn async_timeouts_are_kept() {
+    assert_eq!(inferred_async(0).await, Ok(43));
+    assert!(inferred_async(100).await.is_err());
+    assert_eq!(explicit_async(100).await, Err(Error::Timeout));
+    assert_eq!(helper_async(0).await, Ok(42));
+}
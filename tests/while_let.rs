@@ -0,0 +1,45 @@
+#[maybe_async_cfg2::maybe(sync(all()), async(all()))]
+struct Countdown(u32);
+
+impl Iterator for CountdownSync {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.0 = self.0.checked_sub(1)?;
+        Some(self.0)
+    }
+}
+
+impl CountdownAsync {
+    async fn next(&mut self) -> Option<u32> {
+        tokio::task::yield_now().await;
+        self.0 = self.0.checked_sub(1)?;
+        Some(self.0)
+    }
+}
+
+#[maybe_async_cfg2::maybe(idents(Countdown), sync(all()), async(all()))]
+async fn drain(countdown: &mut Countdown) -> Vec<u32> {
+    let mut items = Vec::new();
+    'outer: while let Some(x) = countdown.next().await {
+        if x == 1 {
+            break 'outer;
+        }
+        items.push(x);
+    }
+    items
+}
+
+#[test]
+fn sync_variant_iterates() {
+    let mut countdown = CountdownSync(4);
+    assert_eq!(drain_sync(&mut countdown), vec![3, 2]);
+    assert_eq!(countdown.next(), Some(0));
+}
+
+#[tokio::test]
+async fn async_variant_polls() {
+    let mut countdown = CountdownAsync(4);
+    assert_eq!(drain_async(&mut countdown).await, vec![3, 2]);
+    assert_eq!(countdown.next().await, Some(0));
+}